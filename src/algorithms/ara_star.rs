@@ -0,0 +1,160 @@
+use priority_queue::PriorityQueue;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use crate::{
+    algorithms::{
+        astar::{is_admissible, remaining_cost, USE_MANHATTAN},
+        route::route_from_predecessors,
+        Parameter, ParameterValues, PathfindingAlgorithm, Query, RunOutput, SearchGraph,
+    },
+    models::{Edge, Node},
+    utils::FloatOrd,
+};
+
+const INITIAL_EPSILON: Parameter = Parameter::number(
+    "initial_epsilon",
+    "Initial inflation",
+    "Weight of the remaining cost estimate for the first, quickly found route",
+    3.0,
+    (1.0, 5.0, 0.1),
+);
+const EPSILON_STEP: Parameter = Parameter::number(
+    "epsilon_step",
+    "Inflation step",
+    "How much the weight decreases before each better route is searched for",
+    0.5,
+    (0.1, 2.0, 0.1),
+);
+
+/// Anytime Repairing A*: a weighted A* whose weight decreases down to one, each search
+/// reusing what the previous ones found, so that better and better routes come out.
+///
+/// Every route found is kept in the solutions along with its optimality bound.
+pub struct AraStar;
+
+impl PathfindingAlgorithm for AraStar {
+    fn id(&self) -> &'static str {
+        "AraStar"
+    }
+
+    fn name(&self) -> &'static str {
+        "ARA Star"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[INITIAL_EPSILON, EPSILON_STEP, USE_MANHATTAN]
+    }
+
+    fn run(&self, query: &Query, graph: &SearchGraph, parameters: &ParameterValues) -> RunOutput {
+        let mut epsilon = parameters.number_of(&INITIAL_EPSILON).max(1.0);
+        let step = parameters.number_of(&EPSILON_STEP);
+        let remaining_cost = remaining_cost(query, parameters);
+        let end = query.end;
+
+        let mut passed_edges = HashSet::new();
+        let mut solutions = Vec::new();
+        // (cost, seconds since the departure)
+        let mut costs: HashMap<&Node, (f32, f32)> = HashMap::from([(query.start, (0.0, 0.0))]);
+        let mut predecessors: HashMap<&Node, &Edge> = HashMap::new();
+        let mut open: PriorityQueue<&Node, Reverse<FloatOrd<f32>>> = PriorityQueue::new();
+        let mut closed: HashSet<&Node> = HashSet::new();
+        // closed nodes reached more cheaply, expanded again by the next search
+        let mut inconsistent: HashSet<&Node> = HashSet::new();
+        open.push(
+            query.start,
+            Reverse(FloatOrd(epsilon * remaining_cost(query.start))),
+        );
+
+        loop {
+            // improves the route until no open node can lead to a cheaper one
+            while let Some((_, &Reverse(FloatOrd(lowest_key)))) = open.peek() {
+                if costs.get(end).is_some_and(|&(cost, _)| cost <= lowest_key) {
+                    break;
+                }
+                let (node, _) = open.pop().unwrap();
+                closed.insert(node);
+                if !query.control.step() {
+                    break;
+                }
+
+                let (cost, elapsed) = costs[node];
+                for edge in graph.edges_from(node) {
+                    let Some((edge_cost, duration)) =
+                        graph.edge_cost(edge, query.cost_model, query.departure + elapsed)
+                    else {
+                        continue;
+                    };
+                    if query.mark_passed_edges {
+                        passed_edges.insert(edge.clone());
+                    }
+
+                    let new_cost = cost + edge_cost.max(0.0);
+                    if costs.get(&edge.to).is_some_and(|&(c, _)| c <= new_cost) {
+                        continue;
+                    }
+                    costs.insert(&edge.to, (new_cost, elapsed + duration));
+                    predecessors.insert(&edge.to, edge);
+                    if closed.contains(&edge.to) {
+                        inconsistent.insert(&edge.to);
+                    } else {
+                        let key = new_cost + epsilon * remaining_cost(&edge.to);
+                        open.push(&edge.to, Reverse(FloatOrd(key)));
+                    }
+                }
+            }
+
+            if query.control.is_cancelled() {
+                break;
+            }
+            // the end is unreachable
+            let Some(&(end_cost, _)) = costs.get(end) else {
+                break;
+            };
+            let lowest_estimate = open
+                .iter()
+                .map(|(node, _)| *node)
+                .chain(inconsistent.iter().copied())
+                .map(|node| costs[node].0 + remaining_cost(node))
+                .fold(f32::INFINITY, f32::min);
+            let bound = if end_cost <= lowest_estimate {
+                1.0
+            } else {
+                (end_cost / lowest_estimate).min(epsilon)
+            };
+            match solutions.last_mut() {
+                Some((last_bound, last_cost)) if *last_cost <= end_cost => {
+                    *last_bound = Some(bound)
+                }
+                _ => solutions.push((Some(bound), end_cost)),
+            }
+            if bound <= 1.0 {
+                break;
+            }
+
+            epsilon = (epsilon - step).max(1.0);
+            let pending = open
+                .iter()
+                .map(|(node, _)| *node)
+                .chain(inconsistent.drain())
+                .collect::<Vec<&Node>>();
+            pending.into_iter().for_each(|node| {
+                let key = costs[node].0 + epsilon * remaining_cost(node);
+                open.push(node, Reverse(FloatOrd(key)));
+            });
+            closed.clear();
+        }
+
+        // the bounds only hold when the estimate never overestimates
+        if !is_admissible(query, graph, parameters) {
+            solutions.iter_mut().for_each(|(bound, _)| *bound = None);
+        }
+        let total_cost = costs.get(end).map(|&(cost, _)| cost);
+        let mut output =
+            route_from_predecessors(query, graph, &predecessors, total_cost, passed_edges);
+        output.solutions = solutions;
+        output
+    }
+}
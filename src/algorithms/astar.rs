@@ -0,0 +1,142 @@
+use crate::{
+    algorithms::{
+        best_first_search, heuristic, route::with_bound, Parameter, ParameterValues,
+        PathfindingAlgorithm, Query, RunOutput, SearchGraph,
+    },
+    models::Node,
+    utils::FloatOrd,
+};
+
+// relative slack allowed when comparing the arc costs with the estimates, for the rounding
+// of the lengths in the files
+const ADMISSIBILITY_TOLERANCE: f32 = 1e-4;
+
+pub(super) const USE_MANHATTAN: Parameter = Parameter::flag(
+    "use_manhattan",
+    "Manhattan distance",
+    "Estimate the remaining cost with the Manhattan distance instead of the Euclidean one",
+    true,
+);
+const HEURISTIC_WEIGHT: Parameter = Parameter::number(
+    "heuristic_weight",
+    "A* weight",
+    "How much the remaining cost estimate weighs against the cost so far",
+    1.5,
+    (1.0, 5.0, 0.1),
+);
+
+/// Estimate of the cost from a node to the end, in the unit of the cost metric.
+pub fn remaining_cost<'a>(
+    query: &'a Query,
+    parameters: &ParameterValues,
+) -> impl Fn(&Node) -> f32 + 'a {
+    let use_manhattan = parameters.flag_of(&USE_MANHATTAN);
+    let scale = Some(FloatOrd(query.cost_model.heuristic_scale()));
+    move |node| heuristic(node, query.end, scale, use_manhattan)
+}
+
+/// Whether [`remaining_cost`] never overestimates on this graph, which the optimality
+/// bounds of the searches using it rely on.
+///
+/// The estimate is a distance between the nodes on the canvas, scaled, so it holds for whole
+/// routes by the triangle inequality as soon as no arc costs less than the estimate between
+/// its ends.
+pub fn is_admissible(query: &Query, graph: &SearchGraph, parameters: &ParameterValues) -> bool {
+    let use_manhattan = parameters.flag_of(&USE_MANHATTAN);
+    let scale = Some(FloatOrd(query.cost_model.heuristic_scale()));
+    graph.arcs().all(|edge| {
+        let estimate = heuristic(&edge.from, &edge.to, scale, use_manhattan);
+        graph
+            .lowest_edge_cost(edge, query.cost_model)
+            .is_none_or(|cost| cost >= estimate * (1. - ADMISSIBILITY_TOLERANCE))
+    })
+}
+
+// `bound` when the estimate is shown not to overestimate, no guarantee otherwise
+fn admissible_bound(
+    query: &Query,
+    graph: &SearchGraph,
+    parameters: &ParameterValues,
+    bound: f32,
+) -> Option<f32> {
+    is_admissible(query, graph, parameters).then_some(bound)
+}
+
+/// Expands the node with the lowest cost so far plus remaining cost estimate.
+pub struct AStar;
+
+impl PathfindingAlgorithm for AStar {
+    fn id(&self) -> &'static str {
+        "AStar"
+    }
+
+    fn name(&self) -> &'static str {
+        "A Star"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[USE_MANHATTAN]
+    }
+
+    fn run(&self, query: &Query, graph: &SearchGraph, parameters: &ParameterValues) -> RunOutput {
+        let remaining_cost = remaining_cost(query, parameters);
+        let output = best_first_search(query, graph, |cost, node| {
+            cost + FloatOrd(remaining_cost(node))
+        });
+        with_bound(output, admissible_bound(query, graph, parameters, 1.0))
+    }
+}
+
+/// A* with the remaining cost estimate inflated, which expands fewer nodes for a route
+/// costing at most the weight times the best one.
+pub struct WeightedAStar;
+
+impl PathfindingAlgorithm for WeightedAStar {
+    // the id of the former hybrid variant, which did the same
+    fn id(&self) -> &'static str {
+        "HybridAStar"
+    }
+
+    fn name(&self) -> &'static str {
+        "Weighted A Star"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[HEURISTIC_WEIGHT, USE_MANHATTAN]
+    }
+
+    fn run(&self, query: &Query, graph: &SearchGraph, parameters: &ParameterValues) -> RunOutput {
+        let weight = parameters.number_of(&HEURISTIC_WEIGHT);
+        let remaining_cost = remaining_cost(query, parameters);
+        let output = best_first_search(query, graph, |cost, node| {
+            cost + FloatOrd(weight * remaining_cost(node))
+        });
+        with_bound(
+            output,
+            admissible_bound(query, graph, parameters, weight.max(1.0)),
+        )
+    }
+}
+
+/// Expands the node closest to the end first, whatever it took to reach it.
+pub struct GreedyBestFirst;
+
+impl PathfindingAlgorithm for GreedyBestFirst {
+    fn id(&self) -> &'static str {
+        "GreedyBestFirst"
+    }
+
+    fn name(&self) -> &'static str {
+        "Greedy best-first"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[USE_MANHATTAN]
+    }
+
+    fn run(&self, query: &Query, graph: &SearchGraph, parameters: &ParameterValues) -> RunOutput {
+        let remaining_cost = remaining_cost(query, parameters);
+        let output = best_first_search(query, graph, |_, node| FloatOrd(remaining_cost(node)));
+        with_bound(output, None)
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    algorithms::{
+        astar::{remaining_cost, USE_MANHATTAN},
+        route::{route_from_predecessors, with_bound},
+        Parameter, ParameterValues, PathfindingAlgorithm, Query, RunOutput, SearchGraph,
+    },
+    models::{Edge, Node},
+    utils::FloatOrd,
+};
+
+const BEAM_WIDTH: Parameter = Parameter::number(
+    "beam_width",
+    "Beam width",
+    "How many nodes are kept at each depth, the others are dropped",
+    20.0,
+    (1.0, 200.0, 1.0),
+);
+
+/// Expands the nodes depth by depth, keeping only the most promising ones at each depth.
+/// Uses little memory but may miss the best route, or any route.
+pub struct BeamSearch;
+
+impl PathfindingAlgorithm for BeamSearch {
+    fn id(&self) -> &'static str {
+        "BeamSearch"
+    }
+
+    fn name(&self) -> &'static str {
+        "Beam search"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[BEAM_WIDTH, USE_MANHATTAN]
+    }
+
+    fn run(&self, query: &Query, graph: &SearchGraph, parameters: &ParameterValues) -> RunOutput {
+        let width = (parameters.number_of(&BEAM_WIDTH) as usize).max(1);
+        let remaining_cost = remaining_cost(query, parameters);
+
+        let mut passed_edges = HashSet::new();
+        // (cost, seconds since the departure) of the nodes kept in a beam
+        let mut costs: HashMap<&Node, (f32, f32)> = HashMap::from([(query.start, (0.0, 0.0))]);
+        let mut predecessors: HashMap<&Node, &Edge> = HashMap::new();
+        let mut beam = vec![query.start];
+
+        while !beam.is_empty() && !costs.contains_key(query.end) && !query.control.is_cancelled() {
+            let mut candidates: HashMap<&Node, (f32, f32, &Edge)> = HashMap::new();
+            for node in beam {
+                query.control.step();
+                let (cost, elapsed) = costs[node];
+                for edge in graph.edges_from(node) {
+                    let Some((edge_cost, duration)) =
+                        graph.edge_cost(edge, query.cost_model, query.departure + elapsed)
+                    else {
+                        continue;
+                    };
+                    if query.mark_passed_edges {
+                        passed_edges.insert(edge.clone());
+                    }
+
+                    let new_cost = cost + edge_cost.max(0.0);
+                    let is_better = |best: f32| new_cost < best;
+                    if costs
+                        .get(&edge.to)
+                        .is_some_and(|&(best, _)| !is_better(best))
+                        || candidates
+                            .get(&edge.to)
+                            .is_some_and(|&(best, ..)| !is_better(best))
+                    {
+                        continue;
+                    }
+                    candidates.insert(&edge.to, (new_cost, elapsed + duration, edge));
+                }
+            }
+
+            // only the kept nodes count as reached, the dropped ones may be reached again
+            let mut candidates = candidates.into_iter().collect::<Vec<_>>();
+            candidates.sort_by_key(|(node, (cost, ..))| FloatOrd(cost + remaining_cost(node)));
+            candidates.truncate(width);
+            beam = candidates
+                .into_iter()
+                .map(|(node, (cost, elapsed, edge))| {
+                    costs.insert(node, (cost, elapsed));
+                    predecessors.insert(node, edge);
+                    node
+                })
+                .collect();
+        }
+
+        let total_cost = costs.get(query.end).map(|&(cost, _)| cost);
+        let output = route_from_predecessors(query, graph, &predecessors, total_cost, passed_edges);
+        with_bound(output, None)
+    }
+}
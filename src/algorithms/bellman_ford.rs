@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    algorithms::{
+        route::{route_from_predecessors, with_bound},
+        ParameterValues, PathfindingAlgorithm, Query, RunOutput, SearchControl, SearchGraph,
+    },
+    models::{Edge, Node},
+};
+
+/// Relaxes every arc until nothing changes, which finds the best route when some arcs
+/// cost less than nothing and reports the negative cycles that leave no best route.
+///
+/// The arcs are costed as entered at the departure, the relaxations being repeated
+/// regardless of when an arc is reached. Turn restrictions are not applied.
+pub struct BellmanFord;
+
+impl PathfindingAlgorithm for BellmanFord {
+    fn id(&self) -> &'static str {
+        "BellmanFord"
+    }
+
+    fn name(&self) -> &'static str {
+        "Bellman-Ford"
+    }
+
+    fn handles_negative_costs(&self) -> bool {
+        true
+    }
+
+    fn run(&self, query: &Query, graph: &SearchGraph, _: &ParameterValues) -> RunOutput {
+        let arcs = costed_arcs(query, graph);
+        let mut costs = HashMap::from([(query.start, 0.0)]);
+        let mut predecessors = HashMap::new();
+        match bellman_ford(&arcs, &mut costs, &mut predecessors, query.control) {
+            Ok(()) => {}
+            // the predecessors of a half-relaxed run may still loop
+            Err(Unsettled::Cancelled) => return RunOutput::default(),
+            Err(Unsettled::NegativeCycle(cycle)) => {
+                return RunOutput {
+                    negative_cycle: cycle,
+                    ..Default::default()
+                }
+            }
+        }
+
+        let total_cost = costs.get(query.end).copied();
+        let passed_edges = passed_arcs(query, &arcs, &costs);
+        let output = route_from_predecessors(query, graph, &predecessors, total_cost, passed_edges);
+        with_bound(output, exact_bound(query, graph))
+    }
+}
+
+// the arcs are costed at the departure and the turns are ignored, the route is only the best
+// one without traffic profiles and turn restrictions
+pub(super) fn exact_bound(query: &Query, graph: &SearchGraph) -> Option<f32> {
+    let is_exact = !graph.has_traffic() && !query.use_turn_restrictions;
+    is_exact.then_some(1.0)
+}
+
+// the open arcs with their cost at the departure
+pub(super) fn costed_arcs<'a>(query: &Query, graph: &'a SearchGraph) -> Vec<(&'a Edge, f32)> {
+    graph
+        .arcs()
+        .filter_map(|edge| {
+            let (cost, _) = graph.edge_cost(edge, query.cost_model, query.departure)?;
+            Some((edge, cost))
+        })
+        .collect()
+}
+
+// why the relaxations stopped before the costs settled
+pub(super) enum Unsettled {
+    Cancelled,
+    // the arcs of the cycle, in order
+    NegativeCycle(Vec<Edge>),
+}
+
+/// Relaxes the arcs from the nodes initially in `costs` until nothing changes,
+/// which never happens with a negative cycle. Each round is a step.
+pub(super) fn bellman_ford<'a>(
+    arcs: &[(&'a Edge, f32)],
+    costs: &mut HashMap<&'a Node, f32>,
+    predecessors: &mut HashMap<&'a Node, &'a Edge>,
+    control: &SearchControl,
+) -> Result<(), Unsettled> {
+    let node_count = arcs
+        .iter()
+        .flat_map(|(edge, _)| [&edge.from, &edge.to])
+        .collect::<HashSet<&Node>>()
+        .len();
+
+    // a route has at most node_count - 1 arcs, so a relaxation in the last round means a cycle
+    let mut last_relaxed = None;
+    for _ in 0..=node_count {
+        if !control.step() {
+            return Err(Unsettled::Cancelled);
+        }
+        last_relaxed = None;
+        for &(edge, cost) in arcs {
+            let Some(&from_cost) = costs.get(&edge.from) else {
+                continue;
+            };
+            let new_cost = from_cost + cost;
+            if costs
+                .get(&edge.to)
+                .is_none_or(|&to_cost| new_cost < to_cost)
+            {
+                costs.insert(&edge.to, new_cost);
+                predecessors.insert(&edge.to, edge);
+                last_relaxed = Some(&edge.to);
+            }
+        }
+        if last_relaxed.is_none() {
+            return Ok(());
+        }
+    }
+
+    // walking back enough predecessors is bound to end up on the cycle
+    let mut node = last_relaxed.unwrap();
+    for _ in 0..node_count {
+        node = &predecessors[node].from;
+    }
+    let mut cycle = Vec::new();
+    let mut current = node;
+    loop {
+        let edge = predecessors[current];
+        cycle.push(edge.clone());
+        current = &edge.from;
+        if current == node {
+            break;
+        }
+    }
+    cycle.reverse();
+
+    Err(Unsettled::NegativeCycle(cycle))
+}
+
+// the arcs leaving the nodes that have been reached, when the passed edges are marked
+pub(super) fn passed_arcs(
+    query: &Query,
+    arcs: &[(&Edge, f32)],
+    costs: &HashMap<&Node, f32>,
+) -> HashSet<Edge> {
+    if !query.mark_passed_edges {
+        return HashSet::new();
+    }
+    arcs.iter()
+        .filter(|(edge, _)| costs.contains_key(&edge.from))
+        .map(|(edge, _)| (*edge).clone())
+        .collect()
+}
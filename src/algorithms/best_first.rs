@@ -0,0 +1,345 @@
+use egui::Pos2;
+use priority_queue::PriorityQueue;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{
+    algorithms::{Query, RunOutput, SearchGraph, Solutions},
+    models::{Edge, Node},
+    utils::{euclidean_distance, manhattan_distance, FloatOrd},
+};
+
+// this is an arbitrary value found by trial and error
+const MULTIPLICITY_BASE: f32 = 13_000.;
+
+/// Expands the frontier entry with the lowest `priority`, given the cost of reaching a node
+/// and the node itself, until the end is reached.
+///
+/// The search runs over the arcs instead of the nodes when the query uses turn restrictions.
+/// Negative costs are counted as nothing, otherwise the search could never settle, and the
+/// route is then recorded without an optimality bound.
+pub fn best_first_search(
+    query: &Query,
+    graph: &SearchGraph,
+    priority: impl Fn(FloatOrd<f32>, &Node) -> FloatOrd<f32>,
+) -> RunOutput {
+    if query.use_turn_restrictions {
+        return edge_based_search(query, graph, priority);
+    }
+
+    let Query {
+        start,
+        end,
+        cost_model,
+        departure,
+        mark_passed_edges,
+        ..
+    } = *query;
+
+    let mut passed_edges = HashSet::new();
+    let mut total_cost: f32 = 0.0;
+    let mut has_negative_costs = false;
+
+    let mut frontier: PriorityQueue<NodeData, Reverse<FloatOrd<f32>>> = PriorityQueue::new();
+    frontier.push(NodeData::from(start.clone()), Reverse(FloatOrd(0.0)));
+
+    let mut came_from: HashMap<NodeData, Option<NodeData>> = HashMap::new();
+    let mut cost_so_far: HashMap<NodeData, FloatOrd<f32>> = HashMap::new();
+    // seconds since the departure, the travel times depend on it
+    let mut time_so_far: HashMap<NodeData, f32> = HashMap::new();
+
+    came_from.insert(NodeData::from(start.clone()), None);
+    cost_so_far.insert(NodeData::from(start.clone()), FloatOrd(0.0));
+    time_so_far.insert(NodeData::from(start.clone()), 0.0);
+
+    while !frontier.is_empty() {
+        let current = frontier.pop().unwrap().0;
+        if !query.control.step() {
+            break;
+        }
+
+        if current.node == *end {
+            total_cost = cost_so_far.get(&current).map(|f| f.0).unwrap_or(0.0);
+            break;
+        }
+
+        let elapsed = *time_so_far.get(&current).unwrap();
+        for next in graph.edges_from(&current.node) {
+            // closed roads are never explored
+            let Some((edge_cost, duration)) =
+                graph.edge_cost(next, cost_model, departure + elapsed)
+            else {
+                continue;
+            };
+            has_negative_costs |= edge_cost < 0.0;
+            let edge_cost = edge_cost.max(0.0);
+
+            if mark_passed_edges {
+                passed_edges.insert(next.clone());
+            }
+
+            let new_cost = *cost_so_far.get(&current).unwrap() + FloatOrd(edge_cost);
+
+            let next_node_data = NodeData::from(next.to.clone());
+            if !cost_so_far.contains_key(&next_node_data)
+                || new_cost < *cost_so_far.get(&next_node_data).unwrap()
+            {
+                cost_so_far.insert(next_node_data.clone(), new_cost);
+                time_so_far.insert(next_node_data.clone(), elapsed + duration);
+
+                frontier.push(
+                    next_node_data.clone(),
+                    Reverse(priority(new_cost, &next.to)),
+                );
+                came_from.insert(next_node_data.clone(), Some(current.clone()));
+            }
+        }
+    }
+
+    let path = reconstruct_path(&came_from, start, end);
+    let mut route_duration = 0.0;
+    let route_edges = path
+        .windows(2)
+        .map(|pair| {
+            let (edge, duration) =
+                graph.route_edge(&pair[0], &pair[1], cost_model, departure + route_duration);
+            route_duration += duration;
+            edge
+        })
+        .collect::<Vec<Edge>>();
+    let route_length = route_edges.iter().map(|edge| edge.length).sum();
+    let selected_edges = route_edges.into_iter().collect();
+
+    RunOutput {
+        path,
+        selected_edges,
+        passed_edges,
+        total_cost,
+        route_length,
+        route_duration,
+        solutions: unbounded(has_negative_costs, total_cost),
+        ..Default::default()
+    }
+}
+
+// the solution of a search that counted negative costs as nothing, whose route may not be the
+// best one; none otherwise, the caller records it with its bound
+fn unbounded(has_negative_costs: bool, total_cost: f32) -> Solutions {
+    if has_negative_costs {
+        vec![(None, total_cost)]
+    } else {
+        vec![]
+    }
+}
+
+/// Same search as [`best_first_search`] over the arcs instead of the nodes,
+/// so that the turn restrictions and penalties between two arcs can be applied.
+fn edge_based_search(
+    query: &Query,
+    graph: &SearchGraph,
+    priority: impl Fn(FloatOrd<f32>, &Node) -> FloatOrd<f32>,
+) -> RunOutput {
+    let Query {
+        start,
+        end,
+        cost_model,
+        departure,
+        mark_passed_edges,
+        allow_u_turns,
+        ..
+    } = *query;
+
+    if start == end {
+        return RunOutput {
+            path: vec![start.clone()],
+            ..Default::default()
+        };
+    }
+
+    let mut passed_edges = HashSet::new();
+    let mut has_negative_costs = false;
+    let mut frontier: PriorityQueue<Edge, Reverse<FloatOrd<f32>>> = PriorityQueue::new();
+    let mut came_from: HashMap<Edge, Option<Edge>> = HashMap::new();
+    let mut cost_so_far: HashMap<Edge, FloatOrd<f32>> = HashMap::new();
+    // seconds since the departure once the arc has been driven
+    let mut time_so_far: HashMap<Edge, f32> = HashMap::new();
+
+    for next in graph.edges_from(start) {
+        let Some((edge_cost, duration)) = graph.edge_cost(next, cost_model, departure) else {
+            continue;
+        };
+        if mark_passed_edges {
+            passed_edges.insert(next.clone());
+        }
+        has_negative_costs |= edge_cost < 0.0;
+        let new_cost = FloatOrd(edge_cost.max(0.0));
+        if cost_so_far.get(next).is_some_and(|cost| *cost <= new_cost) {
+            continue;
+        }
+        cost_so_far.insert(next.clone(), new_cost);
+        time_so_far.insert(next.clone(), duration);
+        came_from.insert(next.clone(), None);
+        frontier.push(next.clone(), Reverse(priority(new_cost, &next.to)));
+    }
+
+    let mut last_edge = None;
+    while let Some((current, _)) = frontier.pop() {
+        if !query.control.step() {
+            break;
+        }
+        if current.to == *end {
+            last_edge = Some(current);
+            break;
+        }
+
+        let elapsed = *time_so_far.get(&current).unwrap();
+        for next in graph.edges_from(&current.to) {
+            let Some(penalty) = graph.turn_penalty(&current, next, allow_u_turns) else {
+                continue;
+            };
+            let Some((edge_cost, duration)) =
+                graph.edge_cost(next, cost_model, departure + elapsed + penalty)
+            else {
+                continue;
+            };
+
+            if mark_passed_edges {
+                passed_edges.insert(next.clone());
+            }
+
+            let turn_cost = cost_model.cost(0.0, penalty) + edge_cost;
+            has_negative_costs |= turn_cost < 0.0;
+            let new_cost = *cost_so_far.get(&current).unwrap() + FloatOrd(turn_cost.max(0.0));
+            if cost_so_far.get(next).is_some_and(|cost| *cost <= new_cost) {
+                continue;
+            }
+            cost_so_far.insert(next.clone(), new_cost);
+            time_so_far.insert(next.clone(), elapsed + penalty + duration);
+            came_from.insert(next.clone(), Some(current.clone()));
+
+            frontier.push(next.clone(), Reverse(priority(new_cost, &next.to)));
+        }
+    }
+
+    // the end is unreachable
+    let Some(last_edge) = last_edge else {
+        return RunOutput {
+            passed_edges,
+            ..Default::default()
+        };
+    };
+    let total_cost = cost_so_far.get(&last_edge).unwrap().0;
+    let route_duration = *time_so_far.get(&last_edge).unwrap();
+
+    let mut route_edges = vec![last_edge.clone()];
+    let mut current = last_edge;
+    while let Some(previous) = came_from.get(&current).unwrap().clone() {
+        route_edges.push(previous.clone());
+        current = previous;
+    }
+    route_edges.reverse();
+
+    let path = std::iter::once(start.clone())
+        .chain(route_edges.iter().map(|edge| edge.to.clone()))
+        .collect();
+    let route_length = route_edges.iter().map(|edge| edge.length).sum();
+
+    RunOutput {
+        path,
+        selected_edges: route_edges.into_iter().collect(),
+        passed_edges,
+        total_cost,
+        route_length,
+        route_duration,
+        solutions: unbounded(has_negative_costs, total_cost),
+        ..Default::default()
+    }
+}
+
+fn reconstruct_path(
+    came_from: &HashMap<NodeData, Option<NodeData>>,
+    start: &Node,
+    end: &Node,
+) -> Vec<Node> {
+    // the end is unreachable, e.g. after arcs have been deleted
+    if !came_from.contains_key(&NodeData::from(end.clone())) {
+        return vec![];
+    }
+
+    let mut path = vec![end.clone()];
+    let mut current = NodeData::from(end.clone());
+
+    while current.node != *start {
+        let next = came_from.get(&current).unwrap().clone().unwrap();
+        path.push(next.node.clone());
+        current = next.clone();
+    }
+    path.reverse();
+
+    path
+}
+
+pub fn heuristic(
+    a: &Node,
+    b: &Node,
+    multiplier: Option<FloatOrd<f32>>,
+    use_manhattan: bool,
+) -> f32 {
+    // Apply a base multiplicity to make it more aggressive by default
+    // Also, the user can set 'simple' values like 1.5, 2.0, etc instead of 19_500.0, 26_000.0, etc
+    let mult = multiplier.unwrap_or(FloatOrd(1.0)).0 * MULTIPLICITY_BASE;
+    mult * distance(&a.position, &b.position, use_manhattan)
+}
+
+fn distance(a: &Pos2, b: &Pos2, use_manhattan: bool) -> f32 {
+    if use_manhattan {
+        manhattan_distance(a, b)
+    } else {
+        euclidean_distance(a, b)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NodeData {
+    node: Node,
+    cost: FloatOrd<f32>,
+}
+
+impl NodeData {
+    fn new(node: Node, cost: FloatOrd<f32>) -> Self {
+        Self { node, cost }
+    }
+
+    fn from(node: Node) -> Self {
+        Self::new(node, FloatOrd(0.0))
+    }
+}
+
+impl Hash for NodeData {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.node.hash(state);
+    }
+}
+
+impl PartialEq for NodeData {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+impl Eq for NodeData {}
+
+impl PartialOrd for NodeData {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NodeData {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.cost.cmp(&other.cost)
+    }
+}
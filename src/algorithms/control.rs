@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Shared with a search running in the background, to follow and stop it.
+#[derive(Debug, Default)]
+pub struct SearchControl {
+    cancelled: AtomicBool,
+    steps: AtomicUsize,
+}
+
+impl SearchControl {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Counts a step of the search, e.g. a node expanded, returns whether to go on.
+    pub fn step(&self) -> bool {
+        self.steps.fetch_add(1, Ordering::Relaxed);
+        !self.is_cancelled()
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,20 @@
+use crate::algorithms::{
+    best_first_search, route::with_bound, ParameterValues, PathfindingAlgorithm, Query, RunOutput,
+    SearchGraph,
+};
+
+pub struct Dijkstra;
+
+impl PathfindingAlgorithm for Dijkstra {
+    fn id(&self) -> &'static str {
+        "Dijkstra"
+    }
+
+    fn name(&self) -> &'static str {
+        "Dijkstra"
+    }
+
+    fn run(&self, query: &Query, graph: &SearchGraph, _: &ParameterValues) -> RunOutput {
+        with_bound(best_first_search(query, graph, |cost, _| cost), Some(1.0))
+    }
+}
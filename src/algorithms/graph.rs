@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::{
+    contexts::CostModel,
+    models::{Edge, Node},
+    scenario::Incident,
+    traffic::TrafficProfiles,
+    utils::FloatOrd,
+};
+
+// (from, via, to) node ids -> penalty in seconds, `None` when the turn is forbidden
+pub type TurnTable = HashMap<(String, String, String), Option<f32>>;
+
+/// What the searches run on besides the query.
+pub struct SearchGraph<'a> {
+    neighbors: &'a HashMap<Node, Vec<Edge>>,
+    incidents: &'a HashMap<Edge, Incident>,
+    traffic: Option<&'a TrafficProfiles>,
+    turns: &'a TurnTable,
+}
+
+impl<'a> SearchGraph<'a> {
+    pub fn new(
+        neighbors: &'a HashMap<Node, Vec<Edge>>,
+        incidents: &'a HashMap<Edge, Incident>,
+        traffic: Option<&'a TrafficProfiles>,
+        turns: &'a TurnTable,
+    ) -> Self {
+        Self {
+            neighbors,
+            incidents,
+            traffic,
+            turns,
+        }
+    }
+
+    /// Whether the travel times depend on the time of the day.
+    pub fn has_traffic(&self) -> bool {
+        self.traffic.is_some()
+    }
+
+    pub fn arcs(&self) -> impl Iterator<Item = &Edge> {
+        self.neighbors.values().flatten()
+    }
+
+    pub fn edges_from(&self, node: &Node) -> impl Iterator<Item = &Edge> {
+        // nodes without outgoing arcs are dead ends
+        self.neighbors.get(node).into_iter().flatten()
+    }
+
+    /// (cost, duration) of an arc entered `at` seconds after midnight, `None` when it is closed.
+    pub fn edge_cost(&self, edge: &Edge, cost_model: &CostModel, at: f32) -> Option<(f32, f32)> {
+        let duration = self
+            .traffic
+            .and_then(|traffic| traffic.travel_time(edge, at))
+            .unwrap_or_else(|| edge.duration());
+        let cost = cost_model.cost(edge.length, duration);
+
+        match self.incidents.get(edge) {
+            Some(incident) => Some((incident.apply(cost)?, duration)),
+            None => Some((cost, duration)),
+        }
+    }
+
+    /// The lowest cost of the arc at any time of the day, `None` when it is closed.
+    pub fn lowest_edge_cost(&self, edge: &Edge, cost_model: &CostModel) -> Option<f32> {
+        let (fastest, slowest) = self
+            .traffic
+            .and_then(|traffic| traffic.travel_time_range(edge))
+            .unwrap_or((edge.duration(), edge.duration()));
+        // the cost grows or shrinks with the duration depending on the sign of its weight
+        let cost = cost_model
+            .cost(edge.length, fastest)
+            .min(cost_model.cost(edge.length, slowest));
+
+        match self.incidents.get(edge) {
+            Some(incident) => incident.apply(cost),
+            None => Some(cost),
+        }
+    }
+
+    // the cheapest of the arcs between two consecutive nodes of the path
+    pub fn route_edge(
+        &self,
+        from: &Node,
+        to: &Node,
+        cost_model: &CostModel,
+        at: f32,
+    ) -> (Edge, f32) {
+        self.edges_from(from)
+            .filter(|edge| edge.to == *to)
+            .filter_map(|edge| Some((edge, self.edge_cost(edge, cost_model, at)?)))
+            .min_by_key(|(_, (cost, _))| FloatOrd(*cost))
+            .map(|(edge, (_, duration))| (edge.clone(), duration))
+            .unwrap()
+    }
+
+    /// Penalty in seconds of turning from `from` onto `to`, `None` when the turn is forbidden.
+    pub fn turn_penalty(&self, from: &Edge, to: &Edge, allow_u_turns: bool) -> Option<f32> {
+        let key = (from.from.id.clone(), from.to.id.clone(), to.to.id.clone());
+        if let Some(penalty) = self.turns.get(&key) {
+            return *penalty;
+        }
+
+        let is_u_turn = to.to == from.from;
+        let is_dead_end = self.edges_from(&from.to).all(|edge| edge.to == from.from);
+        if is_u_turn && !allow_u_turns && !is_dead_end {
+            return None;
+        }
+        Some(0.0)
+    }
+}
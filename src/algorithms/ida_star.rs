@@ -0,0 +1,156 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    algorithms::{
+        astar::{is_admissible, remaining_cost, USE_MANHATTAN},
+        route::{route_from_predecessors, with_bound},
+        Parameter, ParameterValues, PathfindingAlgorithm, Query, RunOutput, SearchGraph,
+    },
+    models::{Edge, Node},
+};
+
+const EXPANSION_LIMIT: Parameter = Parameter::number(
+    "expansion_limit",
+    "Expansion limit (thousands)",
+    "The search gives up once it has expanded that many nodes over all its iterations",
+    1000.0,
+    (10.0, 10000.0, 10.0),
+);
+
+// a node of the depth-first search and the arcs left to try from it
+struct Frame<'a> {
+    node: &'a Node,
+    cost: f32,
+    // seconds since the departure
+    elapsed: f32,
+    via: Option<&'a Edge>,
+    edges: Vec<&'a Edge>,
+    next: usize,
+}
+
+impl<'a> Frame<'a> {
+    fn new(
+        graph: &'a SearchGraph,
+        node: &'a Node,
+        cost: f32,
+        elapsed: f32,
+        via: Option<&'a Edge>,
+    ) -> Self {
+        Self {
+            node,
+            cost,
+            elapsed,
+            via,
+            edges: graph.edges_from(node).collect(),
+            next: 0,
+        }
+    }
+}
+
+/// Iterative deepening A*: depth-first searches bounded by the cost so far plus remaining
+/// cost estimate, the bound growing to the lowest estimate exceeding it after each one.
+///
+/// Within an iteration the lowest cost each node was reached at is kept, so that it is not
+/// expanded again unless reached more cheaply: the memory grows with the nodes reached, as
+/// for A*, rather than with the length of the path.
+pub struct IdaStar;
+
+impl PathfindingAlgorithm for IdaStar {
+    fn id(&self) -> &'static str {
+        "IdaStar"
+    }
+
+    fn name(&self) -> &'static str {
+        "IDA Star"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[EXPANSION_LIMIT, USE_MANHATTAN]
+    }
+
+    fn run(&self, query: &Query, graph: &SearchGraph, parameters: &ParameterValues) -> RunOutput {
+        let limit = (parameters.number_of(&EXPANSION_LIMIT) * 1000.) as usize;
+        let remaining_cost = remaining_cost(query, parameters);
+
+        let mut passed_edges = HashSet::new();
+        let mut expansions = 0;
+        let mut threshold = remaining_cost(query.start);
+        loop {
+            let mut next_threshold = f32::INFINITY;
+            // lowest cost each node has been reached at during this iteration
+            let mut lowest: HashMap<&Node, f32> = HashMap::from([(query.start, 0.0)]);
+            let mut stack = vec![Frame::new(graph, query.start, 0.0, 0.0, None)];
+
+            while let Some(current) = stack.last_mut() {
+                if current.node == query.end {
+                    break;
+                }
+                let Some(&edge) = current.edges.get(current.next) else {
+                    stack.pop();
+                    continue;
+                };
+                current.next += 1;
+                let (cost, elapsed) = (current.cost, current.elapsed);
+
+                let Some((edge_cost, duration)) =
+                    graph.edge_cost(edge, query.cost_model, query.departure + elapsed)
+                else {
+                    continue;
+                };
+                if query.mark_passed_edges {
+                    passed_edges.insert(edge.clone());
+                }
+
+                let new_cost = cost + edge_cost.max(0.0);
+                let estimate = new_cost + remaining_cost(&edge.to);
+                if estimate > threshold {
+                    next_threshold = next_threshold.min(estimate);
+                    continue;
+                }
+                if lowest.get(&edge.to).is_some_and(|&c| c <= new_cost) {
+                    continue;
+                }
+                lowest.insert(&edge.to, new_cost);
+
+                expansions += 1;
+                if expansions > limit || !query.control.step() {
+                    return route_from_predecessors(
+                        query,
+                        graph,
+                        &HashMap::new(),
+                        None,
+                        passed_edges,
+                    );
+                }
+                stack.push(Frame::new(
+                    graph,
+                    &edge.to,
+                    new_cost,
+                    elapsed + duration,
+                    Some(edge),
+                ));
+            }
+
+            if let Some(last) = stack.last() {
+                let predecessors = stack
+                    .iter()
+                    .filter_map(|frame| Some((frame.node, frame.via?)))
+                    .collect::<HashMap<&Node, &Edge>>();
+                let output = route_from_predecessors(
+                    query,
+                    graph,
+                    &predecessors,
+                    Some(last.cost),
+                    passed_edges,
+                );
+                let bound = is_admissible(query, graph, parameters).then_some(1.0);
+                return with_bound(output, bound);
+            }
+            // nothing was left beyond the threshold, the end is unreachable
+            if next_threshold.is_infinite() {
+                return route_from_predecessors(query, graph, &HashMap::new(), None, passed_edges);
+            }
+            threshold = next_threshold;
+        }
+    }
+}
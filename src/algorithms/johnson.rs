@@ -0,0 +1,112 @@
+use priority_queue::PriorityQueue;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use crate::{
+    algorithms::{
+        bellman_ford::{bellman_ford, costed_arcs, exact_bound, passed_arcs, Unsettled},
+        route::{route_from_predecessors, with_bound},
+        ParameterValues, PathfindingAlgorithm, Query, RunOutput, SearchControl, SearchGraph,
+    },
+    models::{Edge, Node},
+    utils::FloatOrd,
+};
+
+/// Reweights the arcs with potentials found by Bellman-Ford so that none costs less
+/// than nothing, then runs Dijkstra on them. Arcs are costed as in [`super::BellmanFord`].
+pub struct Johnson;
+
+impl PathfindingAlgorithm for Johnson {
+    fn id(&self) -> &'static str {
+        "Johnson"
+    }
+
+    fn name(&self) -> &'static str {
+        "Johnson"
+    }
+
+    fn handles_negative_costs(&self) -> bool {
+        true
+    }
+
+    fn run(&self, query: &Query, graph: &SearchGraph, _: &ParameterValues) -> RunOutput {
+        let arcs = costed_arcs(query, graph);
+        let (start, end) = (query.start, query.end);
+
+        // potentials from a virtual node joined to every node by a free arc
+        let mut potentials = arcs
+            .iter()
+            .flat_map(|(edge, _)| [(&edge.from, 0.0), (&edge.to, 0.0)])
+            .collect::<HashMap<&Node, f32>>();
+        potentials.insert(start, 0.0);
+        match bellman_ford(&arcs, &mut potentials, &mut HashMap::new(), query.control) {
+            Ok(()) => {}
+            Err(Unsettled::Cancelled) => return RunOutput::default(),
+            Err(Unsettled::NegativeCycle(cycle)) => {
+                return RunOutput {
+                    negative_cycle: cycle,
+                    ..Default::default()
+                }
+            }
+        }
+
+        // the reweighted arcs all cost at least nothing, which Dijkstra handles
+        let reweighted = arcs
+            .iter()
+            .map(|&(edge, cost)| {
+                let cost = cost + potentials[&edge.from] - potentials[&edge.to];
+                (edge, cost.max(0.0))
+            })
+            .collect::<Vec<(&Edge, f32)>>();
+        let mut predecessors = HashMap::new();
+        let costs = dijkstra(&reweighted, start, &mut predecessors, query.control);
+        // a partial cost is no best route
+        if query.control.is_cancelled() {
+            return RunOutput::default();
+        }
+        let total_cost = costs
+            .get(end)
+            .map(|cost| cost - potentials[start] + potentials[end]);
+
+        let passed_edges = passed_arcs(query, &arcs, &costs);
+        let output = route_from_predecessors(query, graph, &predecessors, total_cost, passed_edges);
+        with_bound(output, exact_bound(query, graph))
+    }
+}
+
+// costs of the nodes reachable from the start over arcs that cost at least nothing
+fn dijkstra<'a>(
+    arcs: &[(&'a Edge, f32)],
+    start: &'a Node,
+    predecessors: &mut HashMap<&'a Node, &'a Edge>,
+    control: &SearchControl,
+) -> HashMap<&'a Node, f32> {
+    let mut outgoing: HashMap<&Node, Vec<(&Edge, f32)>> = HashMap::new();
+    arcs.iter()
+        .for_each(|&(edge, cost)| outgoing.entry(&edge.from).or_default().push((edge, cost)));
+
+    let mut costs = HashMap::from([(start, 0.0)]);
+    let mut settled = HashSet::new();
+    let mut frontier = PriorityQueue::new();
+    frontier.push(start, Reverse(FloatOrd(0.0)));
+
+    while let Some((current, Reverse(FloatOrd(cost)))) = frontier.pop() {
+        if !control.step() {
+            break;
+        }
+        settled.insert(current);
+        for &(edge, edge_cost) in outgoing.get(current).into_iter().flatten() {
+            let new_cost = cost + edge_cost;
+            if settled.contains(&edge.to) || costs.get(&edge.to).is_some_and(|&c| c <= new_cost) {
+                continue;
+            }
+            costs.insert(&edge.to, new_cost);
+            predecessors.insert(&edge.to, edge);
+            frontier.push(&edge.to, Reverse(FloatOrd(new_cost)));
+        }
+    }
+
+    costs
+}
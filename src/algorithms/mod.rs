@@ -0,0 +1,285 @@
+mod ara_star;
+mod astar;
+mod beam;
+mod bellman_ford;
+mod best_first;
+mod control;
+mod dijkstra;
+mod graph;
+mod ida_star;
+mod johnson;
+mod parameters;
+mod route;
+
+use std::{collections::HashSet, sync::Arc};
+
+use crate::{
+    contexts::CostModel,
+    models::{Edge, Node},
+};
+
+#[allow(unused_imports)]
+pub use ara_star::AraStar;
+#[allow(unused_imports)]
+pub use astar::{is_admissible, remaining_cost, AStar, GreedyBestFirst, WeightedAStar};
+#[allow(unused_imports)]
+pub use beam::BeamSearch;
+#[allow(unused_imports)]
+pub use bellman_ford::BellmanFord;
+#[allow(unused_imports)]
+pub use best_first::{best_first_search, heuristic};
+#[allow(unused_imports)]
+pub use control::SearchControl;
+#[allow(unused_imports)]
+pub use dijkstra::Dijkstra;
+#[allow(unused_imports)]
+pub use graph::{SearchGraph, TurnTable};
+#[allow(unused_imports)]
+pub use ida_star::IdaStar;
+#[allow(unused_imports)]
+pub use johnson::Johnson;
+#[allow(unused_imports)]
+pub use parameters::{Parameter, ParameterKind, ParameterValue, ParameterValues};
+
+/// What a search found, the path is empty when the end could not be reached.
+#[derive(Debug, Clone, Default)]
+pub struct RunOutput {
+    pub path: Vec<Node>,
+    // arcs of the route
+    pub selected_edges: HashSet<Edge>,
+    // arcs the search looked at, only kept when asked for
+    pub passed_edges: HashSet<Edge>,
+    pub total_cost: f32,
+    // meters
+    pub route_length: f32,
+    // seconds
+    pub route_duration: f32,
+    // arcs of the negative cycle that left no best route
+    pub negative_cycle: Vec<Edge>,
+    pub solutions: Solutions,
+}
+
+// (bound, cost) of each route found along the way, the returned one last; the bound is how
+// many times the best cost the route may cost at most, `None` when there is no guarantee
+pub type Solutions = Vec<(Option<f32>, f32)>;
+
+/// What a route is searched for, whatever the algorithm.
+pub struct Query<'a> {
+    pub start: &'a Node,
+    pub end: &'a Node,
+    pub cost_model: &'a CostModel,
+    // seconds after midnight
+    pub departure: f32,
+    pub mark_passed_edges: bool,
+    pub use_turn_restrictions: bool,
+    pub allow_u_turns: bool,
+    // checked by the search as it goes, which gives up once cancelled
+    pub control: &'a SearchControl,
+}
+
+/// A search the UI can pick, see [`AlgorithmRegistry`].
+///
+/// The passed edges of the [`RunOutput`] are the trace of the search, drawn when
+/// the user asks for them, the solutions tell how far from the best the route may be.
+pub trait PathfindingAlgorithm: Send + Sync {
+    /// Stable identifier, stored in the sessions.
+    fn id(&self) -> &'static str;
+
+    fn name(&self) -> &'static str;
+
+    /// What the UI generates widgets for, the values are handed over to [`Self::run`].
+    fn parameters(&self) -> &'static [Parameter] {
+        &[]
+    }
+
+    /// Whether the best route is found when some arcs cost less than nothing.
+    fn handles_negative_costs(&self) -> bool {
+        false
+    }
+
+    /// `parameters` holds a value for each of [`Self::parameters`]. The output of a search
+    /// cancelled through [`Query::control`] is meaningless.
+    fn run(&self, query: &Query, graph: &SearchGraph, parameters: &ParameterValues) -> RunOutput;
+}
+
+/// The algorithms offered in the UI, in the order they are listed.
+pub struct AlgorithmRegistry {
+    algorithms: Vec<Arc<dyn PathfindingAlgorithm>>,
+}
+
+impl AlgorithmRegistry {
+    /// The built-in algorithms, register experimental ones on top.
+    pub fn new() -> Self {
+        let mut registry = Self {
+            algorithms: Vec::new(),
+        };
+        registry.register(Dijkstra);
+        registry.register(AStar);
+        registry.register(WeightedAStar);
+        registry.register(GreedyBestFirst);
+        registry.register(BeamSearch);
+        registry.register(IdaStar);
+        registry.register(AraStar);
+        registry.register(BellmanFord);
+        registry.register(Johnson);
+        registry
+    }
+
+    /// Adds an algorithm, replacing the one registered with the same id.
+    pub fn register(&mut self, algorithm: impl PathfindingAlgorithm + 'static) {
+        let algorithm: Arc<dyn PathfindingAlgorithm> = Arc::new(algorithm);
+        match self
+            .algorithms
+            .iter()
+            .position(|a| a.id() == algorithm.id())
+        {
+            Some(index) => self.algorithms[index] = algorithm,
+            None => self.algorithms.push(algorithm),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Arc<dyn PathfindingAlgorithm>> {
+        self.algorithms
+            .iter()
+            .find(|algorithm| algorithm.id() == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn PathfindingAlgorithm>> {
+        self.algorithms.iter()
+    }
+}
+
+impl Default for AlgorithmRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_and_parameter_defaults() {
+        let mut registry = AlgorithmRegistry::new();
+        let count = registry.iter().count();
+        registry.register(Dijkstra);
+        assert_eq!(registry.iter().count(), count);
+        assert!(registry.get("Unknown").is_none());
+
+        let hybrid = registry.get("HybridAStar").unwrap();
+        let mut values = ParameterValues::default();
+        values.set("heuristic_weight", ParameterValue::Number(1.5));
+        let resolved = values.resolve(hybrid.parameters());
+        assert_eq!(resolved.number("heuristic_weight"), Some(1.5));
+        assert_eq!(
+            resolved.get("use_manhattan"),
+            Some(ParameterValue::Flag(true))
+        );
+        // only the parameters of the algorithm are kept
+        assert_eq!(values.resolve(&[]), ParameterValues::default());
+
+        // values from a session may be out of range or of the wrong kind
+        values.set("heuristic_weight", ParameterValue::Number(-3.));
+        values.set("use_manhattan", ParameterValue::Number(2.));
+        let resolved = values.resolve(hybrid.parameters());
+        assert_eq!(resolved.number("heuristic_weight"), Some(1.0));
+        assert_eq!(
+            resolved.get("use_manhattan"),
+            Some(ParameterValue::Flag(true))
+        );
+        values.set("heuristic_weight", ParameterValue::Number(f32::NAN));
+        let resolved = values.resolve(hybrid.parameters());
+        assert_eq!(resolved.number("heuristic_weight"), Some(1.5));
+    }
+
+    #[test]
+    fn algorithms_honour_their_bounds() {
+        use crate::{contexts::AlgorithmContext, parser::build_neighbors, utils::wait_for_route};
+
+        // a 5 x 5 grid of two-way arcs at least 100 m long, the heuristic stays below that
+        let node = |x: usize, y: usize| {
+            let position = egui::Pos2::new(x as f32 * 0.005, y as f32 * 0.005);
+            Node::new(format!("{}-{}", x, y), position, 0., 0.)
+        };
+        let mut edges = Vec::new();
+        for x in 0..5 {
+            for y in 0..5 {
+                let length = 100. + 37. * ((x * 7 + y * 3) % 5) as f32;
+                if x < 4 {
+                    edges.push(Edge::new(node(x, y), node(x + 1, y), length));
+                    edges.push(Edge::new(node(x + 1, y), node(x, y), length));
+                }
+                if y < 4 {
+                    edges.push(Edge::new(node(x, y), node(x, y + 1), length));
+                    edges.push(Edge::new(node(x, y + 1), node(x, y), length));
+                }
+            }
+        }
+        let neighbors = Arc::new(build_neighbors(&edges));
+        let (start, end) = (node(0, 0), node(4, 3));
+
+        let mut algorithm_ctx = AlgorithmContext::new();
+        algorithm_ctx.set_algorithm("Dijkstra");
+        wait_for_route(&mut algorithm_ctx, &start, &end, &neighbors);
+        let best = algorithm_ctx.total_cost;
+
+        let ids = algorithm_ctx
+            .registry()
+            .iter()
+            .map(|algorithm| algorithm.id())
+            .collect::<Vec<_>>();
+        for id in ids {
+            algorithm_ctx.set_algorithm(id);
+            wait_for_route(&mut algorithm_ctx, &start, &end, &neighbors);
+            assert!(!algorithm_ctx.path().is_empty(), "{}", id);
+            let bound = algorithm_ctx.optimality_bound().unwrap_or(f32::INFINITY);
+            assert!(algorithm_ctx.total_cost <= bound * best + 1e-3, "{}", id);
+        }
+
+        // every route ARA* finds is cheaper than the previous one, the last one is the best
+        algorithm_ctx.set_algorithm("AraStar");
+        wait_for_route(&mut algorithm_ctx, &start, &end, &neighbors);
+        let solutions = algorithm_ctx.solutions();
+        assert!(solutions.windows(2).all(|pair| pair[1].1 < pair[0].1));
+        assert_eq!(solutions.last().unwrap().0, Some(1.0));
+        assert!((algorithm_ctx.total_cost - best).abs() < 1e-3);
+    }
+
+    #[test]
+    fn bounds_need_an_admissible_estimate() {
+        use crate::{contexts::AlgorithmContext, parser::build_neighbors, utils::wait_for_route};
+
+        // a line of 100 m arcs, the estimate between the nodes grows with their spacing
+        let route = |spacing: f32, id: &str| {
+            let node = |x: usize| {
+                let position = egui::Pos2::new(x as f32 * spacing, 0.);
+                Node::new(x.to_string(), position, 0., 0.)
+            };
+            let edges = (0..3)
+                .map(|x| Edge::new(node(x), node(x + 1), 100.))
+                .collect::<Vec<Edge>>();
+            let mut algorithm_ctx = AlgorithmContext::new();
+            algorithm_ctx.set_algorithm(id);
+            wait_for_route(
+                &mut algorithm_ctx,
+                &node(0),
+                &node(3),
+                &Arc::new(build_neighbors(&edges)),
+            );
+            (
+                algorithm_ctx.optimality_bound(),
+                algorithm_ctx.solutions().to_vec(),
+            )
+        };
+
+        for id in ["AStar", "IdaStar", "AraStar"] {
+            assert_eq!(route(0.005, id).0, Some(1.0), "{}", id);
+            assert_eq!(route(1., id).0, None, "{}", id);
+        }
+        assert!(route(1., "AraStar")
+            .1
+            .iter()
+            .all(|(bound, _)| bound.is_none()));
+    }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+};
+
+use crate::utils::FloatOrd;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ParameterKind {
+    Number {
+        default: f32,
+        min: f32,
+        max: f32,
+        step: f64,
+    },
+    Flag {
+        default: bool,
+    },
+}
+
+/// A setting of an algorithm, the UI generates a slider or a toggle for it.
+///
+/// Algorithms declaring the same key share its value.
+#[derive(Debug, Clone, Copy)]
+pub struct Parameter {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub description: &'static str,
+    pub kind: ParameterKind,
+}
+
+impl Parameter {
+    pub const fn number(
+        key: &'static str,
+        label: &'static str,
+        description: &'static str,
+        default: f32,
+        (min, max, step): (f32, f32, f64),
+    ) -> Self {
+        Self {
+            key,
+            label,
+            description,
+            kind: ParameterKind::Number {
+                default,
+                min,
+                max,
+                step,
+            },
+        }
+    }
+
+    pub const fn flag(
+        key: &'static str,
+        label: &'static str,
+        description: &'static str,
+        default: bool,
+    ) -> Self {
+        Self {
+            key,
+            label,
+            description,
+            kind: ParameterKind::Flag { default },
+        }
+    }
+
+    pub fn default_value(&self) -> ParameterValue {
+        match self.kind {
+            ParameterKind::Number { default, .. } => ParameterValue::Number(default),
+            ParameterKind::Flag { default } => ParameterValue::Flag(default),
+        }
+    }
+
+    // the value clamped to the range of a number, the default when it is missing, not a number
+    // or of the other kind, as values may come from a session file
+    fn checked(&self, value: Option<ParameterValue>) -> ParameterValue {
+        match (self.kind, value) {
+            (ParameterKind::Number { min, max, .. }, Some(ParameterValue::Number(value)))
+                if !value.is_nan() =>
+            {
+                ParameterValue::Number(value.clamp(min, max))
+            }
+            (ParameterKind::Flag { .. }, Some(ParameterValue::Flag(value))) => {
+                ParameterValue::Flag(value)
+            }
+            _ => self.default_value(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ParameterValue {
+    Number(f32),
+    Flag(bool),
+}
+
+impl PartialEq for ParameterValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ParameterValue::Number(a), ParameterValue::Number(b)) => FloatOrd(*a) == FloatOrd(*b),
+            (ParameterValue::Flag(a), ParameterValue::Flag(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ParameterValue {}
+
+impl Hash for ParameterValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            ParameterValue::Number(value) => FloatOrd(*value).hash(state),
+            ParameterValue::Flag(value) => value.hash(state),
+        }
+    }
+}
+
+/// Parameter values by key, part of the run arguments so they are hashable.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ParameterValues(BTreeMap<String, ParameterValue>);
+
+impl ParameterValues {
+    /// The values of `parameters`, the defaults filling in those not set here or set to a
+    /// value of the wrong kind, the numbers being clamped to their range.
+    pub fn resolve(&self, parameters: &[Parameter]) -> Self {
+        Self(
+            parameters
+                .iter()
+                .map(|parameter| {
+                    let value = parameter.checked(self.get(parameter.key));
+                    (parameter.key.to_string(), value)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<ParameterValue> {
+        self.0.get(key).copied()
+    }
+
+    pub fn set(&mut self, key: &str, value: ParameterValue) {
+        self.0.insert(key.to_string(), value);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.0.remove(key);
+    }
+
+    pub fn number(&self, key: &str) -> Option<f32> {
+        match self.get(key)? {
+            ParameterValue::Number(value) => Some(value),
+            ParameterValue::Flag(_) => None,
+        }
+    }
+
+    /// The value of a number parameter, checked as in [`Self::resolve`].
+    pub fn number_of(&self, parameter: &Parameter) -> f32 {
+        match parameter.checked(self.get(parameter.key)) {
+            ParameterValue::Number(value) => value,
+            // a flag parameter, only the algorithm declaring it can ask for the wrong kind
+            ParameterValue::Flag(value) => f32::from(u8::from(value)),
+        }
+    }
+
+    /// The value of a flag parameter, checked as in [`Self::resolve`].
+    pub fn flag_of(&self, parameter: &Parameter) -> bool {
+        match parameter.checked(self.get(parameter.key)) {
+            ParameterValue::Flag(value) => value,
+            ParameterValue::Number(value) => value != 0.0,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    algorithms::{Query, RunOutput, SearchGraph},
+    models::{Edge, Node},
+};
+
+/// The route to the end along the arcs each node was reached by, `total_cost` is
+/// `None` when the end is unreachable.
+pub(super) fn route_from_predecessors(
+    query: &Query,
+    graph: &SearchGraph,
+    predecessors: &HashMap<&Node, &Edge>,
+    total_cost: Option<f32>,
+    passed_edges: HashSet<Edge>,
+) -> RunOutput {
+    let Some(total_cost) = total_cost else {
+        return RunOutput {
+            passed_edges,
+            ..Default::default()
+        };
+    };
+
+    let mut route_edges = Vec::new();
+    let mut current = query.end;
+    while current != query.start {
+        let edge = predecessors[current];
+        route_edges.push(edge.clone());
+        current = &edge.from;
+    }
+    route_edges.reverse();
+
+    let mut route_duration = 0.0;
+    route_edges.iter().for_each(|edge| {
+        route_duration += graph
+            .edge_cost(edge, query.cost_model, query.departure + route_duration)
+            .unwrap()
+            .1;
+    });
+    let path = std::iter::once(query.start.clone())
+        .chain(route_edges.iter().map(|edge| edge.to.clone()))
+        .collect();
+    let route_length = route_edges.iter().map(|edge| edge.length).sum();
+
+    RunOutput {
+        path,
+        selected_edges: route_edges.into_iter().collect(),
+        passed_edges,
+        total_cost,
+        route_length,
+        route_duration,
+        ..Default::default()
+    }
+}
+
+/// Records the route found as the only solution, with the given optimality bound. A search
+/// that already recorded it, without a bound as it counted negative costs as nothing, keeps it.
+pub(super) fn with_bound(mut output: RunOutput, bound: Option<f32>) -> RunOutput {
+    if !output.path.is_empty() && output.solutions.is_empty() {
+        output.solutions = vec![(bound, output.total_cost)];
+    }
+    output
+}
@@ -0,0 +1,209 @@
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::{
+    models::{Edge, Node},
+    utils::FloatOrd,
+};
+
+// path lengths closer than this, in meters, are considered equal
+const LENGTH_TOLERANCE: f32 = 1e-3;
+
+/// Edge betweenness and node closeness over the arc lengths.
+#[derive(Debug, Clone, Default)]
+pub struct Centrality {
+    // shortest paths going through each arc, scaled up when the sources are sampled
+    pub edge_betweenness: HashMap<Edge, f32>,
+    // arcs by decreasing betweenness
+    pub ranked_edges: Vec<(Edge, f32)>,
+    // only known for the nodes used as sources
+    pub closeness: HashMap<Node, f32>,
+    pub max_betweenness: f32,
+    pub max_closeness: f32,
+    // how many nodes the shortest paths were computed from
+    pub sources: usize,
+}
+
+impl Centrality {
+    /// Brandes' algorithm from every node, or from `samples` random nodes for an approximation.
+    pub fn new(nodes: &[Node], edges: &[Edge], samples: Option<usize>) -> Self {
+        let mut index_of: HashMap<&Node, usize> = HashMap::new();
+        let mut unique_nodes: Vec<&Node> = Vec::new();
+        nodes.iter().for_each(|node| {
+            index_of.entry(node).or_insert_with(|| {
+                unique_nodes.push(node);
+                unique_nodes.len() - 1
+            });
+        });
+
+        // (to, edge index, length) of the arcs leaving each node
+        let mut adjacency: Vec<Vec<(usize, usize, f32)>> = vec![Vec::new(); unique_nodes.len()];
+        edges.iter().enumerate().for_each(|(index, edge)| {
+            if let (Some(&from), Some(&to)) = (index_of.get(&edge.from), index_of.get(&edge.to)) {
+                adjacency[from].push((to, index, edge.length.max(0.0)));
+            }
+        });
+
+        let mut sources = (0..unique_nodes.len()).collect::<Vec<usize>>();
+        if let Some(samples) = samples.filter(|samples| *samples < sources.len()) {
+            sources.shuffle(&mut rand::thread_rng());
+            sources.truncate(samples);
+        }
+
+        // one accumulator per rayon job rather than one per source
+        let (betweenness, closeness) = sources
+            .par_iter()
+            .fold(
+                || (vec![0.0; edges.len()], Vec::new()),
+                |(mut betweenness, mut closeness), source| {
+                    closeness.push((
+                        *source,
+                        single_source(&adjacency, *source, &mut betweenness),
+                    ));
+                    (betweenness, closeness)
+                },
+            )
+            .reduce(
+                || (vec![0.0; edges.len()], Vec::new()),
+                |(mut betweenness, mut closeness), (other_betweenness, other_closeness)| {
+                    betweenness
+                        .iter_mut()
+                        .zip(other_betweenness)
+                        .for_each(|(total, value)| *total += value);
+                    closeness.extend(other_closeness);
+                    (betweenness, closeness)
+                },
+            );
+
+        let scale = unique_nodes.len() as f64 / sources.len().max(1) as f64;
+        let mut ranked_edges = edges
+            .iter()
+            .zip(betweenness)
+            .map(|(edge, value)| (edge.clone(), (value * scale) as f32))
+            .collect::<Vec<(Edge, f32)>>();
+        ranked_edges.sort_by_key(|(_, value)| Reverse(FloatOrd(*value)));
+
+        let closeness = closeness
+            .into_iter()
+            .map(|(source, value)| (unique_nodes[source].clone(), value))
+            .collect::<HashMap<Node, f32>>();
+
+        Self {
+            edge_betweenness: ranked_edges.iter().cloned().collect(),
+            max_betweenness: ranked_edges.first().map_or(0.0, |(_, value)| *value),
+            max_closeness: closeness.values().copied().fold(0.0, f32::max),
+            ranked_edges,
+            closeness,
+            sources: sources.len(),
+        }
+    }
+
+    /// Betweenness of the arc relative to the highest one, in [0, 1].
+    pub fn relative_betweenness(&self, edge: &Edge) -> Option<f32> {
+        let value = self.edge_betweenness.get(edge)?;
+        Some(value / self.max_betweenness.max(f32::EPSILON))
+    }
+
+    /// Closeness of the node relative to the highest one, in [0, 1].
+    pub fn relative_closeness(&self, node: &Node) -> Option<f32> {
+        let value = self.closeness.get(node)?;
+        Some(value / self.max_closeness.max(f32::EPSILON))
+    }
+}
+
+// adds the dependencies of `source` to the betweenness of the arcs and returns its closeness
+fn single_source(
+    adjacency: &[Vec<(usize, usize, f32)>],
+    source: usize,
+    betweenness: &mut [f64],
+) -> f32 {
+    let node_count = adjacency.len();
+    let mut distance = vec![f32::INFINITY; node_count];
+    // number of shortest paths from the source
+    let mut path_count = vec![0.0_f64; node_count];
+    // (node, edge index) on the shortest paths to each node
+    let mut predecessors: Vec<Vec<(usize, usize)>> = vec![Vec::new(); node_count];
+    // nodes by non-decreasing distance
+    let mut settled = Vec::new();
+    let mut is_settled = vec![false; node_count];
+
+    distance[source] = 0.0;
+    path_count[source] = 1.0;
+    let mut frontier = BinaryHeap::new();
+    frontier.push(Reverse((FloatOrd(0.0), source)));
+
+    while let Some(Reverse((FloatOrd(current_distance), node))) = frontier.pop() {
+        if is_settled[node] || current_distance > distance[node] {
+            continue;
+        }
+        is_settled[node] = true;
+        settled.push(node);
+
+        for &(next, edge, length) in &adjacency[node] {
+            let next_distance = current_distance + length;
+            if next_distance < distance[next] - LENGTH_TOLERANCE {
+                distance[next] = next_distance;
+                path_count[next] = path_count[node];
+                predecessors[next] = vec![(node, edge)];
+                frontier.push(Reverse((FloatOrd(next_distance), next)));
+            } else if !is_settled[next]
+                && (next_distance - distance[next]).abs() <= LENGTH_TOLERANCE
+            {
+                path_count[next] += path_count[node];
+                predecessors[next].push((node, edge));
+            }
+        }
+    }
+
+    let mut dependency = vec![0.0_f64; node_count];
+    settled.iter().rev().for_each(|&node| {
+        predecessors[node].iter().for_each(|&(previous, edge)| {
+            let share = path_count[previous] / path_count[node] * (1.0 + dependency[node]);
+            betweenness[edge] += share;
+            dependency[previous] += share;
+        });
+    });
+
+    // Wasserman and Faust's closeness, which copes with unreachable nodes
+    let reached = settled.len() - 1;
+    let total_distance: f32 = settled.iter().map(|node| distance[*node]).sum();
+    if reached == 0 || total_distance <= 0.0 {
+        return 0.0;
+    }
+    (reached as f32 / (node_count - 1) as f32) * (reached as f32 / total_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_graph;
+
+    #[test]
+    fn bridge_has_the_highest_betweenness() {
+        // two two-way pairs 0 - 1 and 2 - 3 joined by the two-way bridge 1 - 2
+        let (nodes, edges) = test_graph(
+            4,
+            &[
+                (0, 1, 10.),
+                (1, 0, 10.),
+                (1, 2, 10.),
+                (2, 1, 10.),
+                (2, 3, 10.),
+                (3, 2, 10.),
+            ],
+        );
+
+        let centrality = Centrality::new(&nodes, &edges, None);
+
+        assert_eq!(centrality.sources, 4);
+        // 0 -> 2, 0 -> 3, 1 -> 2 and 1 -> 3 all cross the bridge
+        assert_eq!(centrality.edge_betweenness[&edges[2]], 4.);
+        assert_eq!(centrality.edge_betweenness[&edges[0]], 3.);
+        assert_eq!(centrality.relative_betweenness(&edges[2]), Some(1.));
+        assert!(centrality.closeness[&nodes[1]] > centrality.closeness[&nodes[0]]);
+    }
+}
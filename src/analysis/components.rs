@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::models::{Edge, Node};
+
+/// Strongly connected components of the graph, largest first, found with Tarjan's algorithm.
+///
+/// The depth-first search keeps its own stack, deep graphs would overflow the thread's.
+pub fn strongly_connected_components(nodes: &[Node], edges: &[Edge]) -> Vec<Vec<Node>> {
+    // duplicate ids are one and the same node
+    let mut index_of: HashMap<&Node, usize> = HashMap::new();
+    let mut unique_nodes: Vec<&Node> = Vec::new();
+    nodes.iter().for_each(|node| {
+        index_of.entry(node).or_insert_with(|| {
+            unique_nodes.push(node);
+            unique_nodes.len() - 1
+        });
+    });
+    let nodes = unique_nodes;
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    edges.iter().for_each(|edge| {
+        if let (Some(&from), Some(&to)) = (index_of.get(&edge.from), index_of.get(&edge.to)) {
+            successors[from].push(to);
+        }
+    });
+
+    // visiting order and lowest order reachable of every node, `None` until visited
+    let mut order: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut low_link = vec![0; nodes.len()];
+    let mut on_stack = vec![false; nodes.len()];
+    let mut stack = Vec::new();
+    let mut next_order = 0;
+    let mut components = Vec::new();
+
+    for root in 0..nodes.len() {
+        if order[root].is_some() {
+            continue;
+        }
+
+        // (node, index of the next successor to look at)
+        let mut call_stack = vec![(root, 0)];
+        while let Some(&mut (node, ref mut next_successor)) = call_stack.last_mut() {
+            if *next_successor == 0 && order[node].is_none() {
+                order[node] = Some(next_order);
+                low_link[node] = next_order;
+                next_order += 1;
+                stack.push(node);
+                on_stack[node] = true;
+            }
+
+            if let Some(&successor) = successors[node].get(*next_successor) {
+                *next_successor += 1;
+                match order[successor] {
+                    None => call_stack.push((successor, 0)),
+                    Some(successor_order) if on_stack[successor] => {
+                        low_link[node] = low_link[node].min(successor_order);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+            if Some(low_link[node]) == order[node] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(nodes[member].clone());
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+    components
+}
+
+/// Components of the graph when the arcs are followed both ways, largest first.
+pub fn weakly_connected_components(nodes: &[Node], edges: &[Edge]) -> Vec<Vec<Node>> {
+    let mut index_of: HashMap<&Node, usize> = HashMap::new();
+    let mut unique_nodes: Vec<&Node> = Vec::new();
+    nodes.iter().for_each(|node| {
+        index_of.entry(node).or_insert_with(|| {
+            unique_nodes.push(node);
+            unique_nodes.len() - 1
+        });
+    });
+    let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); unique_nodes.len()];
+    edges.iter().for_each(|edge| {
+        if let (Some(&from), Some(&to)) = (index_of.get(&edge.from), index_of.get(&edge.to)) {
+            adjacent[from].push(to);
+            adjacent[to].push(from);
+        }
+    });
+
+    let mut is_visited = vec![false; unique_nodes.len()];
+    let mut components = Vec::new();
+    for root in 0..unique_nodes.len() {
+        if std::mem::replace(&mut is_visited[root], true) {
+            continue;
+        }
+        let mut component = Vec::new();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            component.push(unique_nodes[node].clone());
+            adjacent[node].iter().for_each(|&next| {
+                if !std::mem::replace(&mut is_visited[next], true) {
+                    stack.push(next);
+                }
+            });
+        }
+        components.push(component);
+    }
+
+    components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_graph;
+
+    #[test]
+    fn finds_components() {
+        // the cycle 0 -> 1 -> 2 -> 0, then the one-way arcs 2 -> 3 -> 4
+        let (nodes, edges) = test_graph(
+            5,
+            &[(0, 1, 1.), (1, 2, 1.), (2, 0, 1.), (2, 3, 1.), (3, 4, 1.)],
+        );
+
+        let components = strongly_connected_components(&nodes, &edges);
+
+        assert_eq!(components.len(), 3);
+        let mut largest = components[0].clone();
+        largest.sort();
+        assert_eq!(largest, nodes[..3]);
+        assert_eq!(components[1].len(), 1);
+        assert_eq!(components[2].len(), 1);
+
+        // the one-way arcs still connect everything
+        assert_eq!(weakly_connected_components(&nodes, &edges).len(), 1);
+        assert_eq!(weakly_connected_components(&nodes, &edges[..3]).len(), 3);
+    }
+}
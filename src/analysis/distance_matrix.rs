@@ -0,0 +1,139 @@
+use rayon::prelude::*;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    models::{Edge, Node},
+    utils::FloatOrd,
+};
+
+/// Shortest distances between every pair of a list of nodes.
+#[derive(Debug, Clone)]
+pub struct DistanceMatrix {
+    pub ids: Vec<String>,
+    // (length in meters, number of arcs) from row to column, `None` when unreachable
+    pub cells: Vec<Vec<Option<(f32, usize)>>>,
+}
+
+impl DistanceMatrix {
+    /// Runs one Dijkstra per node of the list, in parallel, each stopping once it has reached
+    /// all of them. Fails on ids missing from the graph.
+    pub fn compute(
+        nodes: &[Node],
+        neighbors: &HashMap<Node, Vec<Edge>>,
+        ids: &[String],
+    ) -> Result<Self, String> {
+        let by_id = nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node))
+            .collect::<HashMap<&str, &Node>>();
+        let targets = ids
+            .iter()
+            .map(|id| {
+                by_id
+                    .get(id.as_str())
+                    .copied()
+                    .ok_or_else(|| format!("Unknown node id: {}", id))
+            })
+            .collect::<Result<Vec<&Node>, String>>()?;
+
+        let cells = targets
+            .par_iter()
+            .map(|source| one_to_many(neighbors, source, &targets))
+            .collect();
+
+        Ok(Self {
+            ids: ids.to_vec(),
+            cells,
+        })
+    }
+}
+
+// (length, hops) to each target, the fewest arcs breaking ties between equally long paths
+fn one_to_many(
+    neighbors: &HashMap<Node, Vec<Edge>>,
+    source: &Node,
+    targets: &[&Node],
+) -> Vec<Option<(f32, usize)>> {
+    let mut best: HashMap<&Node, (FloatOrd<f32>, usize)> =
+        HashMap::from([(source, (FloatOrd(0.0), 0))]);
+    let mut settled: HashMap<&Node, (f32, usize)> = HashMap::new();
+    let mut remaining = targets.len();
+    let mut frontier = BinaryHeap::new();
+    frontier.push(Reverse((FloatOrd(0.0_f32), 0_usize, source)));
+
+    while let Some(Reverse((FloatOrd(length), hops, node))) = frontier.pop() {
+        if settled.contains_key(node) {
+            continue;
+        }
+        settled.insert(node, (length, hops));
+        if targets.contains(&node) {
+            remaining -= targets.iter().filter(|target| **target == node).count();
+            if remaining == 0 {
+                break;
+            }
+        }
+
+        for edge in neighbors.get(node).into_iter().flatten() {
+            let next = (FloatOrd(length + edge.length.max(0.0)), hops + 1);
+            if best.get(&edge.to).is_none_or(|known| next < *known) {
+                best.insert(&edge.to, next);
+                frontier.push(Reverse((next.0, next.1, &edge.to)));
+            }
+        }
+    }
+
+    targets
+        .iter()
+        .map(|target| settled.get(target).copied())
+        .collect()
+}
+
+/// Node ids from the first column of a CSV file, or one per line. A header row named
+/// `id`, `node` or `node_id` is skipped, as are the lines starting with `#`.
+pub fn read_node_ids(path: &Path) -> Result<Vec<String>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+
+    let ids = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split(',').next().unwrap().trim().to_string())
+        .enumerate()
+        .filter(|(index, id)| {
+            *index > 0 || !["id", "node", "node_id"].contains(&id.to_lowercase().as_str())
+        })
+        .map(|(_, id)| id)
+        .collect::<Vec<String>>();
+
+    if ids.is_empty() {
+        return Err(format!("No node id found in {}", path.display()));
+    }
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::build_neighbors, utils::test_graph};
+
+    #[test]
+    fn computes_lengths_and_hops() {
+        // 0 -> 1 -> 2 is as long as the direct arc 0 -> 2, node 3 is unreachable
+        let (nodes, edges) = test_graph(4, &[(0, 1, 5.), (1, 2, 5.), (0, 2, 10.), (2, 0, 1.)]);
+        let neighbors = build_neighbors(&edges);
+        let ids = ["0", "2", "3"].map(String::from);
+
+        let matrix = DistanceMatrix::compute(&nodes, &neighbors, &ids).unwrap();
+
+        assert_eq!(matrix.cells[0], [Some((0., 0)), Some((10., 1)), None]);
+        assert_eq!(matrix.cells[1][0], Some((1., 1)));
+        assert_eq!(matrix.cells[2][2], Some((0., 0)));
+        assert!(DistanceMatrix::compute(&nodes, &neighbors, &["9".to_string()]).is_err());
+    }
+}
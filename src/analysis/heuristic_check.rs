@@ -0,0 +1,268 @@
+use priority_queue::PriorityQueue;
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use crate::{
+    algorithms::{
+        remaining_cost, AStar, Dijkstra, ParameterValues, PathfindingAlgorithm, Query,
+        SearchControl, SearchGraph, TurnTable,
+    },
+    contexts::CostModel,
+    models::{Edge, Node},
+    utils::FloatOrd,
+};
+
+// estimates exceeding a cost by less than this are rounding errors
+const COST_TOLERANCE: f32 = 1e-3;
+
+/// How A* fared on a query compared with Dijkstra.
+#[derive(Debug, Clone)]
+pub struct QueryCheck {
+    pub start: Node,
+    pub end: Node,
+    // `None` when the end cannot be reached
+    pub astar_cost: Option<f32>,
+    pub dijkstra_cost: Option<f32>,
+    // nodes whose estimate exceeds the actual cost to the end
+    pub overestimated_nodes: usize,
+    // largest estimate over actual cost to the end, at most 1 when the estimate is admissible
+    pub worst_ratio: f32,
+}
+
+impl QueryCheck {
+    pub fn is_suboptimal(&self) -> bool {
+        match (self.astar_cost, self.dijkstra_cost) {
+            (Some(astar), Some(dijkstra)) => astar > dijkstra + COST_TOLERANCE,
+            (astar, dijkstra) => astar.is_some() != dijkstra.is_some(),
+        }
+    }
+}
+
+/// Whether the remaining cost estimate of A* is admissible and consistent.
+///
+/// The arcs are costed at free flow, incidents, traffic and turns left aside.
+#[derive(Debug, Clone, Default)]
+pub struct HeuristicReport {
+    pub queries: Vec<QueryCheck>,
+    // arcs breaking h(from) <= cost + h(to) towards the end of some query, by how much at most
+    pub inconsistent_edges: HashMap<Edge, f32>,
+    // arcs checked, once per query
+    pub checked_edges: usize,
+}
+
+impl HeuristicReport {
+    /// Checks the given (start, end) queries plus `samples` random ones.
+    pub fn new(
+        neighbors: &HashMap<Node, Vec<Edge>>,
+        cost_model: &CostModel,
+        parameters: &ParameterValues,
+        mut queries: Vec<(Node, Node)>,
+        samples: usize,
+    ) -> Self {
+        let nodes = neighbors.keys().collect::<Vec<&Node>>();
+        if nodes.len() >= 2 {
+            let mut rng = rand::thread_rng();
+            queries.extend((0..samples).map(|_| {
+                let pair = nodes.choose_multiple(&mut rng, 2).collect::<Vec<_>>();
+                ((*pair[0]).clone(), (*pair[1]).clone())
+            }));
+        }
+
+        let parameters = parameters.resolve(AStar.parameters());
+        let (incidents, turns) = (HashMap::new(), TurnTable::new());
+        let graph = SearchGraph::new(neighbors, &incidents, None, &turns);
+        let arcs = graph
+            .arcs()
+            .filter_map(|edge| Some((edge, graph.edge_cost(edge, cost_model, 0.)?.0.max(0.))))
+            .collect::<Vec<(&Edge, f32)>>();
+
+        let checks = queries
+            .par_iter()
+            .map(|(start, end)| check_query(&graph, &arcs, cost_model, &parameters, start, end))
+            .collect::<Vec<_>>();
+
+        let mut inconsistent_edges: HashMap<Edge, f32> = HashMap::new();
+        let queries = checks
+            .into_iter()
+            .map(|(check, inconsistent)| {
+                inconsistent.into_iter().for_each(|(edge, excess)| {
+                    let worst = inconsistent_edges.entry(edge).or_insert(excess);
+                    *worst = worst.max(excess);
+                });
+                check
+            })
+            .collect::<Vec<QueryCheck>>();
+
+        Self {
+            checked_edges: arcs.len() * queries.len(),
+            queries,
+            inconsistent_edges,
+        }
+    }
+
+    pub fn suboptimal_queries(&self) -> impl Iterator<Item = &QueryCheck> {
+        self.queries.iter().filter(|check| check.is_suboptimal())
+    }
+
+    pub fn overestimated_nodes(&self) -> usize {
+        self.queries
+            .iter()
+            .map(|check| check.overestimated_nodes)
+            .sum()
+    }
+
+    pub fn worst_ratio(&self) -> f32 {
+        self.queries
+            .iter()
+            .map(|check| check.worst_ratio)
+            .fold(0., f32::max)
+    }
+
+    pub fn is_admissible(&self) -> bool {
+        self.overestimated_nodes() == 0
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistent_edges.is_empty()
+    }
+}
+
+// the check of a query with the arcs that are inconsistent towards its end
+fn check_query(
+    graph: &SearchGraph,
+    arcs: &[(&Edge, f32)],
+    cost_model: &CostModel,
+    parameters: &ParameterValues,
+    start: &Node,
+    end: &Node,
+) -> (QueryCheck, Vec<(Edge, f32)>) {
+    let query = Query {
+        start,
+        end,
+        cost_model,
+        departure: 0.,
+        mark_passed_edges: false,
+        use_turn_restrictions: false,
+        allow_u_turns: true,
+        control: &SearchControl::default(),
+    };
+    let cost = |algorithm: &dyn PathfindingAlgorithm| {
+        let output = algorithm.run(&query, graph, parameters);
+        (!output.path.is_empty()).then_some(output.total_cost)
+    };
+    let (astar_cost, dijkstra_cost) = (cost(&AStar), cost(&Dijkstra));
+    let estimate = remaining_cost(&query, parameters);
+
+    let inconsistent = arcs
+        .iter()
+        .filter_map(|&(edge, cost)| {
+            let excess = estimate(&edge.from) - cost - estimate(&edge.to);
+            (excess > COST_TOLERANCE).then(|| (edge.clone(), excess))
+        })
+        .collect();
+
+    let mut overestimated_nodes = 0;
+    let mut worst_ratio: f32 = 0.;
+    for (node, actual) in costs_to(arcs, end) {
+        let estimate = estimate(node);
+        if estimate > actual + COST_TOLERANCE {
+            overestimated_nodes += 1;
+        }
+        if actual > 0. {
+            worst_ratio = worst_ratio.max(estimate / actual);
+        }
+    }
+
+    let check = QueryCheck {
+        start: start.clone(),
+        end: end.clone(),
+        astar_cost,
+        dijkstra_cost,
+        overestimated_nodes,
+        worst_ratio,
+    };
+    (check, inconsistent)
+}
+
+// the actual cost to the end from every node that can reach it, by Dijkstra over the reversed arcs
+fn costs_to<'a>(arcs: &[(&'a Edge, f32)], end: &'a Node) -> HashMap<&'a Node, f32> {
+    let mut incoming: HashMap<&Node, Vec<(&Node, f32)>> = HashMap::new();
+    arcs.iter().for_each(|&(edge, cost)| {
+        incoming
+            .entry(&edge.to)
+            .or_default()
+            .push((&edge.from, cost))
+    });
+
+    let mut costs = HashMap::from([(end, 0.)]);
+    let mut settled = HashSet::new();
+    let mut frontier = PriorityQueue::new();
+    frontier.push(end, Reverse(FloatOrd(0.)));
+
+    while let Some((current, Reverse(FloatOrd(cost)))) = frontier.pop() {
+        settled.insert(current);
+        for &(from, arc_cost) in incoming.get(current).into_iter().flatten() {
+            let new_cost = cost + arc_cost;
+            if settled.contains(from) || costs.get(from).is_some_and(|&c| c <= new_cost) {
+                continue;
+            }
+            costs.insert(from, new_cost);
+            frontier.push(from, Reverse(FloatOrd(new_cost)));
+        }
+    }
+
+    costs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::build_neighbors;
+
+    // a 4 x 4 grid of two-way arcs 100 m long, the nodes `spacing` apart on screen
+    fn grid(spacing: f32) -> HashMap<Node, Vec<Edge>> {
+        let node = |x: usize, y: usize| {
+            let position = egui::Pos2::new(x as f32 * spacing, y as f32 * spacing);
+            Node::new(format!("{}-{}", x, y), position, 0., 0.)
+        };
+        let mut edges = Vec::new();
+        for x in 0..4 {
+            for y in 0..4 {
+                if x < 3 {
+                    edges.push(Edge::new(node(x, y), node(x + 1, y), 100.));
+                    edges.push(Edge::new(node(x + 1, y), node(x, y), 100.));
+                }
+                if y < 3 {
+                    edges.push(Edge::new(node(x, y), node(x, y + 1), 100.));
+                    edges.push(Edge::new(node(x, y + 1), node(x, y), 100.));
+                }
+            }
+        }
+        build_neighbors(&edges)
+    }
+
+    #[test]
+    fn detects_overestimates() {
+        let cost_model = CostModel::default();
+        let parameters = ParameterValues::default();
+
+        let report = HeuristicReport::new(&grid(0.005), &cost_model, &parameters, vec![], 5);
+        assert_eq!(report.queries.len(), 5);
+        assert_eq!(report.checked_edges, 48 * 5);
+        assert!(report.is_admissible() && report.is_consistent());
+        assert!(report.worst_ratio() <= 1.);
+        assert_eq!(report.suboptimal_queries().count(), 0);
+
+        // the nodes drawn far apart make the estimate exceed the arc lengths
+        let neighbors = grid(1.);
+        let corner = |id: &str| neighbors.keys().find(|node| node.id == id).unwrap().clone();
+        let queries = vec![(corner("0-0"), corner("3-3"))];
+        let report = HeuristicReport::new(&neighbors, &cost_model, &parameters, queries, 0);
+        assert!(!report.is_admissible() && !report.is_consistent());
+        assert!(report.worst_ratio() > 1.);
+    }
+}
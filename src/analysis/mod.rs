@@ -0,0 +1,22 @@
+mod centrality;
+mod components;
+mod distance_matrix;
+mod heuristic_check;
+mod spanning_tree;
+mod statistics;
+mod validation;
+
+#[allow(unused_imports)]
+pub use centrality::Centrality;
+#[allow(unused_imports)]
+pub use components::{strongly_connected_components, weakly_connected_components};
+#[allow(unused_imports)]
+pub use distance_matrix::{read_node_ids, DistanceMatrix};
+#[allow(unused_imports)]
+pub use heuristic_check::{HeuristicReport, QueryCheck};
+#[allow(unused_imports)]
+pub use spanning_tree::{SpanningTree, TreeKind};
+#[allow(unused_imports)]
+pub use statistics::GraphStatistics;
+#[allow(unused_imports)]
+pub use validation::ValidationReport;
@@ -0,0 +1,254 @@
+use enum_iterator::Sequence;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    models::{Edge, Node},
+    utils::FloatOrd,
+};
+
+#[derive(PartialEq, Eq, Hash, Sequence, Copy, Clone, Debug)]
+pub enum TreeKind {
+    MinimumSpanning,
+    Steiner,
+}
+
+impl fmt::Display for TreeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeKind::MinimumSpanning => write!(f, "Minimum spanning tree"),
+            TreeKind::Steiner => write!(f, "Steiner tree"),
+        }
+    }
+}
+
+/// Arcs of a tree over the graph, the direction of the arcs being ignored.
+#[derive(Debug, Clone)]
+pub struct SpanningTree {
+    pub kind: TreeKind,
+    pub edges: Vec<Edge>,
+    // in meters
+    pub length: f32,
+}
+
+impl SpanningTree {
+    fn new(kind: TreeKind, edges: Vec<Edge>) -> Self {
+        let length = edges.iter().map(|edge| edge.length).sum();
+        Self {
+            kind,
+            edges,
+            length,
+        }
+    }
+
+    /// Kruskal's algorithm, a forest when the graph is not connected.
+    pub fn minimum(nodes: &[Node], edges: &[Edge]) -> Self {
+        let index_of = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node, index))
+            .collect::<HashMap<&Node, usize>>();
+        let mut sorted = edges
+            .iter()
+            .filter(|edge| edge.from != edge.to)
+            .collect::<Vec<&Edge>>();
+        sorted.sort_by_key(|edge| FloatOrd(edge.length));
+
+        let mut components = UnionFind::new(nodes.len());
+        let tree = sorted
+            .into_iter()
+            .filter(
+                |edge| match (index_of.get(&edge.from), index_of.get(&edge.to)) {
+                    (Some(&from), Some(&to)) => components.union(from, to),
+                    _ => false,
+                },
+            )
+            .cloned()
+            .collect();
+
+        Self::new(TreeKind::MinimumSpanning, tree)
+    }
+
+    /// Shortest path heuristic (Takahashi and Matsuyama): the tree grows from the first
+    /// terminal by the shortest path to the closest terminal it does not reach yet.
+    /// At most twice as long as the optimal tree.
+    pub fn steiner(nodes: &[Node], edges: &[Edge], terminals: &[String]) -> Result<Self, String> {
+        let by_id = nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node))
+            .collect::<HashMap<&str, &Node>>();
+        let terminals = terminals
+            .iter()
+            .map(|id| {
+                by_id
+                    .get(id.as_str())
+                    .copied()
+                    .ok_or_else(|| format!("Unknown terminal node: {}", id))
+            })
+            .collect::<Result<Vec<&Node>, String>>()?;
+        let Some(first) = terminals.first() else {
+            return Err("No terminal node selected".to_string());
+        };
+
+        let mut adjacency: HashMap<&Node, Vec<(&Node, &Edge)>> = HashMap::new();
+        edges.iter().for_each(|edge| {
+            adjacency
+                .entry(&edge.from)
+                .or_default()
+                .push((&edge.to, edge));
+            adjacency
+                .entry(&edge.to)
+                .or_default()
+                .push((&edge.from, edge));
+        });
+
+        let mut tree_nodes: HashSet<&Node> = HashSet::from([*first]);
+        let mut tree_edges: Vec<Edge> = Vec::new();
+        let mut remaining = terminals[1..]
+            .iter()
+            .filter(|terminal| !tree_nodes.contains(*terminal))
+            .copied()
+            .collect::<HashSet<&Node>>();
+
+        while !remaining.is_empty() {
+            let Some(path) = shortest_path_to_any(&adjacency, &tree_nodes, &remaining) else {
+                let mut unreachable = remaining
+                    .iter()
+                    .map(|node| node.id.as_str())
+                    .collect::<Vec<&str>>();
+                unreachable.sort();
+                return Err(format!(
+                    "Terminal nodes not connected to the others: {}",
+                    unreachable.join(", ")
+                ));
+            };
+            path.into_iter().for_each(|(node, edge)| {
+                remaining.remove(node);
+                tree_nodes.insert(node);
+                tree_edges.push(edge.clone());
+            });
+        }
+
+        Ok(Self::new(TreeKind::Steiner, tree_edges))
+    }
+}
+
+// multi-source Dijkstra from the tree to the closest target, returns the (node, arc) steps
+fn shortest_path_to_any<'a>(
+    adjacency: &HashMap<&'a Node, Vec<(&'a Node, &'a Edge)>>,
+    sources: &HashSet<&'a Node>,
+    targets: &HashSet<&'a Node>,
+) -> Option<Vec<(&'a Node, &'a Edge)>> {
+    let mut distance: HashMap<&Node, f32> = sources.iter().map(|node| (*node, 0.0)).collect();
+    let mut came_from: HashMap<&Node, (&Node, &Edge)> = HashMap::new();
+    let mut frontier = sources
+        .iter()
+        .map(|node| Reverse((FloatOrd(0.0_f32), *node)))
+        .collect::<BinaryHeap<_>>();
+
+    while let Some(Reverse((FloatOrd(current), node))) = frontier.pop() {
+        if current > distance[node] {
+            continue;
+        }
+        if targets.contains(node) {
+            let mut path = Vec::new();
+            let mut step = node;
+            while let Some(&(previous, edge)) = came_from.get(step) {
+                path.push((step, edge));
+                step = previous;
+            }
+            return Some(path);
+        }
+
+        for &(next, edge) in adjacency.get(node).into_iter().flatten() {
+            let next_distance = current + edge.length.max(0.0);
+            if distance
+                .get(next)
+                .is_none_or(|known| next_distance < *known)
+            {
+                distance.insert(next, next_distance);
+                came_from.insert(next, (node, edge));
+                frontier.push(Reverse((FloatOrd(next_distance), next)));
+            }
+        }
+    }
+
+    None
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+            rank: vec![0; size],
+        }
+    }
+
+    fn find(&mut self, mut element: usize) -> usize {
+        while self.parent[element] != element {
+            self.parent[element] = self.parent[self.parent[element]];
+            element = self.parent[element];
+        }
+        element
+    }
+
+    // merges the sets of `a` and `b`, false when they already were the same
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        match self.rank[a].cmp(&self.rank[b]) {
+            std::cmp::Ordering::Less => self.parent[a] = b,
+            std::cmp::Ordering::Greater => self.parent[b] = a,
+            std::cmp::Ordering::Equal => {
+                self.parent[b] = a;
+                self.rank[a] += 1;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{test_graph, test_node};
+
+    #[test]
+    fn builds_trees() {
+        // a star around 4 plus a longer ring 0 - 1 - 2 - 3
+        let (nodes, edges) = test_graph(
+            5,
+            &[
+                (0, 4, 10.),
+                (1, 4, 10.),
+                (4, 2, 10.),
+                (3, 4, 10.),
+                (0, 1, 15.),
+                (1, 2, 15.),
+                (2, 3, 15.),
+            ],
+        );
+
+        let minimum = SpanningTree::minimum(&nodes, &edges);
+        assert_eq!(minimum.edges.len(), 4);
+        assert_eq!(minimum.length, 40.);
+
+        let terminals = ["0", "2", "3"].map(String::from);
+        let steiner = SpanningTree::steiner(&nodes, &edges, &terminals).unwrap();
+        assert_eq!(steiner.length, 30.);
+
+        let nodes = [nodes, vec![test_node(5)]].concat();
+        let terminals = ["0", "5"].map(String::from);
+        assert!(SpanningTree::steiner(&nodes, &edges, &terminals).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use priority_queue::PriorityQueue;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use crate::{
+    models::{Edge, Node},
+    utils::FloatOrd,
+};
+
+// upper bounds in meters of the arc length bins, the last bin holds the longer arcs
+const LENGTH_BINS: [f32; 9] = [10., 25., 50., 100., 250., 500., 1000., 2500., 5000.];
+
+/// Figures about the loaded network, see [`GraphStatistics::new`].
+#[derive(Debug, Clone, Default)]
+pub struct GraphStatistics {
+    pub node_count: usize,
+    pub edge_count: usize,
+    // number of nodes by degree, incoming and outgoing arcs both count
+    pub degree_histogram: Vec<usize>,
+    // in meters
+    pub total_length: f64,
+    pub min_length: f32,
+    pub max_length: f32,
+    // number of arcs by length, see `length_bin_label`
+    pub length_histogram: Vec<usize>,
+    // (min longitude, min latitude, max longitude, max latitude)
+    pub bounding_box: Option<(f64, f64, f64, f64)>,
+    // (from id, to id, length in meters) of the longest shortest path found
+    pub diameter: Option<(String, String, f32)>,
+    // arcs with and without an arc in the opposite direction
+    pub two_way_arcs: usize,
+    pub one_way_arcs: usize,
+}
+
+impl GraphStatistics {
+    pub fn new(nodes: &[Node], edges: &[Edge]) -> Self {
+        let mut degrees: HashMap<&Node, usize> = nodes.iter().map(|node| (node, 0)).collect();
+        edges.iter().for_each(|edge| {
+            *degrees.entry(&edge.from).or_default() += 1;
+            *degrees.entry(&edge.to).or_default() += 1;
+        });
+        let max_degree = degrees.values().copied().max().unwrap_or_default();
+        let mut degree_histogram = vec![0; max_degree + 1];
+        degrees
+            .values()
+            .for_each(|degree| degree_histogram[*degree] += 1);
+
+        let mut length_histogram = vec![0; LENGTH_BINS.len() + 1];
+        edges.iter().for_each(|edge| {
+            let bin = LENGTH_BINS.partition_point(|bound| *bound <= edge.length);
+            length_histogram[bin] += 1;
+        });
+
+        let bounding_box = nodes.iter().fold(None, |bounds, node| {
+            let (min_lon, min_lat, max_lon, max_lat) =
+                bounds.unwrap_or((f64::MAX, f64::MAX, f64::MIN, f64::MIN));
+            Some((
+                min_lon.min(node.longitude),
+                min_lat.min(node.latitude),
+                max_lon.max(node.longitude),
+                max_lat.max(node.latitude),
+            ))
+        });
+
+        let arcs = edges
+            .iter()
+            .map(|edge| (edge.from.id.as_str(), edge.to.id.as_str()))
+            .collect::<HashSet<(&str, &str)>>();
+        let two_way_arcs = edges
+            .iter()
+            .filter(|edge| arcs.contains(&(edge.to.id.as_str(), edge.from.id.as_str())))
+            .count();
+
+        Self {
+            node_count: nodes.len(),
+            edge_count: edges.len(),
+            degree_histogram,
+            total_length: edges.iter().map(|edge| edge.length as f64).sum(),
+            min_length: edges
+                .iter()
+                .map(|edge| edge.length)
+                .fold(f32::MAX, f32::min),
+            max_length: edges.iter().map(|edge| edge.length).fold(0.0, f32::max),
+            length_histogram,
+            bounding_box,
+            diameter: estimate_diameter(edges),
+            two_way_arcs,
+            one_way_arcs: edges.len() - two_way_arcs,
+        }
+    }
+
+    pub fn mean_degree(&self) -> f32 {
+        if self.node_count == 0 {
+            return 0.0;
+        }
+        2.0 * self.edge_count as f32 / self.node_count as f32
+    }
+
+    pub fn mean_length(&self) -> f32 {
+        if self.edge_count == 0 {
+            return 0.0;
+        }
+        (self.total_length / self.edge_count as f64) as f32
+    }
+
+    pub fn one_way_ratio(&self) -> f32 {
+        if self.edge_count == 0 {
+            return 0.0;
+        }
+        self.one_way_arcs as f32 / self.edge_count as f32
+    }
+
+    pub fn length_bin_label(bin: usize) -> String {
+        match LENGTH_BINS.get(bin) {
+            Some(bound) if bin == 0 => format!("< {} m", bound),
+            Some(bound) => format!("{} - {} m", LENGTH_BINS[bin - 1], bound),
+            None => format!(">= {} m", LENGTH_BINS[LENGTH_BINS.len() - 1]),
+        }
+    }
+}
+
+// double sweep: the node farthest from the best connected one, then the node farthest
+// from it, ignoring the direction of the arcs, gives a lower bound of the diameter
+fn estimate_diameter(edges: &[Edge]) -> Option<(String, String, f32)> {
+    let mut adjacency: HashMap<&Node, Vec<(&Node, f32)>> = HashMap::new();
+    edges.iter().for_each(|edge| {
+        adjacency
+            .entry(&edge.from)
+            .or_default()
+            .push((&edge.to, edge.length));
+        adjacency
+            .entry(&edge.to)
+            .or_default()
+            .push((&edge.from, edge.length));
+    });
+
+    let hub = adjacency
+        .iter()
+        .max_by_key(|(node, neighbors)| (neighbors.len(), Reverse(&node.id)))
+        .map(|(node, _)| *node)?;
+    let (first_end, _) = farthest_node(&adjacency, hub);
+    let (second_end, length) = farthest_node(&adjacency, first_end);
+
+    Some((first_end.id.clone(), second_end.id.clone(), length))
+}
+
+fn farthest_node<'a>(
+    adjacency: &HashMap<&'a Node, Vec<(&'a Node, f32)>>,
+    source: &'a Node,
+) -> (&'a Node, f32) {
+    let mut distances: HashMap<&Node, f32> = HashMap::from([(source, 0.0)]);
+    let mut frontier = PriorityQueue::new();
+    frontier.push(source, Reverse(FloatOrd(0.0)));
+    let mut farthest = (source, 0.0);
+
+    while let Some((node, Reverse(FloatOrd(distance)))) = frontier.pop() {
+        if distance > farthest.1 {
+            farthest = (node, distance);
+        }
+        for (next, length) in adjacency.get(node).into_iter().flatten() {
+            let next_distance = distance + length;
+            if distances
+                .get(next)
+                .is_none_or(|known| next_distance < *known)
+            {
+                distances.insert(next, next_distance);
+                frontier.push(*next, Reverse(FloatOrd(next_distance)));
+            }
+        }
+    }
+
+    farthest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_graph;
+
+    #[test]
+    fn computes_statistics() {
+        // a two-way street 0 - 1 followed by the one-way arcs 1 -> 2 -> 3
+        let (mut nodes, edges) =
+            test_graph(4, &[(0, 1, 5.), (1, 0, 5.), (1, 2, 100.), (2, 3, 1000.)]);
+        // one degree apart along the equator
+        nodes
+            .iter_mut()
+            .enumerate()
+            .for_each(|(id, node)| node.longitude = id as f64);
+
+        let statistics = GraphStatistics::new(&nodes, &edges);
+
+        assert_eq!(statistics.degree_histogram, [0, 1, 2, 1]);
+        assert_eq!(statistics.total_length, 1110.);
+        assert_eq!(statistics.length_histogram.iter().sum::<usize>(), 4);
+        assert_eq!(statistics.length_histogram[0], 2);
+        assert_eq!(statistics.bounding_box, Some((0., 0., 3., 0.)));
+        assert_eq!(statistics.two_way_arcs, 2);
+        assert_eq!(statistics.one_way_ratio(), 0.5);
+        let (_, _, diameter) = statistics.diameter.unwrap();
+        assert_eq!(diameter, 1105.);
+        assert_eq!(GraphStatistics::length_bin_label(1), "10 - 25 m");
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{strongly_connected_components, weakly_connected_components};
+use crate::{
+    models::{Edge, Node},
+    parser::InvalidAttribute,
+};
+
+/// What is wrong with a map and how it splits into strongly connected components.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    // (from, to) ids of the arcs to unknown nodes, they are left out of the graph
+    pub dangling_arcs: Vec<(String, String)>,
+    // optional arc attributes that could not be read, the arcs are kept without them
+    pub invalid_attributes: Vec<InvalidAttribute>,
+    pub duplicate_ids: Vec<String>,
+    pub self_loops: Vec<Edge>,
+    // arcs whose length is zero or negative
+    pub invalid_lengths: Vec<Edge>,
+    // strongly connected, largest first
+    pub components: Vec<Vec<Node>>,
+    // parts of the map no arc leads to or from whatever the direction, the largest
+    // weakly connected component excepted
+    pub islands: Vec<Vec<Node>>,
+    // nodes that are a strongly connected component on their own without being an island,
+    // such as the ends of one-way dead ends; common on road maps, they are not issues
+    pub one_way_nodes: Vec<Node>,
+    component_of: HashMap<Node, usize>,
+}
+
+impl ValidationReport {
+    pub fn new(
+        nodes: &[Node],
+        edges: &[Edge],
+        dangling_arcs: Vec<(String, String)>,
+        invalid_attributes: Vec<InvalidAttribute>,
+    ) -> Self {
+        let mut seen = HashSet::new();
+        let mut duplicate_ids = nodes
+            .iter()
+            .filter(|node| !seen.insert(node.id.as_str()))
+            .map(|node| node.id.clone())
+            .collect::<Vec<String>>();
+        duplicate_ids.sort();
+        duplicate_ids.dedup();
+
+        let self_loops = edges
+            .iter()
+            .filter(|edge| edge.from == edge.to)
+            .cloned()
+            .collect();
+        let invalid_lengths = edges
+            .iter()
+            .filter(|edge| edge.length <= 0.0 || edge.length.is_nan())
+            .cloned()
+            .collect();
+
+        let components = strongly_connected_components(nodes, edges);
+        let islands = weakly_connected_components(nodes, edges)
+            .into_iter()
+            .skip(1)
+            .collect::<Vec<Vec<Node>>>();
+        let island_nodes = islands.iter().flatten().collect::<HashSet<&Node>>();
+        let one_way_nodes = components
+            .iter()
+            .skip(1)
+            .filter(|component| component.len() == 1 && !island_nodes.contains(&component[0]))
+            .map(|component| component[0].clone())
+            .collect();
+        let component_of = components
+            .iter()
+            .enumerate()
+            .flat_map(|(index, component)| component.iter().map(move |node| (node.clone(), index)))
+            .collect();
+
+        Self {
+            dangling_arcs,
+            invalid_attributes,
+            duplicate_ids,
+            self_loops,
+            invalid_lengths,
+            components,
+            islands,
+            one_way_nodes,
+            component_of,
+        }
+    }
+
+    /// Parts of the map cut off from the rest, routes can neither enter nor leave them.
+    pub fn island_count(&self) -> usize {
+        self.islands.len()
+    }
+
+    pub fn issue_count(&self) -> usize {
+        self.dangling_arcs.len()
+            + self.invalid_attributes.len()
+            + self.duplicate_ids.len()
+            + self.self_loops.len()
+            + self.invalid_lengths.len()
+            + self.island_count()
+    }
+
+    /// One line per issue, islands excepted.
+    pub fn issues(&self) -> impl Iterator<Item = String> + '_ {
+        let dangling_arcs = self
+            .dangling_arcs
+            .iter()
+            .map(|(from, to)| format!("Arc {} -> {} refers to an unknown node", from, to));
+        let invalid_attributes = self
+            .invalid_attributes
+            .iter()
+            .map(|(from, to, name, value)| {
+                format!(
+                    "Arc {} -> {} has an invalid {} \"{}\", it is ignored",
+                    from, to, name, value
+                )
+            });
+        let duplicate_ids = self
+            .duplicate_ids
+            .iter()
+            .map(|id| format!("Node id {} is used more than once", id));
+        let self_loops = self
+            .self_loops
+            .iter()
+            .map(|edge| format!("Arc {} -> {} is a self-loop", edge.from.id, edge.to.id));
+        let invalid_lengths = self.invalid_lengths.iter().map(|edge| {
+            format!(
+                "Arc {} -> {} has length {}",
+                edge.from.id, edge.to.id, edge.length
+            )
+        });
+
+        dangling_arcs
+            .chain(invalid_attributes)
+            .chain(duplicate_ids)
+            .chain(self_loops)
+            .chain(invalid_lengths)
+    }
+
+    /// Index of the component of the node, 0 being the largest.
+    pub fn component(&self, node: &Node) -> Option<usize> {
+        self.component_of.get(node).copied()
+    }
+
+    pub fn is_in_largest_component(&self, node: &Node) -> bool {
+        self.component(node) == Some(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{test_graph, test_node};
+
+    #[test]
+    fn reports_issues() {
+        // the last arc is a one-way dead end
+        let (_, edges) = test_graph(4, &[(0, 1, 10.), (1, 0, 0.), (2, 2, 5.), (1, 3, 5.)]);
+        let nodes = [0, 1, 1, 2, 3].map(test_node).to_vec();
+
+        let report = ValidationReport::new(
+            &nodes,
+            &edges,
+            vec![("0".into(), "9".into())],
+            vec![("0".into(), "1".into(), "speed".into(), "0".into())],
+        );
+
+        assert_eq!(report.dangling_arcs.len(), 1);
+        assert_eq!(report.invalid_attributes.len(), 1);
+        assert_eq!(report.duplicate_ids, ["1"]);
+        assert_eq!(report.self_loops.len(), 1);
+        assert_eq!(report.invalid_lengths.len(), 1);
+        assert_eq!(report.island_count(), 1);
+        assert_eq!(report.one_way_nodes, [test_node(3)]);
+        assert!(report.is_in_largest_component(&test_node(0)));
+        assert!(!report.is_in_largest_component(&test_node(2)));
+        assert_eq!(report.issue_count(), 6);
+    }
+}
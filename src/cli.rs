@@ -0,0 +1,85 @@
+use std::{fs::File, io::BufReader, path::PathBuf};
+
+use crate::{
+    analysis::{read_node_ids, DistanceMatrix},
+    export::{write_distance_matrix, MatrixFormat},
+    parser::parse_xml_streaming,
+    translator::Translator,
+};
+
+const USAGE: &str = "Usage:
+    luxembourg-rs                      starts the application
+    luxembourg-rs matrix --map <map.xml> (--nodes <ids.csv> | --ids <id,id,...>)
+                         --output <matrix.csv|matrix.json> [--format csv|json]";
+
+/// Runs the command given on the command line, `None` when there is none and the
+/// application has to start instead.
+pub fn run(args: &[String]) -> Option<Result<(), String>> {
+    let result = match args.first()?.as_str() {
+        "matrix" => run_matrix(&args[1..]),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        command => Err(format!("Unknown command: {}\n{}", command, USAGE)),
+    };
+    Some(result)
+}
+
+fn run_matrix(args: &[String]) -> Result<(), String> {
+    let mut map = None;
+    let mut ids = None;
+    let mut output = None;
+    let mut format = None;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value after {}\n{}", flag, USAGE))?;
+        match flag.as_str() {
+            "--map" => map = Some(PathBuf::from(value)),
+            "--nodes" => ids = Some(read_node_ids(&PathBuf::from(value))?),
+            "--ids" => ids = Some(value.split(',').map(|id| id.trim().to_string()).collect()),
+            "--output" => output = Some(PathBuf::from(value)),
+            "--format" => {
+                format = Some(match value.to_lowercase().as_str() {
+                    "csv" => MatrixFormat::Csv,
+                    "json" => MatrixFormat::Json,
+                    _ => return Err(format!("Unknown format: {}", value)),
+                })
+            }
+            _ => return Err(format!("Unknown option: {}\n{}", flag, USAGE)),
+        }
+    }
+
+    let missing = |name: &str| format!("Missing {}\n{}", name, USAGE);
+    let map = map.ok_or_else(|| missing("--map"))?;
+    let ids: Vec<String> = ids.ok_or_else(|| missing("--nodes or --ids"))?;
+    let output = output.ok_or_else(|| missing("--output"))?;
+    let format = format
+        .or_else(|| MatrixFormat::from_path(&output))
+        .unwrap_or(MatrixFormat::Csv);
+
+    let file = File::open(&map).map_err(|e| format!("Unable to open {}: {}", map.display(), e))?;
+    let total_bytes = file.metadata().map(|m| m.len()).unwrap_or_default();
+    let (nodes, _, neighbors, ..) = parse_xml_streaming(
+        BufReader::new(file),
+        total_bytes,
+        &mut Translator::default(),
+        |_| {},
+    )
+    .map_err(|e| format!("Unable to read {}: {}", map.display(), e))?;
+
+    let matrix = DistanceMatrix::compute(&nodes, &neighbors, &ids)?;
+    write_distance_matrix(&matrix, format, &output)
+        .map_err(|e| format!("Unable to write {}: {}", output.display(), e))?;
+    println!(
+        "Wrote the {} x {} distance matrix to {}",
+        ids.len(),
+        ids.len(),
+        output.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use egui::{Pos2, Rect, Vec2};
+
+/// Bar chart of counts, hovering a bar shows its label and count.
+///
+/// ## Example:
+/// ``` ignore
+/// histogram(ui, &[("1".to_string(), 12), ("2".to_string(), 40)]);
+/// ```
+pub fn histogram(ui: &mut egui::Ui, bins: &[(String, usize)]) -> egui::Response {
+    let (response, painter) = ui.allocate_painter(Vec2::new(240., 60.), egui::Sense::hover());
+    let rect = response.rect;
+    if bins.is_empty() {
+        return response;
+    }
+
+    let highest = bins.iter().map(|(_, count)| *count).max().unwrap().max(1);
+    let bar_width = rect.width() / bins.len() as f32;
+    let hovered = response
+        .hover_pos()
+        .map(|pos| ((pos.x - rect.left()) / bar_width) as usize);
+
+    bins.iter().enumerate().for_each(|(index, (_, count))| {
+        let height = rect.height() * *count as f32 / highest as f32;
+        let bar = Rect::from_min_max(
+            Pos2::new(
+                rect.left() + index as f32 * bar_width,
+                rect.bottom() - height,
+            ),
+            Pos2::new(
+                rect.left() + (index + 1) as f32 * bar_width - 1.,
+                rect.bottom(),
+            ),
+        );
+        let color = if hovered == Some(index) {
+            ui.visuals().strong_text_color()
+        } else {
+            ui.visuals().selection.bg_fill
+        };
+        painter.rect_filled(bar, 0., color);
+    });
+
+    match hovered.and_then(|index| bins.get(index)) {
+        Some((label, count)) => response.on_hover_text(format!("{}: {}", label, count)),
+        None => response,
+    }
+}
@@ -1,5 +1,11 @@
+mod histogram;
+mod parameter;
 mod toggle_switch;
 
+#[allow(unused_imports)]
+pub use histogram::histogram;
+#[allow(unused_imports)]
+pub use parameter::parameter_widget;
 #[allow(unused_imports)]
 pub use toggle_switch::toggle;
 #[allow(unused_imports)]
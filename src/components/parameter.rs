@@ -0,0 +1,41 @@
+use crate::{
+    algorithms::{Parameter, ParameterKind, ParameterValue},
+    components::toggle,
+};
+
+/// Slider or toggle editing the value of an algorithm parameter, depending on its kind.
+///
+/// ## Example:
+/// ``` ignore
+/// parameter_widget(ui, &parameter, &mut value);
+/// ```
+pub fn parameter_widget(
+    ui: &mut egui::Ui,
+    parameter: &Parameter,
+    value: &mut ParameterValue,
+) -> egui::Response {
+    match (parameter.kind, value) {
+        (ParameterKind::Number { min, max, step, .. }, ParameterValue::Number(number)) => ui
+            .add(
+                egui::Slider::new(number, min..=max)
+                    .step_by(step)
+                    .text(parameter.label),
+            )
+            .on_hover_text(parameter.description),
+        (_, ParameterValue::Flag(flag)) => ui
+            .horizontal(|ui| {
+                let response = ui.add(toggle(flag));
+                ui.label(parameter.label);
+                response
+            })
+            .inner
+            .on_hover_text(parameter.description),
+        // a value of the wrong kind, e.g. from a session saved by another version
+        (_, value) => {
+            *value = parameter.default_value();
+            let mut response = ui.label(parameter.label);
+            response.mark_changed();
+            response
+        }
+    }
+}
@@ -1,73 +1,278 @@
-use egui::Pos2;
 use enum_iterator::Sequence;
-use priority_queue::PriorityQueue;
+use log::debug;
+use serde::{Deserialize, Serialize};
 use std::{
-    cmp::Reverse,
     collections::{HashMap, HashSet},
     fmt,
-    hash::Hash,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc,
+    },
 };
 
 use crate::{
-    models::{Edge, Node},
-    utils::{euclidean_distance, manhattan_distance, FloatOrd},
+    algorithms::{
+        AlgorithmRegistry, ParameterValues, PathfindingAlgorithm, Query, RunOutput, SearchControl,
+        SearchGraph, Solutions, TurnTable,
+    },
+    models::{Edge, Node, TurnRestriction},
+    scenario::Incident,
+    traffic::TrafficProfiles,
+    utils::{unordered_hash, FloatOrd, LruCache},
 };
 
-// this is an arbitrary value found by trial and error
-const MULTIPLICITY_BASE: f32 = 13_000.;
+// km/h, the fastest speed assumed by the heuristic when minimising time
+const HEURISTIC_SPEED: f32 = 130.;
 
-// (start, end, is_marking_passed_edges, algorithm_type, astar_weight, use_manhattan)
-type RunArgs = (Node, Node, bool, AlgorithmType, FloatOrd<f32>, bool);
-// note: the assumption is that the neighbors will not change during the lifetime of the context
-//      if the edges would change between runs, the neighbors hashmap should also be included in the RunArgs
+// minutes between two departures of the day profile
+const DAY_PROFILE_STEP: u32 = 60;
+const DAY_PROFILE_DEPARTURES: usize = (24 * 60 / DAY_PROFILE_STEP) as usize;
 
-// (selected_edges, passed_edges, total_cost)
-type RunOutput = (HashSet<Edge>, HashSet<Edge>, f32);
+// runs kept in the cache, a day profile takes 24
+const RUN_CACHE_CAPACITY: usize = 256;
 
-#[derive(PartialEq, Eq, Hash, Sequence, Copy, Clone, Debug)]
-pub enum AlgorithmType {
-    AStar,
-    HybridAStar,
-    Dijkstra,
+// everything a run depends on, the runs are cached by it
+// note: the map hash covers the graph, the incidents, the traffic profiles and the turn
+//      restrictions, `set_graph` has to be called whenever the graph changes
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct RunArgs {
+    map_hash: u64,
+    start: String,
+    end: String,
+    mark_passed_edges: bool,
+    algorithm: String,
+    parameters: ParameterValues,
+    cost_model: CostModel,
+    // minutes after midnight, only set when there are traffic profiles
+    departure: Option<u32>,
+    use_turn_restrictions: bool,
+    allow_u_turns: bool,
+    waypoints: Vec<String>,
+}
+
+// an arc by the ids of its nodes
+type ArcIds = (String, String);
+
+// a RunOutput with the nodes and arcs by id, which is lighter to keep and to store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredRun {
+    path: Vec<String>,
+    selected_edges: Vec<ArcIds>,
+    passed_edges: Vec<ArcIds>,
+    total_cost: f32,
+    route_length: f32,
+    route_duration: f32,
+    negative_cycle: Vec<ArcIds>,
+    solutions: Solutions,
 }
 
-impl fmt::Display for AlgorithmType {
+/// The cached runs as saved between sessions, from the least to the most recently used.
+#[derive(Serialize, Deserialize, Default)]
+pub struct PersistedRuns(Vec<(RunArgs, StoredRun)>);
+
+// (departure in minutes, route duration in seconds, index of the route among the distinct ones)
+pub type DayProfile = Vec<(u32, f32, usize)>;
+
+// (control, (departure, route duration, path) of the departures searched so far) of the day
+// profile being searched in the background
+type PendingProfile = (Arc<SearchControl>, Vec<(u32, f32, Vec<Node>)>);
+
+// (neighbors, incidents, traffic, turns) shared with a search running in the background
+type SharedGraph = (
+    Arc<HashMap<Node, Vec<Edge>>>,
+    Arc<HashMap<Edge, Incident>>,
+    Option<Arc<TrafficProfiles>>,
+    Arc<TurnTable>,
+);
+
+#[derive(PartialEq, Eq, Hash, Sequence, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum CostMetric {
+    #[default]
+    Distance,
+    Time,
+    Custom,
+}
+
+impl fmt::Display for CostMetric {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            AlgorithmType::AStar => write!(f, "A Star"),
-            AlgorithmType::HybridAStar => write!(f, "Hybrid A Star"),
-            AlgorithmType::Dijkstra => write!(f, "Dijkstra"),
+            CostMetric::Distance => write!(f, "Shortest distance"),
+            CostMetric::Time => write!(f, "Fastest time"),
+            CostMetric::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+/// What is minimised by the search, the weights only apply to [`CostMetric::Custom`].
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct CostModel {
+    pub metric: CostMetric,
+    // per meter
+    pub distance_weight: FloatOrd<f32>,
+    // per second
+    pub time_weight: FloatOrd<f32>,
+}
+
+impl CostModel {
+    /// Cost of an arc of the given length (m) driven in the given duration (s).
+    pub fn cost(&self, length: f32, duration: f32) -> f32 {
+        match self.metric {
+            CostMetric::Distance => length,
+            CostMetric::Time => duration,
+            CostMetric::Custom => self.distance_weight.0 * length + self.time_weight.0 * duration,
+        }
+    }
+
+    /// Converts a straight line distance in meters into a cost estimate.
+    pub fn heuristic_scale(&self) -> f32 {
+        let seconds_per_meter = 3.6 / HEURISTIC_SPEED;
+        match self.metric {
+            CostMetric::Distance => 1.0,
+            CostMetric::Time => seconds_per_meter,
+            CostMetric::Custom => self.distance_weight.0 + self.time_weight.0 * seconds_per_meter,
+        }
+    }
+
+    /// The total cost of a route, in the unit of the metric.
+    pub fn format_cost(&self, total_cost: f32) -> String {
+        match self.metric {
+            CostMetric::Distance => format!("Distance: {:.3} km", total_cost / 1000.),
+            CostMetric::Time => format!("Travel time: {:.1} min", total_cost / 60.),
+            CostMetric::Custom => format!("Cost: {:.1}", total_cost),
+        }
+    }
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            metric: CostMetric::Distance,
+            distance_weight: FloatOrd(1.0),
+            time_weight: FloatOrd(1.0),
         }
     }
 }
 
 pub struct AlgorithmContext {
     pub is_marking_passed_edges: bool,
-    pub algorithm_type: AlgorithmType,
-    pub astar_weight: FloatOrd<f32>,
+    // values set in the UI, shared by the algorithms declaring the same key
+    pub parameters: ParameterValues,
     pub total_cost: f32,
-    pub use_manhattan: bool,
+    pub cost_model: CostModel,
+    // length (m) and duration (s) of the route, whatever the metric
+    pub route_length: f32,
+    pub route_duration: f32,
+    // minutes after midnight, only matters with traffic profiles
+    pub departure: u32,
+    // runs the edge-based search, which knows which arc a node was reached from
+    pub use_turn_restrictions: bool,
+    // turning back onto the arc just driven, always allowed at dead ends
+    pub allow_u_turns: bool,
+    // keeps the cached runs between sessions
+    pub persist_runs: bool,
+    // nodes the route goes through between the start and the end, in order
+    pub waypoints: Vec<Node>,
+    registry: AlgorithmRegistry,
+    algorithm: Arc<dyn PathfindingAlgorithm>,
+    path: Vec<Node>,
     selected_edges: HashSet<Edge>,
     passed_edges: HashSet<Edge>,
+    // shared with the searches running in the background, copied when edited meanwhile
+    incidents: Arc<HashMap<Edge, Incident>>,
+    traffic: Option<Arc<TrafficProfiles>>,
+    turns: Arc<TurnTable>,
+    // arcs of the negative cycle found by the last run, if any
+    negative_cycle: Vec<Edge>,
+    solutions: Solutions,
+    // (cost model, departure) the last negative cost arc was looked for with
+    negative_cost_check: Option<((CostModel, u32), Option<Edge>)>,
+    // hash of the arcs alone, and of everything else the searches depend on on top of it
+    graph_hash: u64,
+    map_hash: u64,
     current_run_args: Option<RunArgs>,
-    computed_runs: HashMap<RunArgs, RunOutput>, // (start, end, is_marking_passed_edges) -> (selected_edges, passed_edges)
+    // control of the search running in the background for the current run args, if any
+    pending_run: Option<Arc<SearchControl>>,
+    tx_run: Sender<(RunArgs, RunOutput)>,
+    rx_run: Receiver<(RunArgs, RunOutput)>,
+    pending_profile: Option<PendingProfile>,
+    tx_profile: Sender<(RunArgs, RunOutput)>,
+    rx_profile: Receiver<(RunArgs, RunOutput)>,
+    computed_runs: LruCache<RunArgs, StoredRun>,
 }
 
 impl AlgorithmContext {
     pub fn new() -> Self {
+        let registry = AlgorithmRegistry::new();
+        let algorithm = registry.get("AStar").unwrap().clone();
+        let (tx_run, rx_run) = std::sync::mpsc::channel();
+        let (tx_profile, rx_profile) = std::sync::mpsc::channel();
         Self {
             is_marking_passed_edges: false,
-            algorithm_type: AlgorithmType::HybridAStar,
-            astar_weight: FloatOrd(1.0),
+            parameters: ParameterValues::default(),
             total_cost: 0.0,
-            use_manhattan: true,
+            cost_model: CostModel::default(),
+            route_length: 0.0,
+            route_duration: 0.0,
+            departure: 8 * 60,
+            use_turn_restrictions: false,
+            allow_u_turns: true,
+            persist_runs: false,
+            waypoints: Vec::new(),
+            registry,
+            algorithm,
+            path: Vec::new(),
             selected_edges: HashSet::new(),
             passed_edges: HashSet::new(),
+            incidents: Arc::default(),
+            traffic: None,
+            turns: Arc::default(),
+            negative_cycle: Vec::new(),
+            solutions: Vec::new(),
+            negative_cost_check: None,
+            graph_hash: 0,
+            map_hash: 0,
             current_run_args: None,
-            computed_runs: HashMap::new(),
+            pending_run: None,
+            tx_run,
+            rx_run,
+            pending_profile: None,
+            tx_profile,
+            rx_profile,
+            computed_runs: LruCache::new(RUN_CACHE_CAPACITY),
+        }
+    }
+
+    pub fn registry(&self) -> &AlgorithmRegistry {
+        &self.registry
+    }
+
+    pub fn algorithm(&self) -> &dyn PathfindingAlgorithm {
+        self.algorithm.as_ref()
+    }
+
+    /// Selects the registered algorithm with the given id, returns whether there is one.
+    pub fn set_algorithm(&mut self, id: &str) -> bool {
+        match self.registry.get(id) {
+            Some(algorithm) => {
+                self.algorithm = algorithm.clone();
+                true
+            }
+            None => false,
         }
     }
 
+    /// The values of the parameters of the selected algorithm, defaults included.
+    pub fn parameter_values(&self) -> ParameterValues {
+        self.parameters.resolve(self.algorithm.parameters())
+    }
+
+    pub fn reset_parameters(&mut self) {
+        self.algorithm
+            .parameters()
+            .iter()
+            .for_each(|parameter| self.parameters.remove(parameter.key));
+    }
+
     pub fn is_edge_selected(&self, edge: &Edge) -> bool {
         self.selected_edges.contains(edge)
     }
@@ -76,214 +281,876 @@ impl AlgorithmContext {
         self.passed_edges.contains(edge)
     }
 
+    /// Nodes of the last computed route, from start to end.
+    pub fn path(&self) -> &[Node] {
+        &self.path
+    }
+
+    pub fn passed_edges(&self) -> &HashSet<Edge> {
+        &self.passed_edges
+    }
+
+    /// (bound, cost) of the routes the last run found, the current one last.
+    pub fn solutions(&self) -> &Solutions {
+        &self.solutions
+    }
+
+    /// How many times the best cost the route may cost, `None` without a guarantee.
+    pub fn optimality_bound(&self) -> Option<f32> {
+        self.solutions.last()?.0
+    }
+
+    /// Arcs of the negative cycle that kept the last run from finding a route.
+    pub fn negative_cycle(&self) -> &[Edge] {
+        &self.negative_cycle
+    }
+
+    /// An arc costing less than nothing with the current cost model, on which
+    /// Dijkstra and A* cannot be trusted.
+    pub fn negative_cost_edge(&mut self, edges: &[Edge]) -> Option<&Edge> {
+        let key = (self.cost_model, self.departure);
+        if self
+            .negative_cost_check
+            .as_ref()
+            .is_none_or(|(k, _)| *k != key)
+        {
+            let CostModel {
+                distance_weight,
+                time_weight,
+                ..
+            } = self.cost_model;
+            // only custom costs with a negative weight can get below zero on their own,
+            // with the other metrics it takes an incident
+            let has_negative_weight = self.cost_model.metric == CostMetric::Custom
+                && (distance_weight.0 < 0.0 || time_weight.0 < 0.0);
+            let at = self.departure as f32 * 60.;
+            let edge = edges
+                .iter()
+                .filter(|edge| has_negative_weight || self.incidents.contains_key(*edge))
+                .find(|edge| {
+                    let duration = self
+                        .traffic
+                        .as_ref()
+                        .and_then(|traffic| traffic.travel_time(edge, at))
+                        .unwrap_or_else(|| edge.duration());
+                    let cost = self.cost_model.cost(edge.length, duration);
+                    let cost = match self.incidents.get(*edge) {
+                        Some(incident) => incident.apply(cost),
+                        None => Some(cost),
+                    };
+                    // NaN costs are as bad as negative ones
+                    cost.is_some_and(|cost| cost < 0.0 || cost.is_nan())
+                })
+                .cloned();
+            self.negative_cost_check = Some((key, edge));
+        }
+        self.negative_cost_check.as_ref()?.1.as_ref()
+    }
+
+    pub fn incident(&self, edge: &Edge) -> Option<Incident> {
+        self.incidents.get(edge).copied()
+    }
+
+    pub fn incidents(&self) -> &HashMap<Edge, Incident> {
+        &self.incidents
+    }
+
+    pub fn set_incident(&mut self, edge: Edge, incident: Incident) {
+        Arc::make_mut(&mut self.incidents).insert(edge, incident);
+        self.invalidate();
+    }
+
+    pub fn clear_incident(&mut self, edge: &Edge) {
+        if Arc::make_mut(&mut self.incidents).remove(edge).is_some() {
+            self.invalidate();
+        }
+    }
+
+    pub fn clear_incidents(&mut self) {
+        if !self.incidents.is_empty() {
+            self.incidents = Arc::default();
+            self.invalidate();
+        }
+    }
+
+    pub fn traffic(&self) -> Option<&TrafficProfiles> {
+        self.traffic.as_deref()
+    }
+
+    pub fn set_traffic(&mut self, traffic: Option<TrafficProfiles>) {
+        self.traffic = traffic.map(Arc::new);
+        self.invalidate();
+    }
+
+    pub fn turn_restriction_count(&self) -> usize {
+        self.turns.len()
+    }
+
+    pub fn set_turn_restrictions(&mut self, restrictions: &[TurnRestriction]) {
+        self.turns = Arc::new(
+            restrictions
+                .iter()
+                .map(|turn| {
+                    (
+                        (turn.from.clone(), turn.via.clone(), turn.to.clone()),
+                        turn.penalty,
+                    )
+                })
+                .collect(),
+        );
+        self.invalidate();
+    }
+
     pub fn is_new_args(&self, start: &Node, end: &Node) -> bool {
         match self.current_run_args.as_ref() {
             None => true,
-            Some((s, e, is_marking, algorithm_type, astar_weight, use_manhattan)) => {
-                s != start
-                    || e != end
-                    || *is_marking != self.is_marking_passed_edges
-                    || *algorithm_type != self.algorithm_type
-                    || *astar_weight != self.astar_weight
-                    || *use_manhattan != self.use_manhattan
-            }
+            Some(args) => *args != self.run_args(start, end),
         }
     }
 
-    pub fn compute_path(&mut self, from: &Node, to: &Node, neighbors: &HashMap<Node, Vec<Edge>>) {
+    fn run_args(&self, from: &Node, to: &Node) -> RunArgs {
+        RunArgs {
+            map_hash: self.map_hash,
+            start: from.id.clone(),
+            end: to.id.clone(),
+            mark_passed_edges: self.is_marking_passed_edges,
+            algorithm: self.algorithm.id().to_string(),
+            parameters: self.parameter_values(),
+            cost_model: self.cost_model,
+            departure: self.traffic.as_ref().map(|_| self.departure),
+            use_turn_restrictions: self.use_turn_restrictions,
+            allow_u_turns: self.allow_u_turns,
+            waypoints: self.waypoints.iter().map(|node| node.id.clone()).collect(),
+        }
+    }
+
+    // the start, the waypoints and the end
+    fn stops(&self, from: &Node, to: &Node) -> Vec<Node> {
+        [vec![from.clone()], self.waypoints.clone(), vec![to.clone()]].concat()
+    }
+
+    fn cached_run(
+        &mut self,
+        run_args: &RunArgs,
+        neighbors: &HashMap<Node, Vec<Edge>>,
+    ) -> Option<RunOutput> {
+        resolve_run(self.computed_runs.get(run_args)?, neighbors)
+    }
+
+    /// Starts searching the route on a background thread, the route is shown once
+    /// [`Self::receive_path`] gets it. A search still running is cancelled.
+    pub fn request_path(
+        &mut self,
+        from: &Node,
+        to: &Node,
+        neighbors: &Arc<HashMap<Node, Vec<Edge>>>,
+        ctx: &egui::Context,
+    ) {
         if !self.is_new_args(from, to) {
             return;
         }
-        if let std::collections::hash_map::Entry::Vacant(e) = self.computed_runs.entry((
-            from.clone(),
-            to.clone(),
-            self.is_marking_passed_edges,
-            self.algorithm_type,
-            self.astar_weight,
-            self.use_manhattan,
-        )) {
-            let run_output = run_pathfinding_algorithm(
-                from,
-                to,
-                neighbors,
-                self.is_marking_passed_edges,
-                self.algorithm_type,
-                self.astar_weight,
-                self.use_manhattan,
-            );
-            e.insert(run_output);
+        self.cancel_search();
+        self.clear_route();
+        let run_args = self.run_args(from, to);
+        self.current_run_args = Some(run_args.clone());
+        if let Some(run_output) = self.cached_run(&run_args, neighbors) {
+            self.tx_run.send((run_args, run_output)).unwrap();
+            return;
         }
-        // write the run outputs from the computed_runs hashmap into the context
-        (self.selected_edges, self.passed_edges, self.total_cost) = self
-            .computed_runs
-            .get(&(
-                from.clone(),
-                to.clone(),
-                self.is_marking_passed_edges,
-                self.algorithm_type,
-                self.astar_weight,
-                self.use_manhattan,
-            ))
-            .unwrap()
-            .clone();
-        // update the current_run_args
-        self.current_run_args = Some((
-            from.clone(),
-            to.clone(),
-            self.is_marking_passed_edges,
-            self.algorithm_type,
-            self.astar_weight,
-            self.use_manhattan,
-        ));
-    }
-
-    pub fn is_using_astar(&self) -> bool {
-        self.algorithm_type == AlgorithmType::AStar
-            || self.algorithm_type == AlgorithmType::HybridAStar
+
+        let control = Arc::new(SearchControl::default());
+        self.pending_run = Some(control.clone());
+        let tx_run = self.tx_run.clone();
+        let algorithm = self.algorithm.clone();
+        let stops = self.stops(from, to);
+        let (neighbors, incidents, traffic, turns) = self.shared_graph(neighbors);
+        let ctx = ctx.clone();
+
+        tokio::task::spawn_blocking(move || {
+            debug!("Searching with {}...", algorithm.name());
+            let graph = SearchGraph::new(&neighbors, &incidents, traffic.as_deref(), &turns);
+            let run_output = run(algorithm.as_ref(), &run_args, &stops, &graph, &control);
+            if control.is_cancelled() {
+                debug!("Search cancelled after {} steps", control.steps());
+                return;
+            }
+            debug!("Search done in {} steps", control.steps());
+            // the receiver is gone when the runs have been invalidated in the meantime
+            let _ = tx_run.send((run_args, run_output));
+            ctx.request_repaint();
+        });
     }
-}
 
-fn run_pathfinding_algorithm(
-    start: &Node,
-    end: &Node,
-    neighbors: &HashMap<Node, Vec<Edge>>,
-    mark_passed_edges: bool,
-    algorithm_type: AlgorithmType,
-    heuristic_weight: FloatOrd<f32>,
-    use_manhattan: bool,
-) -> RunOutput {
-    let mut passed_edges = HashSet::new();
-    let mut total_cost: f32 = 0.0;
+    /// Shows the route of the last request once it has been found, returns whether it has.
+    pub fn receive_path(&mut self) -> bool {
+        let mut received = false;
+        while let Ok((run_args, run_output)) = self.rx_run.try_recv() {
+            // a search finishing right as it was cancelled is still worth keeping
+            if self.current_run_args.as_ref() == Some(&run_args) {
+                self.pending_run = None;
+                self.show_run(run_output.clone());
+                received = true;
+            }
+            self.computed_runs.insert(run_args, store_run(&run_output));
+        }
+        received
+    }
 
-    let mut frontier: PriorityQueue<NodeData, Reverse<FloatOrd<f32>>> = PriorityQueue::new();
-    frontier.push(NodeData::from(start.clone()), Reverse(FloatOrd(0.0)));
+    pub fn is_searching(&self) -> bool {
+        self.pending_run.is_some()
+    }
 
-    let mut came_from: HashMap<NodeData, Option<NodeData>> = HashMap::new();
-    let mut cost_so_far: HashMap<NodeData, FloatOrd<f32>> = HashMap::new();
+    /// Steps the running search has taken so far, e.g. nodes expanded.
+    pub fn search_steps(&self) -> Option<usize> {
+        Some(self.pending_run.as_ref()?.steps())
+    }
 
-    came_from.insert(NodeData::from(start.clone()), None);
-    cost_so_far.insert(NodeData::from(start.clone()), FloatOrd(0.0));
+    /// Stops the running search, the route stays empty until the arguments change.
+    pub fn cancel_search(&mut self) {
+        if let Some(control) = self.pending_run.take() {
+            control.cancel();
+        }
+    }
+
+    fn show_run(&mut self, run_output: RunOutput) {
+        RunOutput {
+            path: self.path,
+            selected_edges: self.selected_edges,
+            passed_edges: self.passed_edges,
+            total_cost: self.total_cost,
+            route_length: self.route_length,
+            route_duration: self.route_duration,
+            negative_cycle: self.negative_cycle,
+            solutions: self.solutions,
+        } = run_output;
+    }
+
+    fn clear_route(&mut self) {
+        self.path.clear();
+        self.selected_edges.clear();
+        self.passed_edges.clear();
+        self.negative_cycle.clear();
+        self.solutions.clear();
+        self.total_cost = 0.0;
+        self.route_length = 0.0;
+        self.route_duration = 0.0;
+    }
 
-    while !frontier.is_empty() {
-        let current = frontier.pop().unwrap().0;
+    /// Starts searching the route from `from` to `to`, through the waypoints, for departures
+    /// throughout the day on a background thread, the profile comes from
+    /// [`Self::receive_day_profile`]. A day profile still being searched is cancelled.
+    pub fn request_day_profile(
+        &mut self,
+        from: &Node,
+        to: &Node,
+        neighbors: &Arc<HashMap<Node, Vec<Edge>>>,
+        ctx: &egui::Context,
+    ) {
+        self.cancel_day_profile();
+        // a fresh channel drops the departures of the previous profile
+        (self.tx_profile, self.rx_profile) = std::sync::mpsc::channel();
+        let control = Arc::new(SearchControl::default());
+        self.pending_profile = Some((control.clone(), Vec::new()));
 
-        if current.node == *end {
-            total_cost = cost_so_far.get(&current).map(|f| f.0).unwrap_or(0.0);
-            break;
+        let mut departures = Vec::new();
+        for departure in (0..24 * 60).step_by(DAY_PROFILE_STEP as usize) {
+            let mut run_args = self.run_args(from, to);
+            run_args.departure = Some(departure);
+            match self.cached_run(&run_args, neighbors) {
+                Some(run_output) => self.tx_profile.send((run_args, run_output)).unwrap(),
+                None => departures.push(run_args),
+            }
         }
+        if departures.is_empty() {
+            return;
+        }
+
+        let tx_profile = self.tx_profile.clone();
+        let algorithm = self.algorithm.clone();
+        let stops = self.stops(from, to);
+        let (neighbors, incidents, traffic, turns) = self.shared_graph(neighbors);
+        let ctx = ctx.clone();
 
-        for next in neighbors.get(&current.node).unwrap() {
-            if mark_passed_edges {
-                passed_edges.insert(next.clone());
+        tokio::task::spawn_blocking(move || {
+            let graph = SearchGraph::new(&neighbors, &incidents, traffic.as_deref(), &turns);
+            for run_args in departures {
+                let run_output = run(algorithm.as_ref(), &run_args, &stops, &graph, &control);
+                if control.is_cancelled() {
+                    debug!("Day profile cancelled");
+                    return;
+                }
+                // the receiver is gone when the profile has been requested again in the meantime
+                if tx_profile.send((run_args, run_output)).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
             }
+        });
+    }
 
-            let new_cost = *cost_so_far.get(&current).unwrap() + FloatOrd(next.length);
-
-            let next_node_data = NodeData::from(next.to.clone());
-            if !cost_so_far.contains_key(&next_node_data)
-                || new_cost < *cost_so_far.get(&next_node_data).unwrap()
-            {
-                cost_so_far.insert(next_node_data.clone(), new_cost);
-
-                let priority = match algorithm_type {
-                    AlgorithmType::AStar => FloatOrd(heuristic(&next.to, end, None, use_manhattan)),
-                    AlgorithmType::HybridAStar => {
-                        new_cost
-                            + FloatOrd(heuristic(
-                                &next.to,
-                                end,
-                                Some(heuristic_weight),
-                                use_manhattan,
-                            ))
+    /// The day profile of the last request once every departure has been searched.
+    pub fn receive_day_profile(&mut self) -> Option<DayProfile> {
+        let (_, runs) = self.pending_profile.as_mut()?;
+        while let Ok((run_args, run_output)) = self.rx_profile.try_recv() {
+            runs.push((
+                run_args.departure.unwrap(),
+                run_output.route_duration,
+                run_output.path.clone(),
+            ));
+            self.computed_runs.insert(run_args, store_run(&run_output));
+        }
+        if runs.len() < DAY_PROFILE_DEPARTURES {
+            return None;
+        }
+
+        let (_, mut runs) = self.pending_profile.take()?;
+        runs.sort_by_key(|(departure, ..)| *departure);
+        let mut routes: Vec<Vec<Node>> = Vec::new();
+        let profile = runs
+            .into_iter()
+            .map(|(departure, duration, path)| {
+                let route = match routes.iter().position(|route| *route == path) {
+                    Some(route) => route,
+                    None => {
+                        routes.push(path);
+                        routes.len() - 1
                     }
-                    AlgorithmType::Dijkstra => new_cost,
                 };
+                (departure, duration, route)
+            })
+            .collect();
+        Some(profile)
+    }
 
-                frontier.push(next_node_data.clone(), Reverse(priority));
-                came_from.insert(next_node_data.clone(), Some(current.clone()));
-            }
+    /// (departures searched so far, departures of the profile) while a day profile is searched.
+    pub fn day_profile_progress(&self) -> Option<(usize, usize)> {
+        let (_, runs) = self.pending_profile.as_ref()?;
+        Some((runs.len(), DAY_PROFILE_DEPARTURES))
+    }
+
+    pub fn cancel_day_profile(&mut self) {
+        if let Some((control, _)) = self.pending_profile.take() {
+            control.cancel();
         }
     }
 
-    (
-        reconstruct_path(&came_from, start, end),
-        passed_edges,
-        total_cost,
-    )
-}
+    // only the pointers are copied, the UI thread never waits for the graph to be cloned
+    fn shared_graph(&self, neighbors: &Arc<HashMap<Node, Vec<Edge>>>) -> SharedGraph {
+        (
+            neighbors.clone(),
+            self.incidents.clone(),
+            self.traffic.clone(),
+            self.turns.clone(),
+        )
+    }
 
-fn reconstruct_path(
-    came_from: &HashMap<NodeData, Option<NodeData>>,
-    start: &Node,
-    end: &Node,
-) -> HashSet<Edge> {
-    let _str = format!("{:?}", came_from);
+    /// Fingerprints the arcs the routes are searched on, for when the map is loaded or edited.
+    pub fn set_graph(&mut self, neighbors: &HashMap<Node, Vec<Edge>>) {
+        self.graph_hash = unordered_hash(neighbors.values().flatten().map(|edge| {
+            let numbers = [
+                edge.from.position.x,
+                edge.from.position.y,
+                edge.to.position.x,
+                edge.to.position.y,
+                edge.length,
+                edge.duration(),
+            ];
+            (&edge.from.id, &edge.to.id, numbers.map(FloatOrd))
+        }));
+        self.invalidate();
+    }
+
+    pub fn cached_run_count(&self) -> usize {
+        self.computed_runs.len()
+    }
+
+    pub fn clear_cached_runs(&mut self) {
+        self.computed_runs.clear();
+    }
 
-    let mut selected_edges = HashSet::new();
-    let mut current = NodeData::from(end.clone());
+    /// The cached runs worth saving, those marking the passed edges are left out as they
+    /// are the heaviest by far and quick to search again.
+    pub fn persisted_runs(&self) -> PersistedRuns {
+        let runs = self.computed_runs.iter();
+        PersistedRuns(
+            runs.filter(|(args, _)| !args.mark_passed_edges)
+                .map(|(args, run)| (args.clone(), run.clone()))
+                .collect(),
+        )
+    }
 
-    while current.node != *start {
-        let next = came_from.get(&current).unwrap().clone().unwrap();
-        selected_edges.insert(Edge::new(next.node.clone(), current.node.clone(), 0.0));
-        current = next.clone();
+    /// Caches runs saved by a previous session, only the ones of the same map get reused.
+    pub fn restore_runs(&mut self, runs: PersistedRuns) {
+        runs.0
+            .into_iter()
+            .for_each(|(args, run)| self.computed_runs.insert(args, run));
     }
 
-    selected_edges
+    // the runs cached for other search inputs are kept but no longer match
+    fn invalidate(&mut self) {
+        self.cancel_search();
+        self.cancel_day_profile();
+        // a fresh channel drops the routes still being searched with the previous inputs
+        (self.tx_run, self.rx_run) = std::sync::mpsc::channel();
+        (self.tx_profile, self.rx_profile) = std::sync::mpsc::channel();
+        let incidents = self.incidents.iter().map(|(edge, incident)| {
+            let factor = match incident {
+                Incident::Closed => None,
+                Incident::Slowed { factor } => Some(FloatOrd(*factor)),
+            };
+            (&edge.from.id, &edge.to.id, factor)
+        });
+        let turns = self
+            .turns
+            .iter()
+            .map(|(turn, penalty)| (turn, penalty.map(FloatOrd)));
+        self.map_hash = unordered_hash([
+            self.graph_hash,
+            unordered_hash(incidents),
+            unordered_hash(turns),
+            unordered_hash(&self.traffic),
+        ]);
+        self.current_run_args = None;
+        self.negative_cost_check = None;
+        self.clear_route();
+    }
 }
 
-fn heuristic(a: &Node, b: &Node, multiplier: Option<FloatOrd<f32>>, use_manhattan: bool) -> f32 {
-    // Apply a base multiplicity to make it more aggressive by default
-    // Also, the user can set 'simple' values like 1.5, 2.0, etc instead of 19_500.0, 26_000.0, etc
-    let mult = multiplier.unwrap_or(FloatOrd(1.0)).0 * MULTIPLICITY_BASE;
-    mult * distance(&a.position, &b.position, use_manhattan)
+// one search from each stop to the next, the legs being chained into a single route
+fn run(
+    algorithm: &dyn PathfindingAlgorithm,
+    run_args: &RunArgs,
+    stops: &[Node],
+    graph: &SearchGraph,
+    control: &SearchControl,
+) -> RunOutput {
+    let leg = |start, end, departure| {
+        let query = Query {
+            start,
+            end,
+            cost_model: &run_args.cost_model,
+            departure,
+            mark_passed_edges: run_args.mark_passed_edges,
+            use_turn_restrictions: run_args.use_turn_restrictions,
+            allow_u_turns: run_args.allow_u_turns,
+            control,
+        };
+        algorithm.run(&query, graph, &run_args.parameters)
+    };
+    let mut departure = run_args.departure.unwrap_or_default() as f32 * 60.;
+    if let [start, end] = stops {
+        return leg(start, end, departure);
+    }
+
+    let mut route = RunOutput::default();
+    // legs within their bound of the best leg keep the route within the largest one
+    let mut bound = Some(1.0f32);
+    for stops in stops.windows(2) {
+        let leg = leg(&stops[0], &stops[1], departure);
+        route.passed_edges.extend(leg.passed_edges);
+        // a leg without a route leaves none for the whole trip
+        if leg.path.is_empty() {
+            return RunOutput {
+                passed_edges: route.passed_edges,
+                negative_cycle: leg.negative_cycle,
+                ..Default::default()
+            };
+        }
+        // the leg starts where the previous one ended
+        let skip = usize::from(!route.path.is_empty());
+        route.path.extend(leg.path.into_iter().skip(skip));
+        route.selected_edges.extend(leg.selected_edges);
+        route.total_cost += leg.total_cost;
+        route.route_length += leg.route_length;
+        route.route_duration += leg.route_duration;
+        departure += leg.route_duration;
+        bound = bound
+            .zip(leg.solutions.last().and_then(|(bound, _)| *bound))
+            .map(|(a, b)| a.max(b));
+    }
+    route.solutions = vec![(bound, route.total_cost)];
+    route
 }
 
-fn distance(a: &Pos2, b: &Pos2, use_manhattan: bool) -> f32 {
-    if use_manhattan {
-        manhattan_distance(a, b)
-    } else {
-        euclidean_distance(a, b)
+fn store_run(run_output: &RunOutput) -> StoredRun {
+    let arc_ids = |edges: &mut dyn Iterator<Item = &Edge>| {
+        edges
+            .map(|edge| (edge.from.id.clone(), edge.to.id.clone()))
+            .collect::<Vec<ArcIds>>()
+    };
+    StoredRun {
+        path: run_output.path.iter().map(|node| node.id.clone()).collect(),
+        selected_edges: arc_ids(&mut run_output.selected_edges.iter()),
+        passed_edges: arc_ids(&mut run_output.passed_edges.iter()),
+        total_cost: run_output.total_cost,
+        route_length: run_output.route_length,
+        route_duration: run_output.route_duration,
+        negative_cycle: arc_ids(&mut run_output.negative_cycle.iter()),
+        solutions: run_output.solutions.clone(),
     }
 }
 
-#[derive(Debug, Clone)]
-struct NodeData {
-    node: Node,
-    cost: FloatOrd<f32>,
+// `None` when a node or an arc of the run is not in the graph
+fn resolve_run(stored: &StoredRun, neighbors: &HashMap<Node, Vec<Edge>>) -> Option<RunOutput> {
+    // nodes are equal when their ids are, the position does not matter to find one
+    let node = |id: &String| Node::new(id.clone(), egui::Pos2::ZERO, 0., 0.);
+    let arc = |from: &String, to: &String| {
+        neighbors
+            .get(&node(from))?
+            .iter()
+            .find(|edge| edge.to.id == *to)
+            .cloned()
+    };
+    let arcs = |ids: &[ArcIds]| {
+        ids.iter()
+            .map(|(from, to)| arc(from, to))
+            .collect::<Option<Vec<Edge>>>()
+    };
+
+    // the start, then the nodes the arcs between consecutive ids lead to
+    let mut path = Vec::new();
+    if let Some(start) = stored.path.first() {
+        path.push(neighbors.get_key_value(&node(start))?.0.clone());
+    }
+    for pair in stored.path.windows(2) {
+        path.push(arc(&pair[0], &pair[1])?.to);
+    }
+
+    Some(RunOutput {
+        path,
+        selected_edges: arcs(&stored.selected_edges)?.into_iter().collect(),
+        passed_edges: arcs(&stored.passed_edges)?.into_iter().collect(),
+        total_cost: stored.total_cost,
+        route_length: stored.route_length,
+        route_duration: stored.route_duration,
+        negative_cycle: arcs(&stored.negative_cycle)?,
+        solutions: stored.solutions.clone(),
+    })
 }
 
-impl NodeData {
-    fn new(node: Node, cost: FloatOrd<f32>) -> Self {
-        Self { node, cost }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parser::build_neighbors,
+        utils::{test_graph, test_node, wait_for_route},
+    };
+    use egui::Pos2;
+
+    #[test]
+    fn edge_based_search_respects_turns() {
+        // a square 0-1-2-3 with the diagonal 1 -> 3, every arc of the same length
+        let node = |id: &str, x: f32, y: f32| Node::new(id.to_string(), Pos2::new(x, y), 0., 0.);
+        let nodes = [
+            node("0", 0., 0.),
+            node("1", 1., 0.),
+            node("2", 1., 1.),
+            node("3", 0., 1.),
+        ];
+        let edges = [(0, 1), (1, 2), (2, 3), (1, 3), (0, 3)]
+            .iter()
+            .map(|&(from, to)| Edge::new(nodes[from].clone(), nodes[to].clone(), 100.))
+            .collect::<Vec<Edge>>();
+        let neighbors = Arc::new(build_neighbors(&edges));
+
+        let mut algorithm_ctx = AlgorithmContext::new();
+        algorithm_ctx.set_algorithm("Dijkstra");
+        algorithm_ctx.use_turn_restrictions = true;
+        algorithm_ctx.set_turn_restrictions(&[
+            TurnRestriction::new("0".into(), "1".into(), "3".into(), None),
+            TurnRestriction::new("1".into(), "2".into(), "3".into(), Some(30.)),
+        ]);
+
+        wait_for_route(&mut algorithm_ctx, &nodes[1], &nodes[3], &neighbors);
+        assert_eq!(algorithm_ctx.path().len(), 2);
+
+        // from 0 the diagonal is forbidden, the direct arc wins
+        wait_for_route(&mut algorithm_ctx, &nodes[0], &nodes[3], &neighbors);
+        assert_eq!(algorithm_ctx.path().len(), 2);
+
+        let edges = edges[..4].to_vec();
+        let neighbors = Arc::new(build_neighbors(&edges));
+        algorithm_ctx.set_graph(&neighbors);
+        wait_for_route(&mut algorithm_ctx, &nodes[0], &nodes[3], &neighbors);
+        let path = algorithm_ctx
+            .path()
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(path, ["0", "1", "2", "3"]);
+        // the penalty is paid on top of the three arcs
+        let arc_duration = edges[0].duration();
+        assert!((algorithm_ctx.route_duration - (3. * arc_duration + 30.)).abs() < 1e-3);
     }
 
-    fn from(node: Node) -> Self {
-        Self::new(node, FloatOrd(0.0))
+    #[test]
+    fn cached_runs_survive_sessions_of_the_same_map() {
+        let node = |id: &str, x: f32| Node::new(id.to_string(), Pos2::new(x, 0.), 0., 0.);
+        let nodes = [node("0", 0.), node("1", 1.), node("2", 2.)];
+        let mut edges = vec![
+            Edge::new(nodes[0].clone(), nodes[1].clone(), 100.),
+            Edge::new(nodes[1].clone(), nodes[2].clone(), 100.),
+        ];
+        let neighbors = Arc::new(build_neighbors(&edges));
+
+        let mut algorithm_ctx = AlgorithmContext::new();
+        algorithm_ctx.set_graph(&neighbors);
+        wait_for_route(&mut algorithm_ctx, &nodes[0], &nodes[2], &neighbors);
+        // the run marking the passed edges is only cached
+        algorithm_ctx.is_marking_passed_edges = true;
+        wait_for_route(&mut algorithm_ctx, &nodes[0], &nodes[2], &neighbors);
+        algorithm_ctx.is_marking_passed_edges = false;
+        assert_eq!(algorithm_ctx.cached_run_count(), 2);
+        let runs = serde_json::to_string(&algorithm_ctx.persisted_runs()).unwrap();
+
+        let mut restored = AlgorithmContext::new();
+        restored.restore_runs(serde_json::from_str(&runs).unwrap());
+        assert_eq!(restored.cached_run_count(), 1);
+        restored.set_graph(&neighbors);
+        let run_args = restored.run_args(&nodes[0], &nodes[2]);
+        let cached = restored.cached_run(&run_args, &neighbors).unwrap();
+        assert_eq!(cached.path, algorithm_ctx.path());
+        assert_eq!(cached.selected_edges.len(), 2);
+
+        // a longer arc makes it another map
+        edges[1].length = 150.;
+        restored.set_graph(&build_neighbors(&edges));
+        let run_args = restored.run_args(&nodes[0], &nodes[2]);
+        assert!(restored.cached_run(&run_args, &neighbors).is_none());
     }
-}
 
-impl Hash for NodeData {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.node.hash(state);
+    #[test]
+    fn routes_go_through_the_waypoints() {
+        let node = |id: &str, x: f32| Node::new(id.to_string(), Pos2::new(x, 0.), 0., 0.);
+        let nodes = [node("0", 0.), node("1", 1.), node("2", 2.), node("3", 3.)];
+        let edges = [(0, 1, 100.), (1, 2, 100.), (0, 2, 150.)]
+            .iter()
+            .map(|&(from, to, length)| Edge::new(nodes[from].clone(), nodes[to].clone(), length))
+            .collect::<Vec<Edge>>();
+        let neighbors = Arc::new(build_neighbors(&edges));
+
+        let mut algorithm_ctx = AlgorithmContext::new();
+        algorithm_ctx.set_graph(&neighbors);
+        wait_for_route(&mut algorithm_ctx, &nodes[0], &nodes[2], &neighbors);
+        assert_eq!(algorithm_ctx.path().len(), 2);
+
+        algorithm_ctx.waypoints = vec![nodes[1].clone()];
+        wait_for_route(&mut algorithm_ctx, &nodes[0], &nodes[2], &neighbors);
+        assert_eq!(algorithm_ctx.path(), &nodes[..3]);
+        assert_eq!(algorithm_ctx.total_cost, 200.);
+
+        // no route to the waypoint, none for the whole trip
+        algorithm_ctx.waypoints = vec![nodes[3].clone()];
+        wait_for_route(&mut algorithm_ctx, &nodes[0], &nodes[2], &neighbors);
+        assert!(algorithm_ctx.path().is_empty());
     }
-}
 
-impl PartialEq for NodeData {
-    fn eq(&self, other: &Self) -> bool {
-        self.node == other.node
+    #[test]
+    fn cancelled_searches_give_up() {
+        let (_, edges) = test_graph(2, &[(0, 1, 100.)]);
+        let neighbors = Arc::new(build_neighbors(&edges));
+        let (incidents, turns) = (HashMap::new(), HashMap::new());
+        let graph = SearchGraph::new(&neighbors, &incidents, None, &turns);
+
+        let mut algorithm_ctx = AlgorithmContext::new();
+        let control = SearchControl::default();
+        control.cancel();
+        let ids = algorithm_ctx
+            .registry()
+            .iter()
+            .map(|algorithm| algorithm.id())
+            .collect::<Vec<_>>();
+        for id in ids {
+            algorithm_ctx.set_algorithm(id);
+            let stops = [edges[0].from.clone(), edges[0].to.clone()];
+            let run_args = algorithm_ctx.run_args(&stops[0], &stops[1]);
+            let run_output = run(
+                algorithm_ctx.algorithm(),
+                &run_args,
+                &stops,
+                &graph,
+                &control,
+            );
+            assert!(run_output.path.is_empty(), "{}", id);
+
+            wait_for_route(&mut algorithm_ctx, &stops[0], &stops[1], &neighbors);
+            assert_eq!(algorithm_ctx.path().len(), 2, "{}", id);
+        }
     }
-}
 
-impl Eq for NodeData {}
+    #[test]
+    fn background_searches_keep_only_the_current_route() {
+        let (_, edges) = test_graph(4, &[(0, 1, 100.), (1, 2, 100.), (2, 3, 100.)]);
+        let neighbors = Arc::new(build_neighbors(&edges));
+        // a single background thread runs the searches one after the other
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .max_blocking_threads(1)
+            .build()
+            .unwrap();
+        let _guard = runtime.enter();
+        // waits for the searches queued so far
+        let settle = || {
+            runtime
+                .block_on(tokio::task::spawn_blocking(|| ()))
+                .unwrap()
+        };
+        let ctx = egui::Context::default();
+        let mut algorithm_ctx = AlgorithmContext::new();
+
+        algorithm_ctx.request_path(&test_node(0), &test_node(3), &neighbors, &ctx);
+        assert!(algorithm_ctx.is_searching());
+        settle();
+        assert!(algorithm_ctx.receive_path());
+        assert_eq!(algorithm_ctx.path().len(), 4);
+        assert!(!algorithm_ctx.is_searching());
 
-impl PartialOrd for NodeData {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+        // the route to 2 is only received once the one to 1 is requested, it is cached but
+        // not shown
+        algorithm_ctx.request_path(&test_node(0), &test_node(2), &neighbors, &ctx);
+        settle();
+        algorithm_ctx.request_path(&test_node(0), &test_node(1), &neighbors, &ctx);
+        settle();
+        assert!(algorithm_ctx.receive_path());
+        assert_eq!(algorithm_ctx.path().len(), 2);
+        assert_eq!(algorithm_ctx.cached_run_count(), 3);
+
+        // a search cancelled before it gets to run sends nothing
+        let (tx_start, rx_start) = std::sync::mpsc::channel::<()>();
+        tokio::task::spawn_blocking(move || rx_start.recv());
+        algorithm_ctx.request_path(&test_node(1), &test_node(3), &neighbors, &ctx);
+        algorithm_ctx.cancel_search();
+        assert!(!algorithm_ctx.is_searching());
+        tx_start.send(()).unwrap();
+        settle();
+        assert!(!algorithm_ctx.receive_path());
+        assert!(algorithm_ctx.path().is_empty());
+        assert_eq!(algorithm_ctx.cached_run_count(), 3);
+
+        // a route requested right after cancelling one is the only one received
+        let (tx_start, rx_start) = std::sync::mpsc::channel::<()>();
+        tokio::task::spawn_blocking(move || rx_start.recv());
+        algorithm_ctx.request_path(&test_node(1), &test_node(3), &neighbors, &ctx);
+        algorithm_ctx.cancel_search();
+        algorithm_ctx.request_path(&test_node(2), &test_node(3), &neighbors, &ctx);
+        assert!(algorithm_ctx.is_searching());
+        tx_start.send(()).unwrap();
+        settle();
+        settle();
+        assert!(algorithm_ctx.receive_path());
+        assert_eq!(algorithm_ctx.path(), &[test_node(2), test_node(3)]);
+        assert!(!algorithm_ctx.is_searching());
+        assert_eq!(algorithm_ctx.cached_run_count(), 4);
+
+        algorithm_ctx.request_day_profile(&test_node(0), &test_node(3), &neighbors, &ctx);
+        assert_eq!(algorithm_ctx.day_profile_progress().unwrap().1, 24);
+        settle();
+        let profile = algorithm_ctx.receive_day_profile().unwrap();
+        assert_eq!(profile.len(), 24);
+        assert!(profile.iter().all(|(_, _, route)| *route == 0));
+        assert_eq!(algorithm_ctx.day_profile_progress(), None);
     }
-}
 
-impl Ord for NodeData {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.cost.cmp(&other.cost)
+    #[test]
+    fn negative_costs_and_cycles() {
+        let node = |id: &str| Node::new(id.to_string(), Pos2::ZERO, 0., 0.);
+        let nodes = [node("0"), node("1"), node("2")];
+        // with a cost of length - time, 2 -> 1 costs -50
+        let arc = |from: usize, to: usize, length: f32, time: f32| {
+            Edge::new(nodes[from].clone(), nodes[to].clone(), length).with_attributes(
+                None,
+                None,
+                Some(time),
+            )
+        };
+        let mut edges = vec![
+            arc(0, 1, 100., 10.),
+            arc(0, 2, 100., 50.),
+            arc(2, 1, 10., 60.),
+        ];
+
+        let mut algorithm_ctx = AlgorithmContext::new();
+        algorithm_ctx.cost_model = CostModel {
+            metric: CostMetric::Custom,
+            distance_weight: FloatOrd(1.0),
+            time_weight: FloatOrd(-1.0),
+        };
+        assert_eq!(algorithm_ctx.negative_cost_edge(&edges), Some(&edges[2]));
+
+        // whatever the metric, an incident may make the cost negative
+        let mut distance_ctx = AlgorithmContext::new();
+        assert_eq!(distance_ctx.negative_cost_edge(&edges), None);
+        distance_ctx.set_incident(edges[1].clone(), Incident::Slowed { factor: -1.0 });
+        assert_eq!(distance_ctx.negative_cost_edge(&edges), Some(&edges[1]));
+
+        for id in ["BellmanFord", "Johnson"] {
+            algorithm_ctx.set_algorithm(id);
+            wait_for_route(
+                &mut algorithm_ctx,
+                &nodes[0],
+                &nodes[1],
+                &Arc::new(build_neighbors(&edges)),
+            );
+            let path = algorithm_ctx
+                .path()
+                .iter()
+                .map(|n| n.id.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(path, ["0", "2", "1"]);
+            assert!(algorithm_ctx.total_cost.abs() < 1e-3);
+            assert_eq!(algorithm_ctx.optimality_bound(), Some(1.0));
+        }
+        // costed at the departure and without the turns, they are no longer exact
+        algorithm_ctx.use_turn_restrictions = true;
+        wait_for_route(
+            &mut algorithm_ctx,
+            &nodes[0],
+            &nodes[1],
+            &Arc::new(build_neighbors(&edges)),
+        );
+        assert_eq!(algorithm_ctx.optimality_bound(), None);
+        algorithm_ctx.use_turn_restrictions = false;
+
+        // Dijkstra counts the negative cost as nothing, which makes the route cost 50
+        algorithm_ctx.set_algorithm("Dijkstra");
+        wait_for_route(
+            &mut algorithm_ctx,
+            &nodes[0],
+            &nodes[1],
+            &Arc::new(build_neighbors(&edges)),
+        );
+        assert!((algorithm_ctx.total_cost - 50.).abs() < 1e-3);
+        assert_eq!(algorithm_ctx.optimality_bound(), None);
+
+        // 2 -> 1 -> 2 costs -40
+        edges.push(arc(1, 2, 10., 0.));
+        let neighbors = Arc::new(build_neighbors(&edges));
+        for id in ["BellmanFord", "Johnson"] {
+            algorithm_ctx.set_algorithm(id);
+            algorithm_ctx.set_graph(&neighbors);
+            wait_for_route(&mut algorithm_ctx, &nodes[0], &nodes[1], &neighbors);
+            assert!(algorithm_ctx.path().is_empty());
+            assert_eq!(algorithm_ctx.negative_cycle().len(), 2);
+        }
+
+        // a cancelled run finds nothing, not even the empty route from a node of the cycle
+        // to itself
+        let (incidents, turns) = (HashMap::new(), HashMap::new());
+        let graph = SearchGraph::new(&neighbors, &incidents, None, &turns);
+        let control = SearchControl::default();
+        control.cancel();
+        for id in ["BellmanFord", "Johnson"] {
+            algorithm_ctx.set_algorithm(id);
+            let stops = [nodes[1].clone(), nodes[1].clone()];
+            let run_args = algorithm_ctx.run_args(&stops[0], &stops[1]);
+            let run_output = run(
+                algorithm_ctx.algorithm(),
+                &run_args,
+                &stops,
+                &graph,
+                &control,
+            );
+            assert!(run_output.path.is_empty(), "{}", id);
+            assert!(run_output.negative_cycle.is_empty(), "{}", id);
+        }
     }
 }
@@ -0,0 +1,299 @@
+use enum_iterator::Sequence;
+use std::{
+    collections::HashMap,
+    fmt,
+    path::PathBuf,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc,
+    },
+};
+
+use log::debug;
+
+use crate::{
+    algorithms::ParameterValues,
+    analysis::{Centrality, DistanceMatrix, HeuristicReport, SpanningTree, TreeKind},
+    contexts::CostModel,
+    export::{write_distance_matrix, MatrixFormat},
+    models::{Edge, Node},
+};
+
+#[derive(PartialEq, Eq, Hash, Sequence, Copy, Clone, Debug)]
+pub enum CentralityOverlay {
+    Off,
+    Betweenness,
+    Closeness,
+}
+
+impl fmt::Display for CentralityOverlay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CentralityOverlay::Off => write!(f, "Off"),
+            CentralityOverlay::Betweenness => write!(f, "Edge betweenness"),
+            CentralityOverlay::Closeness => write!(f, "Node closeness"),
+        }
+    }
+}
+
+/// Network-wide computations too slow to run on the UI thread.
+pub struct AnalysisContext {
+    pub overlay: CentralityOverlay,
+    // approximates the betweenness from a random subset of the nodes
+    pub use_sampling: bool,
+    pub samples: usize,
+    // length of the list of critical edges
+    pub top_edges: usize,
+    pub tree_kind: TreeKind,
+    // ids of the nodes the Steiner tree connects
+    pub terminals: Vec<String>,
+    pub is_picking_terminals: bool,
+    // random queries the heuristic is checked on, besides the current one
+    pub heuristic_samples: usize,
+    pub show_heuristic_violations: bool,
+    centrality: Option<Centrality>,
+    is_computing: bool,
+    tx_centrality: Sender<Centrality>,
+    rx_centrality: Receiver<Centrality>,
+    tree: Option<SpanningTree>,
+    is_computing_tree: bool,
+    tx_tree: Sender<Result<SpanningTree, String>>,
+    rx_tree: Receiver<Result<SpanningTree, String>>,
+    heuristic_report: Option<HeuristicReport>,
+    is_checking_heuristic: bool,
+    tx_heuristic: Sender<HeuristicReport>,
+    rx_heuristic: Receiver<HeuristicReport>,
+    is_exporting_matrix: bool,
+    // what to tell the user once the matrix has been written, or why it could not be
+    tx_matrix: Sender<Result<String, String>>,
+    rx_matrix: Receiver<Result<String, String>>,
+}
+
+impl AnalysisContext {
+    pub fn new() -> Self {
+        let (tx_centrality, rx_centrality) = std::sync::mpsc::channel();
+        let (tx_tree, rx_tree) = std::sync::mpsc::channel();
+        let (tx_heuristic, rx_heuristic) = std::sync::mpsc::channel();
+        let (tx_matrix, rx_matrix) = std::sync::mpsc::channel();
+
+        Self {
+            overlay: CentralityOverlay::Off,
+            use_sampling: true,
+            samples: 200,
+            top_edges: 10,
+            tree_kind: TreeKind::MinimumSpanning,
+            terminals: Vec::new(),
+            is_picking_terminals: false,
+            heuristic_samples: 20,
+            show_heuristic_violations: true,
+            centrality: None,
+            is_computing: false,
+            tx_centrality,
+            rx_centrality,
+            tree: None,
+            is_computing_tree: false,
+            tx_tree,
+            rx_tree,
+            heuristic_report: None,
+            is_checking_heuristic: false,
+            tx_heuristic,
+            rx_heuristic,
+            is_exporting_matrix: false,
+            tx_matrix,
+            rx_matrix,
+        }
+    }
+
+    pub fn centrality(&self) -> Option<&Centrality> {
+        self.centrality.as_ref()
+    }
+
+    pub fn is_computing(&self) -> bool {
+        self.is_computing
+    }
+
+    /// Starts computing the centrality of the graph on a background thread.
+    pub fn request_centrality(&mut self, nodes: &[Node], edges: &[Edge], ctx: &egui::Context) {
+        let tx_centrality = self.tx_centrality.clone();
+        let (nodes, edges) = (nodes.to_vec(), edges.to_vec());
+        let samples = self.use_sampling.then_some(self.samples);
+        let ctx = ctx.clone();
+        self.is_computing = true;
+
+        tokio::task::spawn_blocking(move || {
+            debug!("Computing centrality...");
+            // the receiver is gone when the results have been discarded in the meantime
+            let _ = tx_centrality.send(Centrality::new(&nodes, &edges, samples));
+            debug!("Centrality computed");
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn receive_centrality(&mut self) {
+        if let Ok(centrality) = self.rx_centrality.try_recv() {
+            self.centrality = Some(centrality);
+            self.is_computing = false;
+        }
+    }
+
+    pub fn tree(&self) -> Option<&SpanningTree> {
+        self.tree.as_ref()
+    }
+
+    pub fn clear_tree(&mut self) {
+        self.tree = None;
+    }
+
+    pub fn is_computing_tree(&self) -> bool {
+        self.is_computing_tree
+    }
+
+    pub fn toggle_terminal(&mut self, id: &str) {
+        match self.terminals.iter().position(|terminal| terminal == id) {
+            Some(index) => {
+                self.terminals.remove(index);
+            }
+            None => self.terminals.push(id.to_string()),
+        }
+    }
+
+    /// Starts computing a tree of the selected kind on a background thread.
+    pub fn request_tree(&mut self, nodes: &[Node], edges: &[Edge], ctx: &egui::Context) {
+        let tx_tree = self.tx_tree.clone();
+        let (nodes, edges) = (nodes.to_vec(), edges.to_vec());
+        let (kind, terminals) = (self.tree_kind, self.terminals.clone());
+        let ctx = ctx.clone();
+        self.is_computing_tree = true;
+
+        tokio::task::spawn_blocking(move || {
+            debug!("Computing {}...", kind);
+            let tree = match kind {
+                TreeKind::MinimumSpanning => Ok(SpanningTree::minimum(&nodes, &edges)),
+                TreeKind::Steiner => SpanningTree::steiner(&nodes, &edges, &terminals),
+            };
+            debug!("{} computed", kind);
+            // the receiver is gone when the results have been discarded in the meantime
+            let _ = tx_tree.send(tree);
+            ctx.request_repaint();
+        });
+    }
+
+    /// Keeps the tree that has been computed, returns why it could not be otherwise.
+    pub fn receive_tree(&mut self) -> Option<String> {
+        let result = self.rx_tree.try_recv().ok()?;
+        self.is_computing_tree = false;
+        match result {
+            Ok(tree) => {
+                self.tree = Some(tree);
+                None
+            }
+            Err(message) => Some(message),
+        }
+    }
+
+    pub fn heuristic_report(&self) -> Option<&HeuristicReport> {
+        self.heuristic_report.as_ref()
+    }
+
+    pub fn is_checking_heuristic(&self) -> bool {
+        self.is_checking_heuristic
+    }
+
+    /// Checks the A* heuristic on the current query, if any, and random ones on a background thread.
+    pub fn request_heuristic_check(
+        &mut self,
+        neighbors: &Arc<HashMap<Node, Vec<Edge>>>,
+        cost_model: &CostModel,
+        parameters: &ParameterValues,
+        current: Option<(Node, Node)>,
+        ctx: &egui::Context,
+    ) {
+        let tx_heuristic = self.tx_heuristic.clone();
+        let (neighbors, cost_model, parameters) =
+            (neighbors.clone(), *cost_model, parameters.clone());
+        let samples = self.heuristic_samples;
+        let ctx = ctx.clone();
+        self.is_checking_heuristic = true;
+
+        tokio::task::spawn_blocking(move || {
+            debug!("Checking heuristic...");
+            let queries = current.into_iter().collect();
+            let report =
+                HeuristicReport::new(&neighbors, &cost_model, &parameters, queries, samples);
+            debug!("Heuristic checked");
+            // the receiver is gone when the results have been discarded in the meantime
+            let _ = tx_heuristic.send(report);
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn receive_heuristic_report(&mut self) {
+        if let Ok(report) = self.rx_heuristic.try_recv() {
+            self.heuristic_report = Some(report);
+            self.is_checking_heuristic = false;
+        }
+    }
+
+    pub fn is_exporting_matrix(&self) -> bool {
+        self.is_exporting_matrix
+    }
+
+    /// Computes the distance matrix between the nodes on a background thread and writes it.
+    pub fn request_distance_matrix(
+        &mut self,
+        nodes: &[Node],
+        neighbors: &Arc<HashMap<Node, Vec<Edge>>>,
+        ids: Vec<String>,
+        format: MatrixFormat,
+        path: PathBuf,
+        ctx: &egui::Context,
+    ) {
+        let tx_matrix = self.tx_matrix.clone();
+        let (nodes, neighbors) = (nodes.to_vec(), neighbors.clone());
+        let ctx = ctx.clone();
+        self.is_exporting_matrix = true;
+
+        tokio::task::spawn_blocking(move || {
+            debug!("Computing distance matrix...");
+            let result = DistanceMatrix::compute(&nodes, &neighbors, &ids).and_then(|matrix| {
+                write_distance_matrix(&matrix, format, &path)
+                    .map_err(|e| format!("Unable to write {}: {}", path.display(), e))?;
+                Ok(format!(
+                    "{} x {} distance matrix written to {}",
+                    ids.len(),
+                    ids.len(),
+                    path.display()
+                ))
+            });
+            debug!("Distance matrix computed");
+            tx_matrix.send(result).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn receive_distance_matrix(&mut self) -> Option<Result<String, String>> {
+        let result = self.rx_matrix.try_recv().ok()?;
+        self.is_exporting_matrix = false;
+        Some(result)
+    }
+
+    /// Forgets the results, for when the graph has changed.
+    pub fn discard(&mut self) {
+        // a fresh channel drops whatever is still being computed for the previous graph
+        (self.tx_centrality, self.rx_centrality) = std::sync::mpsc::channel();
+        (self.tx_tree, self.rx_tree) = std::sync::mpsc::channel();
+        (self.tx_heuristic, self.rx_heuristic) = std::sync::mpsc::channel();
+        self.centrality = None;
+        self.is_computing = false;
+        self.tree = None;
+        self.is_computing_tree = false;
+        self.heuristic_report = None;
+        self.is_checking_heuristic = false;
+    }
+}
+
+impl Default for AnalysisContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
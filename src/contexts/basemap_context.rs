@@ -0,0 +1,161 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::mpsc::{Receiver, Sender},
+};
+
+use egui::{ColorImage, TextureHandle, TextureOptions};
+
+use crate::{
+    contexts::DrawingContext,
+    tiles::{TileId, TileSource, MAX_TILE_LATITUDE},
+    translator::Translator,
+    utils::constants::{CANVAS_SIZE, MAX_CACHED_TILES, MAX_VISIBLE_TILES, TILE_SIZE},
+};
+
+type TileMessage = (TileId, Option<ColorImage>);
+
+pub struct BasemapContext {
+    pub is_visible: bool,
+    pub opacity: f32,
+    source: Option<TileSource>,
+    // `None` marks tiles the source does not have, so they are not requested again
+    textures: HashMap<TileId, Option<TextureHandle>>,
+    requested: HashSet<TileId>,
+    tx_tiles: Sender<TileMessage>,
+    rx_tiles: Receiver<TileMessage>,
+}
+
+impl BasemapContext {
+    pub fn new() -> Self {
+        let (tx_tiles, rx_tiles) = std::sync::mpsc::channel();
+
+        Self {
+            is_visible: true,
+            opacity: 0.6,
+            source: None,
+            textures: HashMap::new(),
+            requested: HashSet::new(),
+            tx_tiles,
+            rx_tiles,
+        }
+    }
+
+    pub fn source(&self) -> Option<&TileSource> {
+        self.source.as_ref()
+    }
+
+    pub fn set_source(&mut self, source: TileSource) {
+        // a fresh channel drops whatever the previous source is still loading
+        (self.tx_tiles, self.rx_tiles) = std::sync::mpsc::channel();
+        self.textures.clear();
+        self.requested.clear();
+        self.source = Some(source);
+    }
+
+    pub fn texture(&self, tile: &TileId) -> Option<&TextureHandle> {
+        self.textures.get(tile)?.as_ref()
+    }
+
+    /// Uploads the tiles decoded since the last frame.
+    pub fn receive_tiles(&mut self, ctx: &egui::Context) {
+        while let Ok((tile, image)) = self.rx_tiles.try_recv() {
+            self.requested.remove(&tile);
+            let texture = image.map(|image| {
+                ctx.load_texture(
+                    format!("tile-{}-{}-{}", tile.z, tile.x, tile.y),
+                    image,
+                    TextureOptions::LINEAR,
+                )
+            });
+            self.textures.insert(tile, texture);
+        }
+    }
+
+    /// Tiles covering the viewport, at the zoom level closest to the current scale.
+    pub fn visible_tiles(&self, draw_ctx: &DrawingContext, translator: &Translator) -> Vec<TileId> {
+        let Some(source) = &self.source else {
+            return vec![];
+        };
+        let viewport = draw_ctx.viewport();
+        if !viewport.is_positive() {
+            return vec![];
+        }
+
+        let corners = [
+            viewport.left_top(),
+            viewport.right_top(),
+            viewport.right_bottom(),
+            viewport.left_bottom(),
+        ]
+        .map(|corner| translator.unproject(draw_ctx.screen_to_canvas(corner)));
+        let min_lon = corners.iter().map(|c| c.0).fold(f64::MAX, f64::min);
+        let max_lon = corners.iter().map(|c| c.0).fold(f64::MIN, f64::max);
+        let min_lat = corners.iter().map(|c| c.1).fold(f64::MAX, f64::min);
+        let max_lat = corners.iter().map(|c| c.1).fold(f64::MIN, f64::max);
+        if min_lat > MAX_TILE_LATITUDE || max_lat < -MAX_TILE_LATITUDE {
+            return vec![];
+        }
+
+        // the whole world is CANVAS_SIZE.x wide before zooming
+        let world_size = (CANVAS_SIZE.x * draw_ctx.zoom) as f64;
+        let (min_z, max_z) = source.zoom_range();
+        let mut z =
+            ((world_size / TILE_SIZE as f64).log2().round().max(0.0) as u8).clamp(min_z, max_z);
+
+        loop {
+            let top_left = TileId::at(min_lon, max_lat, z);
+            let bottom_right = TileId::at(max_lon, min_lat, z);
+            let count = (bottom_right.x - top_left.x + 1) as usize
+                * (bottom_right.y - top_left.y + 1) as usize;
+
+            if count <= MAX_VISIBLE_TILES || z <= min_z {
+                return (top_left.x..=bottom_right.x)
+                    .flat_map(|x| (top_left.y..=bottom_right.y).map(move |y| TileId::new(z, x, y)))
+                    .take(MAX_VISIBLE_TILES)
+                    .collect();
+            }
+            z -= 1;
+        }
+    }
+
+    /// Starts loading the tiles that are neither cached nor already requested.
+    pub fn request_tiles(&mut self, tiles: &[TileId], ctx: &egui::Context) {
+        let Some(source) = &self.source else {
+            return;
+        };
+
+        if self.textures.len() > MAX_CACHED_TILES {
+            self.textures.retain(|tile, _| tiles.contains(tile));
+        }
+
+        let missing = tiles
+            .iter()
+            .filter(|tile| !self.textures.contains_key(tile) && !self.requested.contains(tile))
+            .copied()
+            .collect::<Vec<TileId>>();
+        if missing.is_empty() {
+            return;
+        }
+        self.requested.extend(missing.iter().copied());
+
+        let source = source.clone();
+        let tx_tiles = self.tx_tiles.clone();
+        let ctx = ctx.clone();
+        tokio::task::spawn_blocking(move || {
+            for tile in missing {
+                let image = source.load(tile);
+                // the receiver is gone when the source changed in the meantime
+                if tx_tiles.send((tile, image)).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+    }
+}
+
+impl Default for BasemapContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,13 +1,34 @@
 use std::{
     collections::HashMap,
-    sync::mpsc::{Receiver, Sender},
+    path::PathBuf,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc,
+    },
 };
 
 use crate::{
-    models::{Edge, Node},
-    utils::constants::xml_data::{MAP2_XML, TEST_XML},
+    analysis::{GraphStatistics, ValidationReport},
+    models::{Edge, Node, TurnRestriction},
+    parser::{build_neighbors, ParseProgress},
+    projections::ProjectionKind,
+    translator::Translator,
+    utils::{
+        constants::{
+            xml_data::{MAP2_XML, TEST_XML},
+            CANVAS_SIZE,
+        },
+        WindowSize,
+    },
 };
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataSource {
+    Embedded(&'static [u8]),
+    // read from disk with the streaming parser, meant for maps too large to embed
+    File(PathBuf),
+}
+
 pub struct DataContext {
     pub rx_nodes: Receiver<Vec<Node>>,
     pub rx_edges: Receiver<Vec<Edge>>,
@@ -15,17 +36,48 @@ pub struct DataContext {
     pub tx_nodes: Sender<Vec<Node>>,
     pub tx_edges: Sender<Vec<Edge>>,
 
+    pub tx_restrictions: Sender<Vec<TurnRestriction>>,
+    pub rx_restrictions: Receiver<Vec<TurnRestriction>>,
+
+    pub tx_validation: Sender<ValidationReport>,
+    pub rx_validation: Receiver<ValidationReport>,
+
+    // (generation, statistics)
+    pub tx_statistics: Sender<(u64, GraphStatistics)>,
+    pub rx_statistics: Receiver<(u64, GraphStatistics)>,
+
     pub tx_neighbors: Sender<HashMap<Node, Vec<Edge>>>,
     pub rx_neighbors: Receiver<HashMap<Node, Vec<Edge>>>,
 
+    pub tx_node_batches: Sender<Vec<Node>>,
+    pub rx_node_batches: Receiver<Vec<Node>>,
+
+    pub tx_progress: Sender<ParseProgress>,
+    pub rx_progress: Receiver<ParseProgress>,
+
+    // why the map could not be loaded, nothing else is sent then
+    pub tx_load_error: Sender<String>,
+    pub rx_load_error: Receiver<String>,
+
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
-    pub neighbors: HashMap<Node, Vec<Edge>>,
+    // shared with the searches running in the background
+    pub neighbors: Arc<HashMap<Node, Vec<Edge>>>,
+    pub restrictions: Vec<TurnRestriction>,
+    pub validation: Option<ValidationReport>,
+    // computed in the background when the analytics panel is open
+    pub statistics: Option<GraphStatistics>,
+    pub statistics_loading: bool,
+    // bumped whenever the graph changes, older statistics are ignored when they arrive
+    statistics_generation: u64,
 
     pub nodes_loading: bool,
     pub edges_loading: bool,
+    pub load_progress: Option<ParseProgress>,
 
-    data_buf: &'static [u8],
+    data_source: DataSource,
+    // the translator used for the loaded map, nodes have to be re-parsed when it changes
+    translator: Translator,
 
     first_load: std::cell::Cell<bool>,
 }
@@ -38,45 +90,208 @@ impl DataContext {
     fn new(nodes: Vec<Node>, edges: Vec<Edge>, neighboors: HashMap<Node, Vec<Edge>>) -> Self {
         let (tx_nodes, rx_nodes) = std::sync::mpsc::channel();
         let (tx_edges, rx_edges) = std::sync::mpsc::channel();
+        let (tx_restrictions, rx_restrictions) = std::sync::mpsc::channel();
+        let (tx_validation, rx_validation) = std::sync::mpsc::channel();
+        let (tx_statistics, rx_statistics) = std::sync::mpsc::channel();
         let (tx_neighboors, rx_neighboors) = std::sync::mpsc::channel();
+        let (tx_node_batches, rx_node_batches) = std::sync::mpsc::channel();
+        let (tx_progress, rx_progress) = std::sync::mpsc::channel();
+        let (tx_load_error, rx_load_error) = std::sync::mpsc::channel();
 
         Self {
             rx_nodes,
             rx_edges,
             tx_nodes,
             tx_edges,
+            tx_restrictions,
+            rx_restrictions,
+            tx_validation,
+            rx_validation,
+            tx_statistics,
+            rx_statistics,
             tx_neighbors: tx_neighboors,
             rx_neighbors: rx_neighboors,
+            tx_node_batches,
+            rx_node_batches,
+            tx_progress,
+            rx_progress,
+            tx_load_error,
+            rx_load_error,
             nodes,
             edges,
-            neighbors: neighboors,
+            neighbors: Arc::new(neighboors),
+            restrictions: Vec::new(),
+            validation: None,
+            statistics: None,
+            statistics_loading: false,
+            statistics_generation: 0,
             nodes_loading: false,
             edges_loading: false,
-            data_buf: MAP2_XML,
+            load_progress: None,
+            data_source: DataSource::Embedded(MAP2_XML),
+            translator: Translator::default(),
             first_load: std::cell::Cell::new(true),
         }
     }
 
-    pub fn data_buffer(&self) -> &'static [u8] {
-        &self.data_buf
+    pub fn data_source(&self) -> &DataSource {
+        &self.data_source
     }
 
-    pub fn data_name(&self) -> &str {
-        if self.data_buf == MAP2_XML {
-            "map2.xml"
-        } else {
-            "test.xml"
+    pub fn data_name(&self) -> String {
+        match &self.data_source {
+            DataSource::Embedded(buf) if *buf == MAP2_XML => "map2.xml".to_string(),
+            DataSource::Embedded(_) => "test.xml".to_string(),
+            DataSource::File(path) => path.display().to_string(),
         }
     }
 
     pub fn switch_data_file(&mut self) {
-        self.data_buf = if self.data_buf == MAP2_XML {
-            TEST_XML
+        self.data_source = if self.data_source == DataSource::Embedded(MAP2_XML) {
+            DataSource::Embedded(TEST_XML)
         } else {
-            MAP2_XML
+            DataSource::Embedded(MAP2_XML)
         };
     }
 
+    pub fn open_data_file(&mut self, path: PathBuf) {
+        self.data_source = DataSource::File(path);
+    }
+
+    pub fn set_data_source(&mut self, data_source: DataSource) {
+        self.data_source = data_source;
+    }
+
+    pub fn translator(&self) -> &Translator {
+        &self.translator
+    }
+
+    pub fn projection(&self) -> ProjectionKind {
+        self.translator.projection()
+    }
+
+    pub fn set_projection(&mut self, projection: ProjectionKind) {
+        self.translator = Translator::new(WindowSize::from(CANVAS_SIZE), projection);
+    }
+
+    pub fn find_node(&self, id: &str) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn find_edge(&self, from: &str, to: &str) -> Option<&Edge> {
+        self.edges
+            .iter()
+            .find(|edge| edge.from.id == from && edge.to.id == to)
+    }
+
+    /// Adds a node along with arcs to or from it.
+    pub fn insert_node(&mut self, node: Node, edges: Vec<Edge>) {
+        self.nodes.push(node);
+        self.edges.extend(edges);
+        self.rebuild_neighbors();
+    }
+
+    /// Removes a node and every arc touching it, which are returned.
+    pub fn remove_node(&mut self, id: &str) -> Option<(Node, Vec<Edge>)> {
+        let index = self.nodes.iter().position(|node| node.id == id)?;
+        let node = self.nodes.remove(index);
+        let (removed, kept) = std::mem::take(&mut self.edges)
+            .into_iter()
+            .partition(|edge| edge.from.id == id || edge.to.id == id);
+        self.edges = kept;
+        self.rebuild_neighbors();
+
+        Some((node, removed))
+    }
+
+    /// Moves a node, the arcs keep their length.
+    pub fn move_node(&mut self, id: &str, longitude: f64, latitude: f64, rebuild: bool) {
+        let position = self.translator.translate_coordinates(longitude, latitude);
+        let label = self
+            .nodes
+            .iter()
+            .find(|node| node.id == id)
+            .and_then(|node| node.label.clone());
+        let moved = Node::new(id.to_string(), position, longitude, latitude).with_label(label);
+
+        self.nodes
+            .iter_mut()
+            .filter(|node| node.id == id)
+            .for_each(|node| *node = moved.clone());
+        self.edges.iter_mut().for_each(|edge| {
+            if edge.from.id == id {
+                edge.from = moved.clone();
+            }
+            if edge.to.id == id {
+                edge.to = moved.clone();
+            }
+        });
+        // skipped while dragging, the neighbors are only needed once the node is dropped
+        if rebuild {
+            self.rebuild_neighbors();
+        }
+    }
+
+    pub fn insert_edge(&mut self, edge: Edge) {
+        self.edges.push(edge);
+        self.rebuild_neighbors();
+    }
+
+    pub fn remove_edge(&mut self, from: &str, to: &str) -> Option<Edge> {
+        let index = self
+            .edges
+            .iter()
+            .position(|edge| edge.from.id == from && edge.to.id == to)?;
+        let edge = self.edges.remove(index);
+        self.rebuild_neighbors();
+
+        Some(edge)
+    }
+
+    /// Gives the loads fresh channels, whatever a load still running sends is then dropped
+    /// instead of being mixed with the next map.
+    pub fn discard_loads(&mut self) {
+        (self.tx_nodes, self.rx_nodes) = std::sync::mpsc::channel();
+        (self.tx_edges, self.rx_edges) = std::sync::mpsc::channel();
+        (self.tx_restrictions, self.rx_restrictions) = std::sync::mpsc::channel();
+        (self.tx_validation, self.rx_validation) = std::sync::mpsc::channel();
+        (self.tx_neighbors, self.rx_neighbors) = std::sync::mpsc::channel();
+        (self.tx_node_batches, self.rx_node_batches) = std::sync::mpsc::channel();
+        (self.tx_progress, self.rx_progress) = std::sync::mpsc::channel();
+        (self.tx_load_error, self.rx_load_error) = std::sync::mpsc::channel();
+    }
+
+    pub fn statistics_generation(&self) -> u64 {
+        self.statistics_generation
+    }
+
+    /// Drops the statistics, those still being computed are ignored once they arrive.
+    pub fn discard_statistics(&mut self) {
+        self.statistics = None;
+        self.statistics_loading = false;
+        self.statistics_generation += 1;
+    }
+
+    /// Validates the graph again after it has been edited, the dangling arcs and the invalid
+    /// attributes only come from the file.
+    pub fn revalidate(&mut self) {
+        let (dangling_arcs, invalid_attributes) = self
+            .validation
+            .take()
+            .map(|report| (report.dangling_arcs, report.invalid_attributes))
+            .unwrap_or_default();
+        self.validation = Some(ValidationReport::new(
+            &self.nodes,
+            &self.edges,
+            dangling_arcs,
+            invalid_attributes,
+        ));
+    }
+
+    fn rebuild_neighbors(&mut self) {
+        self.neighbors = Arc::new(build_neighbors(&self.edges));
+    }
+
     pub fn has_data(&self) -> bool {
         !self.nodes.is_empty() && !self.edges.is_empty()
     }
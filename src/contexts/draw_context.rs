@@ -1,21 +1,38 @@
-use egui::{Pos2, Vec2};
+use egui::{Pos2, Rect, Vec2};
 
 use crate::{
     models::{Edge, Node},
-    utils::constants::{DEFAULT_PAN, DEFAULT_ZOOM},
+    utils::constants::{
+        FIT_MARGIN, MAX_ZOOM_FACTOR, MIN_FLING_SPEED, MIN_ZOOM_FACTOR, PAN_FRICTION,
+        VIEW_ANIMATION_SPEED,
+    },
 };
 
+// (zoom, pan) the view is being animated towards
+type ViewTarget = (f32, Vec2);
+
 #[derive(Debug, Clone)]
 pub struct DrawingContext {
     pub zoom: f32,
     pub pan: Vec2,
+    // bounding box of the loaded nodes, in canvas coordinates
+    bounds: Option<Rect>,
+    // the screen area the map is drawn into
+    viewport: Rect,
+    target: Option<ViewTarget>,
+    // screen points per second, left over after a drag is released
+    pan_velocity: Vec2,
 }
 
 impl DrawingContext {
     pub fn new() -> Self {
         Self {
-            zoom: DEFAULT_ZOOM,
-            pan: DEFAULT_PAN,
+            zoom: 1.0,
+            pan: Vec2::ZERO,
+            bounds: None,
+            viewport: Rect::NOTHING,
+            target: None,
+            pan_velocity: Vec2::ZERO,
         }
     }
 
@@ -30,10 +47,249 @@ impl DrawingContext {
         (from_position, to_position)
     }
 
+    pub fn canvas_to_screen(&self, position: Pos2) -> Pos2 {
+        self.adjust_for_pan_and_zoom(&position)
+    }
+
+    /// Inverse of the pan and zoom adjustment, from screen back to canvas coordinates.
+    pub fn screen_to_canvas(&self, position: Pos2) -> Pos2 {
+        position / self.zoom - self.pan
+    }
+
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+    }
+
+    pub fn set_bounds(&mut self, nodes: &[Node]) {
+        let mut bounds = Rect::NOTHING;
+        nodes
+            .iter()
+            .for_each(|node| bounds.extend_with(node.position));
+
+        self.bounds = if nodes.is_empty() { None } else { Some(bounds) };
+    }
+
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+
+    /// Zooms and pans so that the whole map fits in the viewport.
+    pub fn fit_to_bounds(&mut self) {
+        self.stop_motion();
+        if let Some((zoom, pan)) = self.fit_view() {
+            self.zoom = zoom;
+            self.pan = pan;
+        }
+    }
+
+    pub fn animate_fit_to_bounds(&mut self) {
+        if let Some(target) = self.fit_view() {
+            self.animate_to(target);
+        }
+    }
+
+    /// Multiplies the zoom by `factor` while keeping the canvas point under `anchor` in place.
+    pub fn zoom_at(&mut self, anchor: Pos2, factor: f32) {
+        self.stop_motion();
+        (self.zoom, self.pan) = self.zoomed_view(anchor, factor);
+        self.clamp_pan();
+    }
+
+    pub fn animate_zoom_at(&mut self, anchor: Pos2, factor: f32) {
+        // chain onto a running animation so repeated key presses accumulate
+        let (zoom, pan) = self.target.unwrap_or((self.zoom, self.pan));
+        let world = anchor / zoom - pan;
+        let zoom = (zoom * factor).clamp(self.min_zoom(), self.max_zoom());
+
+        self.animate_to((zoom, anchor.to_vec2() / zoom - world.to_vec2()));
+    }
+
+    /// Moves the view by `delta` screen points, animated.
+    pub fn animate_pan_by(&mut self, delta: Vec2) {
+        let (zoom, pan) = self.target.unwrap_or((self.zoom, self.pan));
+
+        self.animate_to((zoom, pan + delta / zoom));
+    }
+
+    /// Moves the canvas `position` to the centre of the viewport, animated.
+    pub fn animate_center_on(&mut self, position: Pos2) {
+        let (zoom, _) = self.target.unwrap_or((self.zoom, self.pan));
+
+        self.animate_to((
+            zoom,
+            self.viewport.center().to_vec2() / zoom - position.to_vec2(),
+        ));
+    }
+
+    /// Keeps panning with `velocity` (screen points per second) after a drag, slowing down by friction.
+    pub fn fling(&mut self, velocity: Vec2) {
+        self.target = None;
+        self.pan_velocity = if velocity.length() > MIN_FLING_SPEED {
+            velocity
+        } else {
+            Vec2::ZERO
+        };
+    }
+
+    pub fn stop_motion(&mut self) {
+        self.target = None;
+        self.pan_velocity = Vec2::ZERO;
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.target.is_some() || self.pan_velocity != Vec2::ZERO
+    }
+
+    /// Advances the running animation or kinetic pan by `dt` seconds.
+    pub fn step(&mut self, dt: f32) {
+        if let Some((zoom, pan)) = self.target {
+            let t = 1.0 - (-VIEW_ANIMATION_SPEED * dt).exp();
+            // zoom is interpolated in log space so every step feels the same
+            self.zoom = (self.zoom.ln() + (zoom.ln() - self.zoom.ln()) * t).exp();
+            self.pan += (pan - self.pan) * t;
+
+            let is_done = (zoom / self.zoom).ln().abs() < 1e-3
+                && ((pan - self.pan) * self.zoom).length() < 0.5;
+            if is_done {
+                self.zoom = zoom;
+                self.pan = pan;
+                self.target = None;
+            }
+        }
+
+        if self.pan_velocity != Vec2::ZERO {
+            self.pan += self.pan_velocity * dt / self.zoom;
+            self.pan_velocity *= (-PAN_FRICTION * dt).exp();
+            if self.pan_velocity.length() < MIN_FLING_SPEED {
+                self.pan_velocity = Vec2::ZERO;
+            }
+            self.clamp_pan();
+        }
+    }
+
+    pub fn min_zoom(&self) -> f32 {
+        self.fit_zoom() * MIN_ZOOM_FACTOR
+    }
+
+    pub fn max_zoom(&self) -> f32 {
+        self.fit_zoom() * MAX_ZOOM_FACTOR
+    }
+
+    /// Pan range that keeps at least part of the map inside the viewport at the current zoom.
+    pub fn pan_limits(&self) -> (Vec2, Vec2) {
+        let Some(bounds) = self.bounds else {
+            return (Vec2::ZERO, Vec2::ZERO);
+        };
+
+        let min_pan = self.viewport.min.to_vec2() / self.zoom - bounds.max.to_vec2();
+        let max_pan = self.viewport.max.to_vec2() / self.zoom - bounds.min.to_vec2();
+
+        (min_pan.min(max_pan), max_pan.max(min_pan))
+    }
+
+    pub fn clamp_pan(&mut self) {
+        if self.bounds.is_none() {
+            return;
+        }
+        let (min_pan, max_pan) = self.pan_limits();
+        self.pan = self.pan.clamp(min_pan, max_pan);
+    }
+
+    fn animate_to(&mut self, target: ViewTarget) {
+        self.pan_velocity = Vec2::ZERO;
+        self.target = Some(target);
+    }
+
+    fn fit_view(&self) -> Option<ViewTarget> {
+        let bounds = self.bounds?;
+        if !self.viewport.is_positive() {
+            return None;
+        }
+
+        let zoom = self.fit_zoom();
+        // centre of the bounds ends up in the centre of the viewport
+        let pan = self.viewport.center().to_vec2() / zoom - bounds.center().to_vec2();
+
+        Some((zoom, pan))
+    }
+
+    fn zoomed_view(&self, anchor: Pos2, factor: f32) -> ViewTarget {
+        let world = self.screen_to_canvas(anchor);
+        let zoom = (self.zoom * factor).clamp(self.min_zoom(), self.max_zoom());
+
+        (zoom, anchor.to_vec2() / zoom - world.to_vec2())
+    }
+
+    fn fit_zoom(&self) -> f32 {
+        let Some(bounds) = self.bounds else {
+            return 1.0;
+        };
+        if !self.viewport.is_positive() {
+            return 1.0;
+        }
+
+        // a single node (or a straight line) has no extent on one of the axes
+        let size = bounds.size().max(Vec2::splat(f32::EPSILON));
+        let zoom = (self.viewport.width() / size.x).min(self.viewport.height() / size.y);
+
+        zoom * FIT_MARGIN
+    }
+
     fn adjust_for_pan_and_zoom(&self, position: &Pos2) -> Pos2 {
         (*position + self.pan) * self.zoom
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use egui::{pos2, Rect};
+
+    use super::DrawingContext;
+    use crate::models::Node;
+
+    #[test]
+    fn fit_to_bounds_centres_the_map() {
+        let nodes = vec![
+            Node::new("0".to_string(), pos2(700.0, 230.0), 6.1, 49.6),
+            Node::new("1".to_string(), pos2(702.0, 231.0), 6.2, 49.5),
+        ];
+
+        let mut draw_ctx = DrawingContext::new();
+        draw_ctx.set_viewport(Rect::from_min_max(pos2(0.0, 0.0), pos2(1000.0, 800.0)));
+        draw_ctx.set_bounds(&nodes);
+        draw_ctx.fit_to_bounds();
+
+        let from = draw_ctx.calc_node_coords(&nodes[0]);
+        let to = draw_ctx.calc_node_coords(&nodes[1]);
+        let centre = from + (to - from) / 2.0;
+        assert!((centre - pos2(500.0, 400.0)).length() < 0.5);
+        assert!(from.x >= 0.0 && to.x <= 1000.0);
+        assert!(from.y >= 0.0 && to.y <= 800.0);
+
+        let (min_pan, max_pan) = draw_ctx.pan_limits();
+        assert!(min_pan.x <= draw_ctx.pan.x && draw_ctx.pan.x <= max_pan.x);
+        assert!(min_pan.y <= draw_ctx.pan.y && draw_ctx.pan.y <= max_pan.y);
+    }
+
+    #[test]
+    fn zoom_keeps_the_anchor_in_place() {
+        let nodes = vec![
+            Node::new("0".to_string(), pos2(700.0, 230.0), 6.1, 49.6),
+            Node::new("1".to_string(), pos2(702.0, 231.0), 6.2, 49.5),
+        ];
+
+        let mut draw_ctx = DrawingContext::new();
+        draw_ctx.set_viewport(Rect::from_min_max(pos2(0.0, 0.0), pos2(1000.0, 800.0)));
+        draw_ctx.set_bounds(&nodes);
+        draw_ctx.fit_to_bounds();
+
+        let anchor = draw_ctx.calc_node_coords(&nodes[1]);
+        draw_ctx.zoom_at(anchor, 2.0);
+
+        assert!((draw_ctx.calc_node_coords(&nodes[1]) - anchor).length() < 0.5);
+    }
+}
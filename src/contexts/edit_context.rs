@@ -0,0 +1,214 @@
+use enum_iterator::Sequence;
+use std::fmt;
+
+use crate::{
+    contexts::DataContext,
+    models::{Edge, Node},
+};
+
+#[derive(PartialEq, Eq, Hash, Sequence, Copy, Clone, Debug)]
+pub enum EditTool {
+    Select,
+    AddNode,
+    AddArc,
+    Delete,
+}
+
+impl fmt::Display for EditTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditTool::Select => write!(f, "Select / move"),
+            EditTool::AddNode => write!(f, "Add node"),
+            EditTool::AddArc => write!(f, "Add arc"),
+            EditTool::Delete => write!(f, "Delete"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Selection {
+    Node(String),
+    // (from, to)
+    Edge(String, String),
+}
+
+/// A reversible change to the graph.
+#[derive(Debug, Clone)]
+pub enum EditOp {
+    AddNode(Node, Vec<Edge>),
+    RemoveNode(Node, Vec<Edge>),
+    // (longitude, latitude) before and after
+    MoveNode {
+        id: String,
+        from: (f64, f64),
+        to: (f64, f64),
+    },
+    AddEdge(Edge),
+    RemoveEdge(Edge),
+    ReplaceEdge {
+        before: Edge,
+        after: Edge,
+    },
+}
+
+impl EditOp {
+    fn inverse(&self) -> EditOp {
+        match self.clone() {
+            EditOp::AddNode(node, edges) => EditOp::RemoveNode(node, edges),
+            EditOp::RemoveNode(node, edges) => EditOp::AddNode(node, edges),
+            EditOp::MoveNode { id, from, to } => EditOp::MoveNode {
+                id,
+                from: to,
+                to: from,
+            },
+            EditOp::AddEdge(edge) => EditOp::RemoveEdge(edge),
+            EditOp::RemoveEdge(edge) => EditOp::AddEdge(edge),
+            EditOp::ReplaceEdge { before, after } => EditOp::ReplaceEdge {
+                before: after,
+                after: before,
+            },
+        }
+    }
+
+    fn perform(&self, data_ctx: &mut DataContext) {
+        match self {
+            EditOp::AddNode(node, edges) => data_ctx.insert_node(node.clone(), edges.clone()),
+            EditOp::RemoveNode(node, _) => {
+                data_ctx.remove_node(&node.id);
+            }
+            EditOp::MoveNode { id, to, .. } => data_ctx.move_node(id, to.0, to.1, true),
+            EditOp::AddEdge(edge) => data_ctx.insert_edge(edge.clone()),
+            EditOp::RemoveEdge(edge) => {
+                data_ctx.remove_edge(&edge.from.id, &edge.to.id);
+            }
+            EditOp::ReplaceEdge { before, after } => {
+                data_ctx.remove_edge(&before.from.id, &before.to.id);
+                data_ctx.insert_edge(after.clone());
+            }
+        }
+    }
+}
+
+pub struct EditContext {
+    pub is_editing: bool,
+    pub tool: EditTool,
+    pub selection: Option<Selection>,
+    // first node of the arc being added
+    pub arc_start: Option<String>,
+    // node being dragged and where it was picked up
+    pub dragged: Option<(String, (f64, f64))>,
+    pub length_input: f32,
+    undo_stack: Vec<EditOp>,
+    redo_stack: Vec<EditOp>,
+    // depth of the undo stack when the map was loaded or saved, `None` once that state
+    // cannot be reached again
+    saved_depth: Option<usize>,
+}
+
+impl EditContext {
+    pub fn new() -> Self {
+        Self {
+            is_editing: false,
+            tool: EditTool::Select,
+            selection: None,
+            arc_start: None,
+            dragged: None,
+            length_input: 0.0,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            saved_depth: Some(0),
+        }
+    }
+
+    /// Forgets the history, for when another map is loaded.
+    pub fn reset(&mut self) {
+        self.selection = None;
+        self.arc_start = None;
+        self.dragged = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.saved_depth = Some(0);
+    }
+
+    /// The map differs from the file it was loaded from or last saved to.
+    pub fn is_dirty(&self) -> bool {
+        self.saved_depth != Some(self.undo_stack.len())
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_depth = Some(self.undo_stack.len());
+    }
+
+    pub fn apply(&mut self, op: EditOp, data_ctx: &mut DataContext) {
+        op.perform(data_ctx);
+        self.record(op);
+    }
+
+    /// Adds an operation that has already been performed to the history.
+    pub fn record(&mut self, op: EditOp) {
+        // the saved state was among the undone operations that are now dropped
+        if self.saved_depth > Some(self.undo_stack.len()) {
+            self.saved_depth = None;
+        }
+        self.undo_stack.push(op);
+        self.redo_stack.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn undo(&mut self, data_ctx: &mut DataContext) -> bool {
+        let Some(op) = self.undo_stack.pop() else {
+            return false;
+        };
+        op.inverse().perform(data_ctx);
+        self.redo_stack.push(op);
+        true
+    }
+
+    pub fn redo(&mut self, data_ctx: &mut DataContext) -> bool {
+        let Some(op) = self.redo_stack.pop() else {
+            return false;
+        };
+        op.perform(data_ctx);
+        self.undo_stack.push(op);
+        true
+    }
+}
+
+impl Default for EditContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_node;
+
+    #[test]
+    fn undoing_back_to_the_saved_map_is_clean() {
+        let mut data_ctx = DataContext::default();
+        let mut edit_ctx = EditContext::new();
+
+        edit_ctx.apply(EditOp::AddNode(test_node(0), vec![]), &mut data_ctx);
+        assert!(edit_ctx.is_dirty());
+        edit_ctx.undo(&mut data_ctx);
+        assert!(!edit_ctx.is_dirty());
+        edit_ctx.redo(&mut data_ctx);
+        edit_ctx.mark_saved();
+        assert!(!edit_ctx.is_dirty());
+
+        // the saved map is gone from the history once another edit replaces it
+        edit_ctx.undo(&mut data_ctx);
+        edit_ctx.apply(EditOp::AddNode(test_node(1), vec![]), &mut data_ctx);
+        edit_ctx.undo(&mut data_ctx);
+        assert!(edit_ctx.is_dirty());
+    }
+}
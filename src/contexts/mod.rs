@@ -1,12 +1,27 @@
 mod algorithm_context;
+mod analysis_context;
+mod basemap_context;
 mod data_context;
 mod draw_context;
+mod edit_context;
 
 #[allow(unused_imports)]
 pub use algorithm_context::AlgorithmContext;
 #[allow(unused_imports)]
-pub use algorithm_context::AlgorithmType;
+pub use algorithm_context::CostMetric;
+#[allow(unused_imports)]
+pub use algorithm_context::DayProfile;
+#[allow(unused_imports)]
+pub use algorithm_context::{CostModel, PersistedRuns};
+#[allow(unused_imports)]
+pub use analysis_context::{AnalysisContext, CentralityOverlay};
+#[allow(unused_imports)]
+pub use basemap_context::BasemapContext;
 #[allow(unused_imports)]
 pub use data_context::DataContext;
 #[allow(unused_imports)]
+pub use data_context::DataSource;
+#[allow(unused_imports)]
 pub use draw_context::DrawingContext;
+#[allow(unused_imports)]
+pub use edit_context::{EditContext, EditOp, EditTool, Selection};
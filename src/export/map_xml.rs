@@ -0,0 +1,108 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use super::xml_escape;
+use crate::models::{Edge, Node, TurnRestriction};
+
+/// Writes a graph back to the `<map><nodes><arcs>` format read by the parser.
+pub fn write_map_xml(
+    description: &str,
+    nodes: &[Node],
+    edges: &[Edge],
+    restrictions: &[TurnRestriction],
+    path: &Path,
+) -> io::Result<()> {
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="utf-8"?>"#).unwrap();
+    writeln!(xml, r#"<map description="{}">"#, xml_escape(description)).unwrap();
+
+    writeln!(xml, "  <nodes>").unwrap();
+    nodes.iter().for_each(|node| {
+        // the attributes are swapped like in the original files, see `parse_xml`
+        let mut element = format!(
+            r#"    <node id="{}" longitude="{}" latitude="{}""#,
+            xml_escape(&node.id),
+            (node.latitude * 100000.0).round() as i64,
+            (node.longitude * 100000.0).round() as i64
+        );
+        if let Some(label) = &node.label {
+            write!(element, r#" name="{}""#, xml_escape(label)).unwrap();
+        }
+        writeln!(xml, "{}/>", element).unwrap()
+    });
+    writeln!(xml, "  </nodes>").unwrap();
+
+    writeln!(xml, "  <arcs>").unwrap();
+    edges.iter().for_each(|edge| {
+        let mut arc = format!(
+            r#"    <arc from="{}" to="{}" length="{}""#,
+            xml_escape(&edge.from.id),
+            xml_escape(&edge.to.id),
+            edge.length
+        );
+        if let Some(road_class) = edge.road_class {
+            write!(arc, r#" class="{}""#, road_class).unwrap();
+        }
+        if let Some(speed) = edge.speed_limit {
+            write!(arc, r#" speed="{}""#, speed).unwrap();
+        }
+        if let Some(time) = edge.travel_time {
+            write!(arc, r#" time="{}""#, time).unwrap();
+        }
+        writeln!(xml, "{}/>", arc).unwrap();
+    });
+    writeln!(xml, "  </arcs>").unwrap();
+
+    if !restrictions.is_empty() {
+        writeln!(xml, "  <restrictions>").unwrap();
+        restrictions.iter().for_each(|turn| {
+            let mut restriction = format!(
+                r#"    <restriction from="{}" via="{}" to="{}""#,
+                xml_escape(&turn.from),
+                xml_escape(&turn.via),
+                xml_escape(&turn.to)
+            );
+            if let Some(penalty) = turn.penalty {
+                write!(restriction, r#" penalty="{}""#, penalty).unwrap();
+            }
+            writeln!(xml, "{}/>", restriction).unwrap();
+        });
+        writeln!(xml, "  </restrictions>").unwrap();
+    }
+    writeln!(xml, "</map>").unwrap();
+
+    fs::write(path, xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::parse_xml, translator::Translator, utils::constants::xml_data::TEST_XML};
+
+    #[test]
+    fn written_map_parses_back() {
+        let mut translator = Translator::default();
+        let (mut nodes, edges, ..) = parse_xml(TEST_XML, &mut translator);
+        nodes[0].label = Some("Place d'Armes & co".to_string());
+        let restrictions = vec![
+            TurnRestriction::new("0".into(), "1".into(), "2".into(), None),
+            TurnRestriction::new("2".into(), "1".into(), "0".into(), Some(15.)),
+        ];
+
+        let path = std::env::temp_dir().join("luxembourg-rs-written-map.xml");
+        write_map_xml("test", &nodes, &edges, &restrictions, &path).unwrap();
+        let (read_nodes, read_edges, _, read_restrictions, ..) =
+            parse_xml(&fs::read(&path).unwrap(), &mut translator);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(nodes, read_nodes);
+        assert_eq!(edges, read_edges);
+        assert_eq!(restrictions, read_restrictions);
+        nodes.iter().zip(&read_nodes).for_each(|(node, read)| {
+            assert_eq!(node.position, read.position);
+            assert_eq!(node.label, read.label);
+        });
+        edges.iter().zip(&read_edges).for_each(|(edge, read)| {
+            assert_eq!(edge.length, read.length);
+        });
+    }
+}
@@ -0,0 +1,114 @@
+use enum_iterator::Sequence;
+use std::{fmt, fmt::Write, fs, io, path::Path};
+
+use crate::analysis::DistanceMatrix;
+
+#[derive(PartialEq, Eq, Hash, Sequence, Copy, Clone, Debug)]
+pub enum MatrixFormat {
+    Csv,
+    Json,
+}
+
+impl MatrixFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MatrixFormat::Csv => "csv",
+            MatrixFormat::Json => "json",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "csv" => Some(MatrixFormat::Csv),
+            "json" => Some(MatrixFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MatrixFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatrixFormat::Csv => write!(f, "CSV"),
+            MatrixFormat::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+/// Writes the matrix as one `from,to,distance_m,hops` CSV row per pair, the unreachable
+/// pairs having empty values, or as JSON with one `distances_m` and one `hops` matrix.
+pub fn write_distance_matrix(
+    matrix: &DistanceMatrix,
+    format: MatrixFormat,
+    path: &Path,
+) -> io::Result<()> {
+    let contents = match format {
+        MatrixFormat::Csv => {
+            let mut csv = String::from("from,to,distance_m,hops\n");
+            matrix
+                .ids
+                .iter()
+                .zip(&matrix.cells)
+                .for_each(|(from, row)| {
+                    matrix
+                        .ids
+                        .iter()
+                        .zip(row)
+                        .for_each(|(to, cell)| match cell {
+                            Some((length, hops)) => {
+                                writeln!(csv, "{},{},{:.1},{}", from, to, length, hops).unwrap()
+                            }
+                            None => writeln!(csv, "{},{},,", from, to).unwrap(),
+                        })
+                });
+            csv
+        }
+        MatrixFormat::Json => {
+            let column = |value: fn(&(f32, usize)) -> serde_json::Value| {
+                matrix
+                    .cells
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|cell| cell.as_ref().map_or(serde_json::Value::Null, value))
+                            .collect::<Vec<serde_json::Value>>()
+                    })
+                    .collect::<Vec<Vec<serde_json::Value>>>()
+            };
+            let json = serde_json::json!({
+                "ids": matrix.ids,
+                "distances_m": column(|(length, _)| serde_json::json!(length)),
+                "hops": column(|(_, hops)| serde_json::json!(hops)),
+            });
+            serde_json::to_string_pretty(&json).unwrap()
+        }
+    };
+
+    fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_csv_rows() {
+        let matrix = DistanceMatrix {
+            ids: vec!["a".to_string(), "b".to_string()],
+            cells: vec![
+                vec![Some((0., 0)), Some((1500., 3))],
+                vec![None, Some((0., 0))],
+            ],
+        };
+        let path = std::env::temp_dir().join("luxembourg-rs-matrix.csv");
+
+        write_distance_matrix(&matrix, MatrixFormat::Csv, &path).unwrap();
+        let csv = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            csv,
+            "from,to,distance_m,hops\na,a,0.0,0\na,b,1500.0,3\nb,a,,\nb,b,0.0,0\n"
+        );
+    }
+}
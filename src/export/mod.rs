@@ -0,0 +1,21 @@
+mod map_xml;
+mod matrix;
+mod route;
+mod view;
+
+#[allow(unused_imports)]
+pub use map_xml::write_map_xml;
+#[allow(unused_imports)]
+pub use matrix::{write_distance_matrix, MatrixFormat};
+#[allow(unused_imports)]
+pub use route::{RouteExport, RouteFormat};
+#[allow(unused_imports)]
+pub use view::{Primitive, Scene, ViewFormat};
+
+pub fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,269 @@
+use enum_iterator::Sequence;
+use std::{fmt, fmt::Write, fs, io, path::Path};
+
+use super::xml_escape;
+use crate::models::{Edge, Node};
+
+#[derive(PartialEq, Eq, Hash, Sequence, Copy, Clone, Debug)]
+pub enum RouteFormat {
+    GeoJson,
+    Gpx,
+    Kml,
+}
+
+impl RouteFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RouteFormat::GeoJson => "geojson",
+            RouteFormat::Gpx => "gpx",
+            RouteFormat::Kml => "kml",
+        }
+    }
+}
+
+impl fmt::Display for RouteFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RouteFormat::GeoJson => write!(f, "GeoJSON"),
+            RouteFormat::Gpx => write!(f, "GPX"),
+            RouteFormat::Kml => write!(f, "KML"),
+        }
+    }
+}
+
+/// A computed route along with the settings it was computed with.
+pub struct RouteExport<'a> {
+    pub map_name: &'a str,
+    pub algorithm: String,
+    pub heuristic_weight: Option<f32>,
+    // in meters
+    pub length: f32,
+    // in seconds
+    pub duration: f32,
+    pub path: &'a [Node],
+    pub explored_edges: Option<Vec<&'a Edge>>,
+}
+
+impl RouteExport<'_> {
+    pub fn write(&self, format: RouteFormat, path: &Path) -> io::Result<()> {
+        let contents = match format {
+            RouteFormat::GeoJson => self.to_geojson(),
+            RouteFormat::Gpx => self.to_gpx(),
+            RouteFormat::Kml => self.to_kml(),
+        };
+
+        fs::write(path, contents)
+    }
+
+    fn description(&self) -> String {
+        let mut description = format!(
+            "{} on {}, {:.3} km, {:.1} min",
+            self.algorithm,
+            self.map_name,
+            self.length / 1000.,
+            self.duration / 60.
+        );
+        if let Some(weight) = self.heuristic_weight {
+            write!(description, ", heuristic weight {:.1}", weight).unwrap();
+        }
+
+        description
+    }
+
+    fn to_geojson(&self) -> String {
+        let coordinates = |nodes: &[&Node]| {
+            nodes
+                .iter()
+                .map(|node| format!("[{:.7},{:.7}]", node.longitude, node.latitude))
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+
+        let route_nodes = self.path.iter().collect::<Vec<&Node>>();
+        let node_ids = self
+            .path
+            .iter()
+            .map(|node| json_string(&node.id))
+            .collect::<Vec<String>>()
+            .join(",");
+        let weight = self
+            .heuristic_weight
+            .map(|weight| weight.to_string())
+            .unwrap_or("null".to_string());
+
+        let mut features = vec![format!(
+            r#"{{"type":"Feature","properties":{{"name":"Route","map":{},"algorithm":{},"heuristic_weight":{},"length_m":{},"duration_s":{},"node_ids":[{}]}},"geometry":{{"type":"LineString","coordinates":[{}]}}}}"#,
+            json_string(self.map_name),
+            json_string(&self.algorithm),
+            weight,
+            self.length,
+            self.duration,
+            node_ids,
+            coordinates(&route_nodes),
+        )];
+
+        if let Some(explored_edges) = &self.explored_edges {
+            let lines = explored_edges
+                .iter()
+                .map(|edge| format!("[{}]", coordinates(&[&edge.from, &edge.to])))
+                .collect::<Vec<String>>()
+                .join(",");
+            features.push(format!(
+                r#"{{"type":"Feature","properties":{{"name":"Explored edges","count":{}}},"geometry":{{"type":"MultiLineString","coordinates":[{}]}}}}"#,
+                explored_edges.len(),
+                lines
+            ));
+        }
+
+        format!(
+            r#"{{"type":"FeatureCollection","features":[{}]}}"#,
+            features.join(",")
+        )
+    }
+
+    fn to_gpx(&self) -> String {
+        let track_point = |node: &Node| {
+            format!(
+                r#"<trkpt lat="{:.7}" lon="{:.7}"><name>{}</name></trkpt>"#,
+                node.latitude,
+                node.longitude,
+                xml_escape(&node.id)
+            )
+        };
+
+        let mut gpx = String::new();
+        writeln!(gpx, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        writeln!(
+            gpx,
+            r#"<gpx version="1.1" creator="luxembourg-rs" xmlns="http://www.topografix.com/GPX/1/1">"#
+        )
+        .unwrap();
+        writeln!(
+            gpx,
+            "<metadata><name>Route</name><desc>{}</desc></metadata>",
+            xml_escape(&self.description())
+        )
+        .unwrap();
+
+        writeln!(
+            gpx,
+            "<trk><name>Route</name><type>{}</type><trkseg>",
+            xml_escape(&self.algorithm)
+        )
+        .unwrap();
+        self.path
+            .iter()
+            .for_each(|node| writeln!(gpx, "{}", track_point(node)).unwrap());
+        writeln!(gpx, "</trkseg></trk>").unwrap();
+
+        if let Some(explored_edges) = &self.explored_edges {
+            writeln!(gpx, "<trk><name>Explored edges</name>").unwrap();
+            explored_edges.iter().for_each(|edge| {
+                writeln!(
+                    gpx,
+                    "<trkseg>{}{}</trkseg>",
+                    track_point(&edge.from),
+                    track_point(&edge.to)
+                )
+                .unwrap()
+            });
+            writeln!(gpx, "</trk>").unwrap();
+        }
+        writeln!(gpx, "</gpx>").unwrap();
+
+        gpx
+    }
+
+    fn to_kml(&self) -> String {
+        let coordinates = |nodes: &[&Node]| {
+            nodes
+                .iter()
+                .map(|node| format!("{:.7},{:.7}", node.longitude, node.latitude))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        let data = |name: &str, value: &str| {
+            format!(
+                r#"<Data name="{}"><value>{}</value></Data>"#,
+                name,
+                xml_escape(value)
+            )
+        };
+
+        let mut kml = String::new();
+        writeln!(kml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        writeln!(
+            kml,
+            r#"<kml xmlns="http://www.opengis.net/kml/2.2"><Document>"#
+        )
+        .unwrap();
+        writeln!(kml, "<name>{}</name>", xml_escape(self.map_name)).unwrap();
+        writeln!(
+            kml,
+            r#"<Style id="route"><LineStyle><color>ff0000ff</color><width>4</width></LineStyle></Style>"#
+        )
+        .unwrap();
+        writeln!(
+            kml,
+            r#"<Style id="explored"><LineStyle><color>ff00ff00</color><width>1</width></LineStyle></Style>"#
+        )
+        .unwrap();
+
+        let mut extended_data = vec![
+            data("algorithm", &self.algorithm),
+            data("length_m", &self.length.to_string()),
+            data("duration_s", &self.duration.to_string()),
+        ];
+        if let Some(weight) = self.heuristic_weight {
+            extended_data.push(data("heuristic_weight", &weight.to_string()));
+        }
+        let route_nodes = self.path.iter().collect::<Vec<&Node>>();
+        writeln!(
+            kml,
+            "<Placemark><name>Route</name><description>{}</description><styleUrl>#route</styleUrl>\
+             <ExtendedData>{}</ExtendedData><LineString><tessellate>1</tessellate>\
+             <coordinates>{}</coordinates></LineString></Placemark>",
+            xml_escape(&self.description()),
+            extended_data.join(""),
+            coordinates(&route_nodes)
+        )
+        .unwrap();
+
+        if let Some(explored_edges) = &self.explored_edges {
+            let lines = explored_edges
+                .iter()
+                .map(|edge| {
+                    format!(
+                        "<LineString><coordinates>{}</coordinates></LineString>",
+                        coordinates(&[&edge.from, &edge.to])
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("");
+            writeln!(
+                kml,
+                "<Placemark><name>Explored edges</name><styleUrl>#explored</styleUrl>\
+                 <MultiGeometry>{}</MultiGeometry></Placemark>",
+                lines
+            )
+            .unwrap();
+        }
+        writeln!(kml, "</Document></kml>").unwrap();
+
+        kml
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    text.chars().for_each(|c| match c {
+        '"' => escaped.push_str("\\\""),
+        '\\' => escaped.push_str("\\\\"),
+        c if c.is_control() => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+        c => escaped.push(c),
+    });
+    escaped.push('"');
+
+    escaped
+}
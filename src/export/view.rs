@@ -0,0 +1,206 @@
+use egui::{Color32, Pos2, Rect, Vec2};
+use enum_iterator::Sequence;
+use std::{fmt, fmt::Write, fs, path::Path};
+
+use crate::utils::distance_to_segment;
+
+#[derive(PartialEq, Eq, Hash, Sequence, Copy, Clone, Debug)]
+pub enum ViewFormat {
+    Png,
+    Svg,
+}
+
+impl ViewFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ViewFormat::Png => "png",
+            ViewFormat::Svg => "svg",
+        }
+    }
+}
+
+impl fmt::Display for ViewFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ViewFormat::Png => write!(f, "PNG"),
+            ViewFormat::Svg => write!(f, "SVG"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Primitive {
+    Line {
+        from: Pos2,
+        to: Pos2,
+        width: f32,
+        color: Color32,
+    },
+    Circle {
+        center: Pos2,
+        radius: f32,
+        color: Color32,
+    },
+}
+
+impl Primitive {
+    fn bounding_rect(&self) -> Rect {
+        match self {
+            Primitive::Line {
+                from, to, width, ..
+            } => Rect::from_two_pos(*from, *to).expand(*width),
+            Primitive::Circle { center, radius, .. } => {
+                Rect::from_center_size(*center, Vec2::splat(2.0 * radius))
+            }
+        }
+    }
+}
+
+/// What is drawn on the map, in image coordinates, ready to be written to a file.
+pub struct Scene {
+    pub size: [u32; 2],
+    pub background: Color32,
+    primitives: Vec<Primitive>,
+}
+
+impl Scene {
+    pub fn new(size: [u32; 2], background: Color32) -> Self {
+        Self {
+            size,
+            background,
+            primitives: Vec::new(),
+        }
+    }
+
+    /// Adds a primitive, skipping it when it falls outside of the image.
+    pub fn push(&mut self, primitive: Primitive) {
+        let image_rect = Rect::from_min_size(
+            Pos2::ZERO,
+            Vec2::new(self.size[0] as f32, self.size[1] as f32),
+        );
+        if image_rect.intersects(primitive.bounding_rect()) {
+            self.primitives.push(primitive);
+        }
+    }
+
+    pub fn write(&self, format: ViewFormat, path: &Path) -> Result<(), String> {
+        match format {
+            ViewFormat::Png => self.to_image().save(path).map_err(|e| e.to_string()),
+            ViewFormat::Svg => fs::write(path, self.to_svg()).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn to_svg(&self) -> String {
+        let [width, height] = self.size;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            svg_color(self.background)
+        )
+        .unwrap();
+        writeln!(svg, r#"<g stroke-linecap="round">"#).unwrap();
+        self.primitives.iter().for_each(|primitive| match primitive {
+            Primitive::Line {
+                from,
+                to,
+                width,
+                color,
+            } => writeln!(
+                svg,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{}" stroke-width="{}"/>"#,
+                from.x,
+                from.y,
+                to.x,
+                to.y,
+                svg_color(*color),
+                width
+            )
+            .unwrap(),
+            Primitive::Circle {
+                center,
+                radius,
+                color,
+            } => writeln!(
+                svg,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="{}"/>"#,
+                center.x,
+                center.y,
+                radius,
+                svg_color(*color)
+            )
+            .unwrap(),
+        });
+        writeln!(svg, "</g>\n</svg>").unwrap();
+
+        svg
+    }
+
+    fn to_image(&self) -> image::RgbaImage {
+        let [width, height] = self.size;
+        let mut image =
+            image::RgbaImage::from_pixel(width, height, image::Rgba(self.background.to_array()));
+
+        self.primitives.iter().for_each(|primitive| {
+            let rect = primitive.bounding_rect().expand(1.0);
+            let min_x = rect.min.x.max(0.0) as u32;
+            let min_y = rect.min.y.max(0.0) as u32;
+            let max_x = (rect.max.x.max(0.0) as u32).min(width - 1);
+            let max_y = (rect.max.y.max(0.0) as u32).min(height - 1);
+
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    let pixel = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                    let (coverage, color) = match primitive {
+                        Primitive::Line {
+                            from,
+                            to,
+                            width,
+                            color,
+                        } => (
+                            width / 2.0 + 0.5 - distance_to_segment(pixel, *from, *to),
+                            color,
+                        ),
+                        Primitive::Circle {
+                            center,
+                            radius,
+                            color,
+                        } => (radius + 0.5 - pixel.distance(*center), color),
+                    };
+                    if coverage > 0.0 {
+                        blend(image.get_pixel_mut(x, y), *color, coverage.min(1.0));
+                    }
+                }
+            }
+        });
+
+        image
+    }
+}
+
+// source-over blending of a premultiplied colour scaled by its pixel coverage
+fn blend(pixel: &mut image::Rgba<u8>, color: Color32, coverage: f32) {
+    let source = color.to_array().map(|c| c as f32 * coverage);
+    let inverse_alpha = 1.0 - source[3] / 255.0;
+
+    for (channel, source) in pixel.0.iter_mut().zip(source) {
+        *channel = (source + *channel as f32 * inverse_alpha)
+            .round()
+            .min(255.0) as u8;
+    }
+}
+
+fn svg_color(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("rgba({},{},{},{:.3})", r, g, b, a as f32 / 255.0)
+    }
+}
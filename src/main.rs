@@ -1,8 +1,18 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
+mod algorithms;
+mod analysis;
+mod cli;
 mod contexts;
+mod export;
 mod map;
 mod models;
+mod node_search;
 mod parser;
+mod projections;
+mod scenario;
+mod session;
+mod tiles;
+mod traffic;
 mod translator;
 mod utils;
 mod components;
@@ -23,6 +33,15 @@ fn main() -> Result<(), eframe::Error> {
     builder.target(Target::Stdout);
     builder.init();
 
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if let Some(result) = cli::run(&args) {
+        if let Err(message) = result {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let rt = Runtime::new().expect("Unable to create Runtime");
     // Enter the runtime so that `tokio::spawn` is available immediately.
     let _enter = rt.enter();
@@ -45,6 +64,6 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "luxembourg-rs",
         options,
-        Box::new(|_cc| Box::<Map>::from(Map::new())),
+        Box::new(|cc| Box::<Map>::from(Map::new(cc))),
     )
 }
\ No newline at end of file
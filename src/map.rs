@@ -1,17 +1,48 @@
-use egui::{Align2, Color32, Pos2};
+use egui::{epaint::Vertex, Align2, Color32, Key, Mesh, Pos2, Shape, Vec2};
 use egui_toast::{Toast, ToastKind, ToastOptions, Toasts};
 use enum_iterator::all;
 use log::debug;
-use std::{collections::HashMap, sync::mpsc::Sender};
+use std::{fmt, fs::File, io::BufReader, path::PathBuf, sync::Arc};
+
+mod centrality;
+mod edit;
+mod heuristic_check;
+mod incidents;
+mod matrix;
+mod node_search;
+mod route_export;
+mod session;
+mod statistics;
+mod traffic;
+mod trees;
+mod validation;
+mod view_export;
 
 use crate::{
-    components::{toggle, toggle_ui},
-    contexts::{AlgorithmContext, AlgorithmType, DataContext, DrawingContext},
+    analysis::{TreeKind, ValidationReport},
+    components::{parameter_widget, toggle, toggle_ui},
+    contexts::{
+        AlgorithmContext, AnalysisContext, BasemapContext, CostMetric, DataContext, DataSource,
+        DayProfile, DrawingContext, EditContext, PersistedRuns,
+    },
+    export::{MatrixFormat, RouteFormat, ViewFormat},
     models::{Edge, Node},
-    parser::parse_xml,
+    parser::{parse_xml, parse_xml_streaming, ParseEvent},
+    projections::ProjectionKind,
+    scenario::Incident,
+    session::{Session, RUNS_KEY, SESSION_KEY},
+    tiles::TileSource,
+    traffic::format_time_of_day,
     utils::{
-        constants::{DEFAULT_PAN, DEFAULT_ZOOM, MAX_PAN, MAX_ZOOM, MIN_PAN},
-        euclidean_distance, FloatOrd, FrameHistory,
+        constants::{
+            ARC_PICK_DISTANCE, CLOSED_EDGE_COLOR, EDGE_WIDTH, HEAT_EDGE_EXTRA_WIDTH,
+            HEURISTIC_VIOLATION_COLOR, HEURISTIC_VIOLATION_WIDTH, INCIDENT_EDGE_WIDTH,
+            KEYBOARD_PAN_STEP, KEYBOARD_ZOOM_STEP, NEGATIVE_CYCLE_COLOR, NODE_RADIUS,
+            PASSED_EDGE_COLOR, PASSED_EDGE_WIDTH, ROUTE_EDGE_COLOR, ROUTE_EDGE_WIDTH,
+            SEARCH_PROGRESS_INTERVAL, SELECTED_NODE_COLOR, SELECTED_NODE_RADIUS, SLOWED_EDGE_COLOR,
+            WAYPOINT_NODE_COLOR, ZOOM_SCROLL_SPEED,
+        },
+        distance_to_segment, euclidean_distance, FloatOrd, FrameHistory,
     },
 };
 
@@ -19,17 +50,61 @@ pub struct Map {
     data_ctx: DataContext,
     draw_ctx: DrawingContext,
     algorithm_ctx: AlgorithmContext,
+    basemap_ctx: BasemapContext,
+    edit_ctx: EditContext,
+    analysis_ctx: AnalysisContext,
     state: UIState,
+    // restored once the map it refers to has been loaded
+    pending_session: Option<Session>,
+}
+
+// what a selected node becomes in the route
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum NodeRole {
+    Start,
+    End,
+    Waypoint,
+}
+
+impl fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeRole::Start => write!(f, "start"),
+            NodeRole::End => write!(f, "end"),
+            NodeRole::Waypoint => write!(f, "waypoint"),
+        }
+    }
 }
 
 impl Map {
-    pub fn new() -> Self {
-        Self {
+    pub fn new(cc: &eframe::CreationContext) -> Self {
+        let mut map = Self {
             data_ctx: DataContext::default(),
             draw_ctx: DrawingContext::new(),
             algorithm_ctx: AlgorithmContext::new(),
+            basemap_ctx: BasemapContext::new(),
+            edit_ctx: EditContext::new(),
+            analysis_ctx: AnalysisContext::new(),
             state: UIState::default(),
+            pending_session: None,
+        };
+
+        // the first load picks up the restored map, no need to reload here
+        if let Some(session) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Session>(storage, SESSION_KEY))
+        {
+            map.apply_session(session);
         }
+        if let Some(runs) = cc
+            .storage
+            .filter(|_| map.algorithm_ctx.persist_runs)
+            .and_then(|storage| eframe::get_value::<PersistedRuns>(storage, RUNS_KEY))
+        {
+            map.algorithm_ctx.restore_runs(runs);
+        }
+
+        map
     }
 
     fn is_selected(&self, node_id: &str) -> bool {
@@ -47,10 +122,14 @@ impl Map {
     }
 
     fn find_closest_node(&self, pos: Pos2) -> Option<Node> {
+        self.find_closest_node_where(pos, |_| true)
+    }
+
+    fn find_closest_node_where(&self, pos: Pos2, filter: impl Fn(&Node) -> bool) -> Option<Node> {
         let mut closest_node = None;
         let mut closest_distance = f32::MAX;
 
-        for node in &self.data_ctx.nodes {
+        for node in self.data_ctx.nodes.iter().filter(|node| filter(node)) {
             let node_pos = self.draw_ctx.calc_node_coords(node);
             let distance = euclidean_distance(&pos, &node_pos);
 
@@ -63,17 +142,102 @@ impl Map {
         closest_node
     }
 
+    fn find_closest_edge(&self, pos: Pos2) -> Option<Edge> {
+        self.data_ctx
+            .edges
+            .iter()
+            .map(|edge| {
+                let (from, to) = self.draw_ctx.calc_edge_coords(edge);
+                (edge, distance_to_segment(pos, from, to))
+            })
+            .filter(|(_, distance)| *distance < ARC_PICK_DISTANCE)
+            .min_by_key(|(_, distance)| FloatOrd(*distance))
+            .map(|(edge, _)| edge.clone())
+    }
+
+    // start and end nodes may be restricted to the largest strongly connected component
+    fn is_pickable(&self, node: &Node) -> bool {
+        !self.state.pick_in_largest_component
+            || self
+                .data_ctx
+                .validation
+                .as_ref()
+                .is_none_or(|report| report.is_in_largest_component(node))
+    }
+
+    fn component_color(&self, node: &Node) -> Option<Color32> {
+        if !self.state.highlight_components {
+            return None;
+        }
+        match self.data_ctx.validation.as_ref()?.component(node)? {
+            0 => None,
+            component => Some(route_color(component - 1)),
+        }
+    }
+
     fn send_load_data_req(&mut self, ctx: &egui::Context) {
+        self.data_ctx.discard_loads();
         self.data_ctx.nodes_loading = true;
         self.data_ctx.edges_loading = true;
+        self.data_ctx.load_progress = None;
+        self.data_ctx.nodes.clear();
+        self.data_ctx.edges.clear();
+        self.data_ctx.validation = None;
+        self.data_ctx.discard_statistics();
+        self.analysis_ctx.discard();
+        self.analysis_ctx.terminals.clear();
+        self.edit_ctx.reset();
+        // the cached runs, the incidents and the traffic belong to the previous map
+        self.algorithm_ctx.clear_incidents();
+        self.algorithm_ctx.set_traffic(None);
+        self.algorithm_ctx.waypoints.clear();
+        self.state.day_profile = None;
+        self.state.node_matches.clear();
 
-        send_parse_request(
-            self.data_ctx.tx_nodes.clone(),
-            self.data_ctx.tx_edges.clone(),
-            self.data_ctx.tx_neighbors.clone(),
-            self.data_ctx.data_buffer(),
-            ctx.clone(),
-        );
+        send_parse_request(&self.data_ctx, ctx.clone());
+    }
+
+    fn render_basemap(&mut self, ui: &mut egui::Ui) {
+        if !self.basemap_ctx.is_visible || self.basemap_ctx.source().is_none() {
+            return;
+        }
+
+        self.basemap_ctx.receive_tiles(ui.ctx());
+        let translator = self.data_ctx.translator();
+        let tiles = self.basemap_ctx.visible_tiles(&self.draw_ctx, translator);
+        self.basemap_ctx.request_tiles(&tiles, ui.ctx());
+
+        let tint = Color32::from_white_alpha((self.basemap_ctx.opacity * 255.).round() as u8);
+        let uvs = [
+            Pos2::new(0., 0.),
+            Pos2::new(1., 0.),
+            Pos2::new(1., 1.),
+            Pos2::new(0., 1.),
+        ];
+
+        tiles.iter().for_each(|tile| {
+            let Some(texture) = self.basemap_ctx.texture(tile) else {
+                return;
+            };
+
+            // the corners are projected one by one so tiles follow any projection
+            let mut mesh = Mesh::with_texture(texture.id());
+            tile.corners()
+                .iter()
+                .zip(uvs)
+                .for_each(|(&(longitude, latitude), uv)| {
+                    let position = translator.translate_coordinates(longitude, latitude);
+                    mesh.vertices.push(Vertex {
+                        pos: self.draw_ctx.canvas_to_screen(position),
+                        uv,
+                        color: tint,
+                    });
+                });
+            mesh.add_triangle(0, 1, 2);
+            mesh.add_triangle(0, 2, 3);
+
+            ui.painter().add(Shape::mesh(mesh));
+        });
     }
 
     fn render_edges(&mut self, ui: &mut egui::Ui) {
@@ -81,38 +245,60 @@ impl Map {
             return;
         }
 
-        let edge_color = if ui.visuals().dark_mode {
-            Color32::DARK_GRAY
-        } else {
-            Color32::GRAY
-        };
+        let edge_color = edge_color(ui.visuals().dark_mode);
 
         let mut selected_to_draw = Vec::new();
         let mut passed_to_draw = Vec::new();
+        let mut incidents_to_draw = Vec::new();
         let mut edges_to_draw = Vec::new();
 
         self.data_ctx.edges.iter().for_each(|edge| {
             if self.state.is_drawing_path {
                 if self.algorithm_ctx.is_edge_selected(edge) {
-                    selected_to_draw.push((edge.clone(), 2.0, Color32::RED));
+                    selected_to_draw.push((edge.clone(), ROUTE_EDGE_WIDTH, ROUTE_EDGE_COLOR));
                     return;
                 } else if self.algorithm_ctx.is_marking_passed_edges
                     && self.algorithm_ctx.is_edge_passed(edge)
                 {
-                    passed_to_draw.push((edge.clone(), 1.0, Color32::GREEN));
+                    passed_to_draw.push((edge.clone(), PASSED_EDGE_WIDTH, PASSED_EDGE_COLOR));
                     return;
                 }
             }
-            edges_to_draw.push((edge, 0.5, edge_color));
+            if let Some(incident) = self.algorithm_ctx.incident(edge) {
+                incidents_to_draw.push((
+                    edge.clone(),
+                    INCIDENT_EDGE_WIDTH,
+                    incident_color(incident),
+                ));
+                return;
+            }
+            if let Some(heat) = self.centrality_heat(edge) {
+                edges_to_draw.push((
+                    edge,
+                    EDGE_WIDTH + heat * HEAT_EDGE_EXTRA_WIDTH,
+                    heat_color(heat),
+                ));
+                return;
+            }
+            // arcs inside an island take the colour of its nodes
+            let color = self
+                .component_color(&edge.from)
+                .filter(|color| self.component_color(&edge.to) == Some(*color))
+                .unwrap_or(edge_color);
+            edges_to_draw.push((edge, EDGE_WIDTH, color));
         });
 
         assert_eq!(
-            edges_to_draw.len() + selected_to_draw.len() + passed_to_draw.len(),
+            edges_to_draw.len()
+                + selected_to_draw.len()
+                + passed_to_draw.len()
+                + incidents_to_draw.len(),
             self.data_ctx.edges.len(),
             "edges_to_draw + passed_to_draw count does not match data_ctx.edges count"
         );
         self.state.selected_edges = Some(selected_to_draw);
         self.state.passed_edges = Some(passed_to_draw);
+        self.state.incident_edges = Some(incidents_to_draw);
 
         edges_to_draw.into_iter().for_each(|(edge, size, color)| {
             let (from, to) = self.draw_ctx.calc_edge_coords(edge);
@@ -126,10 +312,17 @@ impl Map {
 
         self.data_ctx.nodes.iter().for_each(|node| {
             if self.is_selected(&node.id) {
-                selected_to_draw.push((node.clone(), 3.0, Color32::RED));
+                selected_to_draw.push((node.clone(), SELECTED_NODE_RADIUS, SELECTED_NODE_COLOR));
                 return;
             }
-            nodes_to_draw.push((node, 0.5, ui.visuals().text_color()));
+            if self.algorithm_ctx.waypoints.contains(node) {
+                selected_to_draw.push((node.clone(), SELECTED_NODE_RADIUS, WAYPOINT_NODE_COLOR));
+                return;
+            }
+            let color = self
+                .component_color(node)
+                .unwrap_or(ui.visuals().text_color());
+            nodes_to_draw.push((node, NODE_RADIUS, color));
         });
 
         assert_eq!(
@@ -148,53 +341,62 @@ impl Map {
     }
 
     fn select_node(&mut self, node: Node) {
-        let node_id = node.id.clone();
         match (&self.state.start_node, &self.state.end_node) {
-            (None, _) => {
-                // start node is not set
-                self.state.start_node = Some(node);
-                self.state.show_toast(
-                    format!("Node {} selected as 'start'", &node_id),
-                    ToastKind::Info,
-                );
-            }
-            (Some(_), None) => {
-                // end node is not set
-                self.state.end_node = Some(node);
-                self.state.show_toast(
-                    format!("Node {} selected as 'end'", &node_id),
-                    ToastKind::Info,
-                );
-            }
+            // start node is not set
+            (None, _) => self.assign_node(node, NodeRole::Start),
+            // end node is not set
+            (Some(_), None) => self.assign_node(node, NodeRole::End),
+            // both start and end nodes are set
             (Some(_), Some(_)) => {
-                // both start and end nodes are set
-                self.state.start_node = Some(node);
                 self.state.end_node = None;
-                self.state.is_drawing_path = false;
-                self.state.show_toast(
-                    format!("Node {} selected as 'start'", &node_id),
-                    ToastKind::Info,
-                );
+                self.assign_node(node, NodeRole::Start);
             }
         }
     }
 
+    // clicked or searched for, the node has to be pickable either way
+    fn assign_node(&mut self, node: Node, role: NodeRole) {
+        if !self.is_pickable(&node) {
+            self.state.show_toast(
+                format!("Node {} is outside of the largest component", node.id),
+                ToastKind::Warning,
+            );
+            return;
+        }
+
+        let node_id = node.id.clone();
+        match role {
+            NodeRole::Start => self.state.start_node = Some(node),
+            NodeRole::End => self.state.end_node = Some(node),
+            NodeRole::Waypoint => self.algorithm_ctx.waypoints.push(node),
+        }
+        self.state.is_drawing_path = false;
+        self.state.show_toast(
+            format!("Node {} selected as '{}'", node_id, role),
+            ToastKind::Info,
+        );
+    }
+
     fn render_controls(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         ui.heading("Luxembourg Map");
         ui.horizontal(|ui| {
             ui.vertical(|ui| {
+                let max_zoom = self.draw_ctx.max_zoom();
+                let (min_pan, max_pan) = self.draw_ctx.pan_limits();
+                let min_zoom = self.draw_ctx.min_zoom();
                 ui.add(
-                    egui::Slider::new(&mut self.draw_ctx.zoom, 0.0..=MAX_ZOOM)
-                        .step_by(0.1)
+                    egui::Slider::new(&mut self.draw_ctx.zoom, min_zoom..=max_zoom)
+                        .logarithmic(true)
                         .text("Zoom"),
-                );
+                )
+                .on_hover_text("Scroll or pinch over the map, or use + / - and 0 to fit");
                 ui.add(
-                    egui::Slider::new(&mut self.draw_ctx.pan.x, MIN_PAN.x..=MAX_PAN.x)
+                    egui::Slider::new(&mut self.draw_ctx.pan.x, min_pan.x..=max_pan.x)
                         .step_by(0.1)
                         .text("Pan x"),
                 );
                 ui.add(
-                    egui::Slider::new(&mut self.draw_ctx.pan.y, MIN_PAN.y..=MAX_PAN.y)
+                    egui::Slider::new(&mut self.draw_ctx.pan.y, min_pan.y..=max_pan.y)
                         .step_by(0.1)
                         .text("Pan y"),
                 );
@@ -204,59 +406,197 @@ impl Map {
                     self.data_ctx.switch_data_file();
                     self.send_load_data_req(ctx);
                 }
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.state.map_path_input)
+                            .hint_text("path/to/map.xml")
+                            .desired_width(160.),
+                    );
+                    if ui
+                        .button("Open map file")
+                        .on_hover_text("Load a map from disk using the streaming parser")
+                        .clicked()
+                    {
+                        let path = PathBuf::from(self.state.map_path_input.trim());
+                        if path.is_file() {
+                            self.data_ctx.open_data_file(path);
+                            self.send_load_data_req(ctx);
+                        } else {
+                            self.state.show_toast(
+                                format!("File not found: {}", path.display()),
+                                ToastKind::Error,
+                            );
+                        }
+                    }
+                });
+                let projection_before = self.data_ctx.projection();
+                let mut projection = projection_before;
+                egui::ComboBox::from_label("Projection")
+                    .selected_text(projection.to_string())
+                    .show_ui(ui, |ui| {
+                        for kind in all::<ProjectionKind>() {
+                            ui.selectable_value(&mut projection, kind, kind.to_string());
+                        }
+                    });
+                if projection != projection_before {
+                    self.data_ctx.set_projection(projection);
+                    self.send_load_data_req(ctx);
+                }
+                egui::CollapsingHeader::new("Find node")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        self.render_node_search(ui);
+                    });
+                egui::CollapsingHeader::new("Export view")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_view_export(ui);
+                    });
+                egui::CollapsingHeader::new("Basemap")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_basemap_controls(ui);
+                    });
+                egui::CollapsingHeader::new("Analytics")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_analytics(ui, ctx);
+                    });
+                egui::CollapsingHeader::new("Centrality")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_centrality_controls(ui, ctx);
+                    });
+                egui::CollapsingHeader::new("Distance matrix")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_matrix_controls(ui, ctx);
+                    });
+                egui::CollapsingHeader::new("Network design")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_tree_controls(ui, ctx);
+                    });
+                egui::CollapsingHeader::new("Heuristic check")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_heuristic_check(ui, ctx);
+                    });
+                egui::CollapsingHeader::new("Validation")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_validation_report(ui);
+                    });
+                egui::CollapsingHeader::new("Edit map")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_edit_controls(ui);
+                    });
+                egui::CollapsingHeader::new("Incidents")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_incident_controls(ui);
+                    });
+                egui::CollapsingHeader::new("Traffic")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_traffic_controls(ui);
+                    });
+                egui::CollapsingHeader::new("Session")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.render_session_controls(ui, ctx);
+                    });
                 ui.label("Show passed edges");
                 ui.add(toggle(&mut self.algorithm_ctx.is_marking_passed_edges));
                 egui::ComboBox::from_label("Select algorithm variation")
-                    .selected_text(self.algorithm_ctx.algorithm_type.to_string())
+                    .selected_text(self.algorithm_ctx.algorithm().name())
                     .show_ui(ui, |ui| {
-                        for algorithm in all::<AlgorithmType>() {
+                        let selected = self.algorithm_ctx.algorithm().id();
+                        let mut clicked = None;
+                        for algorithm in self.algorithm_ctx.registry().iter() {
                             if ui
-                                .selectable_label(
-                                    self.algorithm_ctx.algorithm_type.to_string()
-                                        == algorithm.to_string(),
-                                    algorithm.to_string(),
-                                )
+                                .selectable_label(selected == algorithm.id(), algorithm.name())
                                 .on_hover_text("Select algorithm variation")
                                 .clicked()
                             {
-                                self.algorithm_ctx.algorithm_type = algorithm;
+                                clicked = Some(algorithm.id());
                             }
                         }
+                        if let Some(id) = clicked {
+                            self.algorithm_ctx.set_algorithm(id);
+                        }
+                    });
+                self.render_algorithm_parameters(ui);
+                let cost_model = &mut self.algorithm_ctx.cost_model;
+                egui::ComboBox::from_label("Cost metric")
+                    .selected_text(cost_model.metric.to_string())
+                    .show_ui(ui, |ui| {
+                        for metric in all::<CostMetric>() {
+                            ui.selectable_value(&mut cost_model.metric, metric, metric.to_string());
+                        }
                     });
-                if self.algorithm_ctx.algorithm_type == AlgorithmType::HybridAStar {
-                    ui.label("A* weight");
+                if cost_model.metric == CostMetric::Custom {
                     ui.add(
-                        egui::Slider::new(&mut self.algorithm_ctx.astar_weight.0, 0.0..=2.0)
+                        egui::Slider::new(&mut cost_model.distance_weight.0, -10.0..=10.0)
                             .step_by(0.1)
-                            .text("Weight"),
+                            .text("Per meter"),
                     );
-                    if ui.button("Reset A* weight").clicked() {
-                        self.algorithm_ctx.astar_weight = FloatOrd(1.0);
+                    ui.add(
+                        egui::Slider::new(&mut cost_model.time_weight.0, -10.0..=10.0)
+                            .step_by(0.1)
+                            .text("Per second"),
+                    );
+                }
+                if !self.algorithm_ctx.algorithm().handles_negative_costs() {
+                    if let Some(edge) = self.algorithm_ctx.negative_cost_edge(&self.data_ctx.edges)
+                    {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!(
+                                "The arc {} -> {} has a negative cost, use Bellman-Ford or Johnson",
+                                edge.from.id, edge.to.id
+                            ),
+                        );
                     }
                 }
-                if self.algorithm_ctx.is_using_astar() {
-                    ui.label(format!(
-                        "Uses {} distance",
-                        if self.algorithm_ctx.use_manhattan {
-                            "Manhattan"
-                        } else {
-                            "Euclidean"
-                        }
-                    ));
-                    ui.add(toggle(&mut self.algorithm_ctx.use_manhattan));
+                ui.label(format!(
+                    "Turn restrictions ({})",
+                    self.algorithm_ctx.turn_restriction_count()
+                ));
+                ui.add(toggle(&mut self.algorithm_ctx.use_turn_restrictions))
+                    .on_hover_text("Search over the arcs to respect forbidden turns and penalties");
+                if self.algorithm_ctx.use_turn_restrictions {
+                    ui.checkbox(&mut self.algorithm_ctx.allow_u_turns, "Allow U-turns");
                 }
                 if ui
                     .button("Reset zoom and pan")
                     .on_hover_text("Reset zoom and pan")
                     .clicked()
                 {
-                    self.draw_ctx.zoom = DEFAULT_ZOOM;
-                    self.draw_ctx.pan = DEFAULT_PAN;
+                    self.draw_ctx.animate_fit_to_bounds();
                 }
                 ui.label(format!("Data loaded: {}", self.data_ctx.has_data()));
+                let (longitude, latitude) = self
+                    .data_ctx
+                    .translator()
+                    .unproject(self.draw_ctx.screen_to_canvas(self.state.mouse_pos));
+                ui.label(format!("Cursor: {:.5}, {:.5}", latitude, longitude));
                 ui.label(format!("FPS: {:.1}", self.state.frame_history.fps()));
                 self.state.frame_history.ui(ui);
 
+                if let Some(progress) = &self.data_ctx.load_progress {
+                    if self.data_ctx.edges_loading {
+                        ui.add(
+                            egui::ProgressBar::new(progress.fraction())
+                                .text(format!(
+                                    "{} nodes, {} edges",
+                                    progress.nodes, progress.edges
+                                ))
+                                .desired_width(200.),
+                        );
+                    }
+                }
                 if self.data_ctx.nodes_loading {
                     ui.label("Loading nodes...");
                 }
@@ -266,53 +606,239 @@ impl Map {
             });
         });
 
+        if self.algorithm_ctx.is_searching() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!(
+                    "Searching, {} steps",
+                    self.algorithm_ctx.search_steps().unwrap_or_default()
+                ));
+                if ui.button("Cancel").clicked() {
+                    self.algorithm_ctx.cancel_search();
+                }
+            });
+            // the steps are only counted, the search does not ask for repaints
+            ui.ctx().request_repaint_after(SEARCH_PROGRESS_INTERVAL);
+        }
         if self.state.is_drawing_path {
-            ui.label(format!(
-                "Distance: {:.3} km",
-                // The total cost seems to be in meters, so we divide by 1000 to get kilometers
-                self.algorithm_ctx.total_cost / 1000.
-            ));
+            ui.label(
+                self.algorithm_ctx
+                    .cost_model
+                    .format_cost(self.algorithm_ctx.total_cost),
+            );
+            if !self.algorithm_ctx.path().is_empty() {
+                ui.label(match self.algorithm_ctx.optimality_bound() {
+                    Some(bound) if bound <= 1.0 => "Optimal route".to_string(),
+                    Some(bound) => format!("At most {:.2} times the best cost", bound),
+                    None => "No optimality guarantee".to_string(),
+                })
+                .on_hover_text("Assuming the remaining cost estimate never overestimates");
+            }
+            let solutions = self.algorithm_ctx.solutions();
+            if solutions.len() > 1 {
+                ui.collapsing(format!("{} successive routes", solutions.len()), |ui| {
+                    solutions.iter().for_each(|(bound, cost)| {
+                        ui.label(format!(
+                            "{}, bound {:.2}",
+                            self.algorithm_ctx.cost_model.format_cost(*cost),
+                            bound.unwrap_or(f32::INFINITY)
+                        ));
+                    });
+                });
+            }
+            if self.algorithm_ctx.cost_model.metric != CostMetric::Distance {
+                ui.label(format!(
+                    "Distance: {:.3} km",
+                    self.algorithm_ctx.route_length / 1000.
+                ));
+            }
+            if self.algorithm_ctx.cost_model.metric != CostMetric::Time {
+                ui.label(format!(
+                    "Travel time: {:.1} min",
+                    self.algorithm_ctx.route_duration / 60.
+                ));
+            }
+            if self.algorithm_ctx.traffic().is_some() {
+                let departure = self.algorithm_ctx.departure as f32 * 60.;
+                ui.label(format!(
+                    "Departure {}, arrival {}",
+                    format_time_of_day(departure),
+                    format_time_of_day(departure + self.algorithm_ctx.route_duration)
+                ));
+            }
             if self.state.passed_edges.is_some() {
                 ui.label(format!(
                     "Extra edges passed: {}",
                     self.state.passed_edges.as_ref().unwrap().len()
                 ));
             }
+            egui::CollapsingHeader::new("Export route")
+                .default_open(false)
+                .show(ui, |ui| {
+                    self.render_route_export(ui);
+                });
         }
 
+        // typing in a text field must not move the map
+        let keyboard_free = !ctx.wants_keyboard_input();
+        // nor must clicking the controls edit it
+        let controls_rect = ui.min_rect();
+
         ui.ctx().input(|i| {
             self.state.mouse_pos = i.pointer.interact_pos().unwrap_or_default();
 
             if i.pointer.is_decidedly_dragging() {
-                let delta = i.pointer.delta();
+                let origin = i.pointer.press_origin().unwrap_or(self.state.mouse_pos);
+                if !self.drag_node(origin, controls_rect) {
+                    let delta = i.pointer.delta();
 
-                self.draw_ctx.pan.x += delta.x / self.draw_ctx.zoom;
-                self.draw_ctx.pan.y += delta.y / self.draw_ctx.zoom;
+                    self.draw_ctx.stop_motion();
+                    self.draw_ctx.pan.x += delta.x / self.draw_ctx.zoom;
+                    self.draw_ctx.pan.y += delta.y / self.draw_ctx.zoom;
+                    self.draw_ctx.clamp_pan();
+                }
+                self.state.is_dragging = true;
+            } else if self.state.is_dragging {
+                if let Some((id, from)) = self.edit_ctx.dragged.take() {
+                    self.drop_node(id, from);
+                } else {
+                    // drag released, let the map glide on
+                    self.draw_ctx.fling(i.pointer.velocity());
+                }
+                self.state.is_dragging = false;
             } else if i.pointer.any_click() {
-                let closest_node = self.find_closest_node(self.state.mouse_pos);
-                if let Some(node) = closest_node {
-                    self.select_node(node);
+                if self.edit_ctx.is_editing {
+                    if !controls_rect.contains(self.state.mouse_pos) {
+                        self.handle_edit_click(self.state.mouse_pos);
+                    }
+                } else if self.state.is_placing_incidents {
+                    if !controls_rect.contains(self.state.mouse_pos) {
+                        self.toggle_incident(self.state.mouse_pos);
+                    }
+                } else if self.analysis_ctx.is_picking_terminals
+                    && self.analysis_ctx.tree_kind == TreeKind::Steiner
+                {
+                    if let Some(node) = (!controls_rect.contains(self.state.mouse_pos))
+                        .then(|| self.find_closest_node(self.state.mouse_pos))
+                        .flatten()
+                    {
+                        self.analysis_ctx.toggle_terminal(&node.id);
+                    }
+                } else {
+                    let closest_node = self.find_closest_node_where(self.state.mouse_pos, |node| {
+                        self.is_pickable(node)
+                    });
+                    if let Some(node) = closest_node {
+                        self.select_node(node);
+                    }
                 }
             }
 
-            if i.raw_scroll_delta.y != 0.0 {
-                let zoom_before = self.draw_ctx.zoom;
-                self.draw_ctx.zoom += i.raw_scroll_delta.y / 10.0;
+            // pinch gestures and ctrl + scroll
+            let zoom_delta = i.zoom_delta();
+            if zoom_delta != 1.0 {
+                self.draw_ctx.zoom_at(self.state.mouse_pos, zoom_delta);
+            }
+
+            let scroll = i.smooth_scroll_delta.y;
+            if scroll != 0.0 {
+                self.draw_ctx
+                    .zoom_at(self.state.mouse_pos, (scroll * ZOOM_SCROLL_SPEED).exp());
+            }
+
+            if keyboard_free {
+                let center = self.draw_ctx.viewport().center();
+                let pan_step = self.draw_ctx.viewport().size() * KEYBOARD_PAN_STEP;
 
-                if self.draw_ctx.zoom < 1.0 {
-                    self.draw_ctx.zoom = 1.0;
+                if i.key_pressed(Key::Plus) || i.key_pressed(Key::Equals) {
+                    self.draw_ctx.animate_zoom_at(center, KEYBOARD_ZOOM_STEP);
+                }
+                if i.key_pressed(Key::Minus) {
+                    self.draw_ctx
+                        .animate_zoom_at(center, 1.0 / KEYBOARD_ZOOM_STEP);
+                }
+                if i.key_pressed(Key::Num0) || i.key_pressed(Key::Home) {
+                    self.draw_ctx.animate_fit_to_bounds();
+                }
+                if i.key_pressed(Key::ArrowLeft) {
+                    self.draw_ctx.animate_pan_by(Vec2::new(pan_step.x, 0.0));
+                }
+                if i.key_pressed(Key::ArrowRight) {
+                    self.draw_ctx.animate_pan_by(Vec2::new(-pan_step.x, 0.0));
+                }
+                if i.key_pressed(Key::ArrowUp) {
+                    self.draw_ctx.animate_pan_by(Vec2::new(0.0, pan_step.y));
+                }
+                if i.key_pressed(Key::ArrowDown) {
+                    self.draw_ctx.animate_pan_by(Vec2::new(0.0, -pan_step.y));
                 }
 
-                let new_pan = self.draw_ctx.pan * (self.draw_ctx.zoom / zoom_before);
+                if self.edit_ctx.is_editing {
+                    self.handle_edit_keys(i);
+                }
+            }
 
-                let screen_center = i.screen_rect().center();
+            // a long pause between frames must not make the animation jump
+            self.draw_ctx.step(i.stable_dt.min(0.1));
+        });
 
-                let delta = (screen_center - self.state.mouse_pos) / 10.;
+        if self.draw_ctx.is_animating() {
+            ctx.request_repaint();
+        }
+    }
 
-                self.draw_ctx.pan += (new_pan - self.draw_ctx.pan + delta) / self.draw_ctx.zoom;
-                self.draw_ctx.pan = self.draw_ctx.pan.clamp(MIN_PAN, MAX_PAN);
+    fn render_basemap_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.basemap_path_input)
+                    .hint_text("tiles/ or map.mbtiles")
+                    .desired_width(160.),
+            );
+            if ui
+                .button("Load tiles")
+                .on_hover_text("A z/x/y.png directory or an MBTiles file")
+                .clicked()
+            {
+                let path = PathBuf::from(self.state.basemap_path_input.trim());
+                match TileSource::open(&path) {
+                    Ok(source) => {
+                        self.basemap_ctx.set_source(source);
+                        self.basemap_ctx.is_visible = true;
+                    }
+                    Err(message) => self.state.show_toast(message, ToastKind::Error),
+                }
             }
         });
+        if let Some(source) = self.basemap_ctx.source() {
+            let (min_zoom, max_zoom) = source.zoom_range();
+            ui.label(format!(
+                "Tiles: {} (zoom {}-{})",
+                source.path().display(),
+                min_zoom,
+                max_zoom
+            ));
+            ui.label("Show basemap");
+            ui.add(toggle(&mut self.basemap_ctx.is_visible));
+            ui.add(egui::Slider::new(&mut self.basemap_ctx.opacity, 0.0..=1.0).text("Opacity"));
+        }
+    }
+
+    // widgets generated from the parameters the selected algorithm declares
+    fn render_algorithm_parameters(&mut self, ui: &mut egui::Ui) {
+        let parameters = self.algorithm_ctx.algorithm().parameters();
+        if parameters.is_empty() {
+            return;
+        }
+        let values = self.algorithm_ctx.parameter_values();
+        for parameter in parameters {
+            let mut value = values.get(parameter.key).unwrap();
+            if parameter_widget(ui, parameter, &mut value).changed() {
+                self.algorithm_ctx.parameters.set(parameter.key, value);
+            }
+        }
+        if ui.button("Reset parameters").clicked() {
+            self.algorithm_ctx.reset_parameters();
+        }
     }
 
     fn try_initialize_data(&mut self, ctx: &egui::Context) {
@@ -321,9 +847,42 @@ impl Map {
         }
     }
 
-    fn check_for_data_updates(&mut self) {
+    fn check_for_data_updates(&mut self, ctx: &egui::Context) {
+        while let Ok(progress) = self.data_ctx.rx_progress.try_recv() {
+            self.data_ctx.load_progress = Some(progress);
+        }
+        while let Ok(batch) = self.data_ctx.rx_node_batches.try_recv() {
+            // partial nodes are only drawn until the complete list arrives
+            if self.data_ctx.nodes_loading {
+                let is_first_batch = self.data_ctx.nodes.is_empty();
+                self.data_ctx.nodes.extend(batch);
+                self.draw_ctx.set_bounds(&self.data_ctx.nodes);
+                if is_first_batch && self.pending_session.is_none() {
+                    self.draw_ctx.fit_to_bounds();
+                }
+            }
+        }
+        if let Ok(message) = self.data_ctx.rx_load_error.try_recv() {
+            // the nodes of the batches sent before the error are dropped as well
+            self.data_ctx.nodes.clear();
+            self.data_ctx.nodes_loading = false;
+            self.data_ctx.edges_loading = false;
+            self.data_ctx.load_progress = None;
+            self.draw_ctx.set_bounds(&self.data_ctx.nodes);
+            self.pending_session = None;
+            self.state.show_toast(
+                format!("Unable to load {}: {}", self.data_ctx.data_name(), message),
+                ToastKind::Error,
+            );
+        }
         if let Ok(nodes) = self.data_ctx.rx_nodes.try_recv() {
             self.data_ctx.nodes = nodes;
+            self.draw_ctx.set_bounds(&self.data_ctx.nodes);
+            if self.pending_session.is_some() {
+                self.restore_session_view();
+            } else {
+                self.draw_ctx.fit_to_bounds();
+            }
             debug!("Nodes received");
             self.state.show_toast(
                 format!("Nodes received: {}", self.data_ctx.nodes.len()),
@@ -339,14 +898,58 @@ impl Map {
                 ToastKind::Info,
             );
             self.data_ctx.edges_loading = false;
+
+            // profiles stored alongside the map, e.g. map.xml and map.csv
+            if let DataSource::File(path) = self.data_ctx.data_source() {
+                let traffic_path = path.with_extension("csv");
+                if traffic_path.is_file() {
+                    self.load_traffic(&traffic_path);
+                }
+            }
+        }
+        self.analysis_ctx.receive_centrality();
+        self.analysis_ctx.receive_heuristic_report();
+        if let Some(message) = self.analysis_ctx.receive_tree() {
+            self.state.show_toast(message, ToastKind::Error);
+        }
+        match self.analysis_ctx.receive_distance_matrix() {
+            Some(Ok(message)) => self.state.show_toast(message, ToastKind::Success),
+            Some(Err(message)) => self.state.show_toast(message, ToastKind::Error),
+            None => {}
+        }
+        if let Ok((generation, statistics)) = self.data_ctx.rx_statistics.try_recv() {
+            if generation == self.data_ctx.statistics_generation() {
+                self.data_ctx.statistics = Some(statistics);
+                self.data_ctx.statistics_loading = false;
+            }
+        }
+        if let Ok(report) = self.data_ctx.rx_validation.try_recv() {
+            if report.issue_count() > 0 {
+                self.state.show_toast(
+                    format!(
+                        "{} issues found in the map, see Validation",
+                        report.issue_count()
+                    ),
+                    ToastKind::Warning,
+                );
+            }
+            self.data_ctx.validation = Some(report);
+        }
+        if let Ok(restrictions) = self.data_ctx.rx_restrictions.try_recv() {
+            self.algorithm_ctx.set_turn_restrictions(&restrictions);
+            self.data_ctx.restrictions = restrictions;
+            debug!("Turn restrictions received");
         }
         if let Ok(neighbors) = self.data_ctx.rx_neighbors.try_recv() {
-            self.data_ctx.neighbors = neighbors;
+            self.data_ctx.neighbors = Arc::new(neighbors);
+            self.algorithm_ctx.set_graph(&self.data_ctx.neighbors);
             debug!("Neighbors received");
             self.state.show_toast(
                 format!("Neighbors received: {}", self.data_ctx.neighbors.len()),
                 ToastKind::Info,
             );
+            // the path is computed right below, now that the neighbors are known
+            self.restore_session_nodes();
         }
         if self.state.is_start_and_end_set()
             && self.algorithm_ctx.is_new_args(
@@ -355,24 +958,48 @@ impl Map {
             )
         {
             debug!("Start and end nodes are set");
-            if self.algorithm_ctx.is_new_args(
+            self.state.show_toast(
+                format!(
+                    "Starting A* run from {} to {}",
+                    self.state.start_node.as_ref().unwrap().id,
+                    self.state.end_node.as_ref().unwrap().id
+                ),
+                ToastKind::Info,
+            );
+            self.algorithm_ctx.request_path(
                 self.state.start_node.as_ref().unwrap(),
                 self.state.end_node.as_ref().unwrap(),
-            ) {
+                &self.data_ctx.neighbors,
+                ctx,
+            );
+        }
+        if let Some(profile) = self.algorithm_ctx.receive_day_profile() {
+            if let Some((_, _, day_profile)) = &mut self.state.day_profile {
+                *day_profile = profile;
+            }
+        }
+        if self.algorithm_ctx.receive_path() && self.state.is_start_and_end_set() {
+            if !self.algorithm_ctx.negative_cycle().is_empty() {
+                let cycle = self
+                    .algorithm_ctx
+                    .negative_cycle()
+                    .iter()
+                    .map(|edge| edge.from.id.as_str())
+                    .collect::<Vec<&str>>();
+                self.state.show_toast(
+                    format!("Negative cycle through {}", cycle.join(", ")),
+                    ToastKind::Error,
+                );
+            } else if self.algorithm_ctx.path().is_empty() {
                 self.state.show_toast(
                     format!(
-                        "Starting A* run from {} to {}",
+                        "No route from {} to {}",
                         self.state.start_node.as_ref().unwrap().id,
                         self.state.end_node.as_ref().unwrap().id
                     ),
-                    ToastKind::Info,
+                    ToastKind::Warning,
                 );
             }
-            self.algorithm_ctx.compute_path(
-                self.state.start_node.as_ref().unwrap(),
-                self.state.end_node.as_ref().unwrap(),
-                &self.data_ctx.neighbors,
-            );
             self.state.is_drawing_path = true;
         }
     }
@@ -385,6 +1012,10 @@ impl Map {
 
     fn render_ui(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui: &mut egui::Ui| {
+            self.draw_ctx.set_viewport(ui.max_rect());
+
+            // Draw basemap
+            self.render_basemap(ui);
             // Draw edges
             self.render_edges(ui);
             // Draw nodes
@@ -402,6 +1033,13 @@ impl Map {
     }
 
     fn render_selected(&self, ui: &mut egui::Ui) {
+        if let Some(incident_edges) = &self.state.incident_edges {
+            incident_edges.iter().for_each(|(edge, size, color)| {
+                let (from, to) = self.draw_ctx.calc_edge_coords(edge);
+                ui.painter().line_segment([from, to], (*size, *color));
+            });
+        }
+
         if let Some(passed_edges) = &self.state.passed_edges {
             passed_edges.iter().for_each(|(edge, size, color)| {
                 let (from, to) = self.draw_ctx.calc_edge_coords(edge);
@@ -423,6 +1061,32 @@ impl Map {
                     .circle_filled(position_on_screen, *size, *color);
             });
         }
+
+        self.render_tree(ui);
+
+        if let Some(report) = self
+            .analysis_ctx
+            .heuristic_report()
+            .filter(|_| self.analysis_ctx.show_heuristic_violations)
+        {
+            report.inconsistent_edges.keys().for_each(|edge| {
+                let (from, to) = self.draw_ctx.calc_edge_coords(edge);
+                ui.painter().line_segment(
+                    [from, to],
+                    (HEURISTIC_VIOLATION_WIDTH, HEURISTIC_VIOLATION_COLOR),
+                );
+            });
+        }
+
+        self.algorithm_ctx.negative_cycle().iter().for_each(|edge| {
+            let (from, to) = self.draw_ctx.calc_edge_coords(edge);
+            ui.painter()
+                .arrow(from, to - from, (ROUTE_EDGE_WIDTH, NEGATIVE_CYCLE_COLOR));
+        });
+
+        if self.edit_ctx.is_editing {
+            self.render_edit_highlights(ui);
+        }
     }
 }
 
@@ -431,10 +1095,21 @@ impl eframe::App for Map {
         self.update_fps(ctx, frame);
 
         self.try_initialize_data(ctx);
-        self.check_for_data_updates();
+        self.check_for_data_updates(ctx);
 
         self.render_ui(ctx);
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SESSION_KEY, &self.capture_session());
+        // an empty list replaces the runs kept by an earlier session
+        let runs = if self.algorithm_ctx.persist_runs {
+            self.algorithm_ctx.persisted_runs()
+        } else {
+            PersistedRuns::default()
+        };
+        eframe::set_value(storage, RUNS_KEY, &runs);
+    }
 }
 
 struct UIState {
@@ -444,10 +1119,39 @@ struct UIState {
     selected_nodes: Option<Vec<(Node, f32, Color32)>>,
     selected_edges: Option<Vec<(Edge, f32, Color32)>>,
     passed_edges: Option<Vec<(Edge, f32, Color32)>>,
+    incident_edges: Option<Vec<(Edge, f32, Color32)>>,
+    highlight_components: bool,
+    pick_in_largest_component: bool,
     is_drawing_path: bool,
     frame_history: FrameHistory,
     mouse_pos: Pos2,
+    is_dragging: bool,
     toasts: Toasts,
+    map_path_input: String,
+    basemap_path_input: String,
+    route_format: RouteFormat,
+    export_explored_edges: bool,
+    export_path_input: String,
+    view_format: ViewFormat,
+    export_full_map: bool,
+    export_width: u32,
+    view_path_input: String,
+    session_path_input: String,
+    map_save_path_input: String,
+    is_placing_incidents: bool,
+    incident_closes: bool,
+    incident_factor: f32,
+    scenario_path_input: String,
+    traffic_path_input: String,
+    matrix_ids_input: String,
+    matrix_nodes_path_input: String,
+    matrix_path_input: String,
+    matrix_format: MatrixFormat,
+    // (start id, end id, profile) of the last analysed day
+    day_profile: Option<(String, String, DayProfile)>,
+    node_query: String,
+    // (node, what matched the query if not its id)
+    node_matches: Vec<(Node, Option<String>)>,
 }
 
 impl UIState {
@@ -476,38 +1180,167 @@ impl Default for UIState {
             selected_nodes: None,
             selected_edges: None,
             passed_edges: None,
+            incident_edges: None,
+            highlight_components: false,
+            pick_in_largest_component: false,
             is_drawing_path: false,
             frame_history: FrameHistory::default(),
             mouse_pos: Pos2::new(0.0, 0.0),
+            is_dragging: false,
             toasts: Toasts::new()
                 .anchor(Align2::RIGHT_TOP, (-10.0, 10.0))
                 .direction(egui::Direction::TopDown),
+            map_path_input: String::new(),
+            basemap_path_input: String::new(),
+            route_format: RouteFormat::GeoJson,
+            export_explored_edges: false,
+            export_path_input: String::new(),
+            view_format: ViewFormat::Png,
+            export_full_map: false,
+            export_width: 4096,
+            view_path_input: String::new(),
+            session_path_input: String::new(),
+            map_save_path_input: String::new(),
+            is_placing_incidents: false,
+            incident_closes: true,
+            incident_factor: 2.0,
+            scenario_path_input: String::new(),
+            traffic_path_input: String::new(),
+            matrix_ids_input: String::new(),
+            matrix_nodes_path_input: String::new(),
+            matrix_path_input: String::new(),
+            matrix_format: MatrixFormat::Csv,
+            day_profile: None,
+            node_query: String::new(),
+            node_matches: Vec::new(),
         }
     }
 }
 
-fn send_parse_request(
-    tx_nodes: Sender<Vec<Node>>,
-    tx_edges: Sender<Vec<Edge>>,
-    tx_neighbors: Sender<HashMap<Node, Vec<Edge>>>,
-    data_buffer: &'static [u8],
-    ctx: egui::Context,
-) {
-    tokio::spawn(async move {
+fn route_color(route: usize) -> Color32 {
+    const PALETTE: [Color32; 6] = [
+        Color32::from_rgb(31, 119, 180),
+        Color32::from_rgb(255, 127, 14),
+        Color32::from_rgb(44, 160, 44),
+        Color32::from_rgb(214, 39, 40),
+        Color32::from_rgb(148, 103, 189),
+        Color32::from_rgb(140, 86, 75),
+    ];
+    PALETTE[route % PALETTE.len()]
+}
+
+// blue for the least central arcs to red for the most central ones, `heat` in [0, 1]
+fn heat_color(heat: f32) -> Color32 {
+    const STOPS: [Color32; 5] = [
+        Color32::from_rgb(44, 123, 182),
+        Color32::from_rgb(171, 217, 233),
+        Color32::from_rgb(255, 255, 191),
+        Color32::from_rgb(253, 174, 97),
+        Color32::from_rgb(215, 25, 28),
+    ];
+    let position = heat.clamp(0., 1.) * (STOPS.len() - 1) as f32;
+    let stop = (position as usize).min(STOPS.len() - 2);
+    let fraction = position - stop as f32;
+    let [r, g, b, _] = STOPS[stop].to_array();
+    let [next_r, next_g, next_b, _] = STOPS[stop + 1].to_array();
+    let lerp = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * fraction) as u8;
+
+    Color32::from_rgb(lerp(r, next_r), lerp(g, next_g), lerp(b, next_b))
+}
+
+fn incident_color(incident: Incident) -> Color32 {
+    match incident {
+        Incident::Closed => CLOSED_EDGE_COLOR,
+        Incident::Slowed { .. } => SLOWED_EDGE_COLOR,
+    }
+}
+
+fn edge_color(dark_mode: bool) -> Color32 {
+    if dark_mode {
+        Color32::DARK_GRAY
+    } else {
+        Color32::GRAY
+    }
+}
+
+fn send_parse_request(data_ctx: &DataContext, ctx: egui::Context) {
+    let tx_nodes = data_ctx.tx_nodes.clone();
+    let tx_edges = data_ctx.tx_edges.clone();
+    let tx_neighbors = data_ctx.tx_neighbors.clone();
+    let tx_restrictions = data_ctx.tx_restrictions.clone();
+    let tx_validation = data_ctx.tx_validation.clone();
+    let tx_node_batches = data_ctx.tx_node_batches.clone();
+    let tx_progress = data_ctx.tx_progress.clone();
+    let tx_load_error = data_ctx.tx_load_error.clone();
+    let data_source = data_ctx.data_source().clone();
+    let mut translator = data_ctx.translator().clone();
+
+    tokio::task::spawn_blocking(move || {
         debug!("Parsing map...");
-        let (nodes, edges, neighbors) = parse_xml(data_buffer);
+        let parsed = match data_source {
+            DataSource::Embedded(data_buffer) => Ok(parse_xml(data_buffer, &mut translator)),
+            DataSource::File(path) => File::open(&path)
+                .map_err(|e| format!("Unable to open {}: {}", path.display(), e))
+                .and_then(|file| {
+                    let total_bytes = file.metadata().map(|m| m.len()).unwrap_or_default();
+
+                    parse_xml_streaming(
+                        BufReader::new(file),
+                        total_bytes,
+                        &mut translator,
+                        // the receivers are gone once another load has started
+                        |event| {
+                            match event {
+                                ParseEvent::NodeBatch(batch) => {
+                                    let _ = tx_node_batches.send(batch);
+                                }
+                                ParseEvent::Progress(progress) => {
+                                    let _ = tx_progress.send(progress);
+                                }
+                            }
+                            ctx.request_repaint();
+                        },
+                    )
+                }),
+        };
+        let (nodes, edges, neighbors, restrictions, dangling_arcs, invalid_attributes) =
+            match parsed {
+                Ok(parsed) => parsed,
+                Err(message) => {
+                    debug!("Map not parsed: {}", message);
+                    let _ = tx_load_error.send(message);
+                    ctx.request_repaint();
+                    return;
+                }
+            };
         debug!("Map parsed");
 
+        debug!("Validating map...");
+        let report = ValidationReport::new(&nodes, &edges, dangling_arcs, invalid_attributes);
+        debug!("Map validated");
+
         debug!("Sending nodes...");
-        tx_nodes.send(nodes).unwrap();
+        if tx_nodes.send(nodes).is_err() {
+            debug!("Map dropped, another one is being loaded");
+            return;
+        }
         debug!("Nodes sent");
 
+        // the receivers are gone if another load started in the meantime
         debug!("Sending edges...");
-        tx_edges.send(edges).unwrap();
+        let _ = tx_edges.send(edges);
         debug!("Edges sent");
 
+        debug!("Sending validation report...");
+        let _ = tx_validation.send(report);
+        debug!("Validation report sent");
+
+        debug!("Sending turn restrictions...");
+        let _ = tx_restrictions.send(restrictions);
+        debug!("Turn restrictions sent");
+
         debug!("Sending neighbors...");
-        tx_neighbors.send(neighbors).unwrap();
+        let _ = tx_neighbors.send(neighbors);
         debug!("Neighbors sent");
 
         debug!("Map loaded");
@@ -0,0 +1,121 @@
+use egui::{Pos2, Rect, Vec2};
+use enum_iterator::all;
+
+use super::{heat_color, Map};
+use crate::{contexts::CentralityOverlay, models::Edge};
+
+// centrality: computing it, the heatmap overlay and the most critical arcs
+impl Map {
+    pub(super) fn render_centrality_controls(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.checkbox(&mut self.analysis_ctx.use_sampling, "Sample the sources")
+            .on_hover_text("Approximates the betweenness from random nodes, much faster");
+        if self.analysis_ctx.use_sampling {
+            ui.add(
+                egui::Slider::new(&mut self.analysis_ctx.samples, 10..=2000)
+                    .logarithmic(true)
+                    .text("Sources"),
+            );
+        }
+        ui.horizontal(|ui| {
+            let can_compute = self.data_ctx.has_data() && !self.analysis_ctx.is_computing();
+            if ui
+                .add_enabled(can_compute, egui::Button::new("Compute centrality"))
+                .clicked()
+            {
+                self.analysis_ctx.request_centrality(
+                    &self.data_ctx.nodes,
+                    &self.data_ctx.edges,
+                    ctx,
+                );
+            }
+            if self.analysis_ctx.is_computing() {
+                ui.spinner();
+            }
+        });
+
+        let Some(centrality) = self.analysis_ctx.centrality() else {
+            return;
+        };
+        ui.label(format!(
+            "Computed from {} of {} nodes",
+            centrality.sources,
+            self.data_ctx.nodes.len()
+        ));
+        let max = match self.analysis_ctx.overlay {
+            CentralityOverlay::Off => None,
+            CentralityOverlay::Betweenness => Some(centrality.max_betweenness),
+            CentralityOverlay::Closeness => Some(centrality.max_closeness),
+        };
+
+        egui::ComboBox::from_label("Overlay")
+            .selected_text(self.analysis_ctx.overlay.to_string())
+            .show_ui(ui, |ui| {
+                for overlay in all::<CentralityOverlay>() {
+                    ui.selectable_value(
+                        &mut self.analysis_ctx.overlay,
+                        overlay,
+                        overlay.to_string(),
+                    );
+                }
+            });
+        if let Some(max) = max {
+            render_heat_legend(ui, max);
+        }
+        ui.add(egui::Slider::new(&mut self.analysis_ctx.top_edges, 1..=50).text("Critical edges"));
+
+        let centrality = self.analysis_ctx.centrality().unwrap();
+        egui::ScrollArea::vertical()
+            .id_source("critical_edges")
+            .max_height(120.)
+            .show(ui, |ui| {
+                centrality
+                    .ranked_edges
+                    .iter()
+                    .take(self.analysis_ctx.top_edges)
+                    .enumerate()
+                    .for_each(|(rank, (edge, betweenness))| {
+                        ui.label(format!(
+                            "{}. {} -> {}: {:.0}",
+                            rank + 1,
+                            edge.from.id,
+                            edge.to.id,
+                            betweenness
+                        ));
+                    });
+            });
+    }
+
+    // relative centrality of the arc when the overlay is shown, in [0, 1]
+    pub(super) fn centrality_heat(&self, edge: &Edge) -> Option<f32> {
+        let centrality = self.analysis_ctx.centrality()?;
+        match self.analysis_ctx.overlay {
+            CentralityOverlay::Off => None,
+            CentralityOverlay::Betweenness => centrality.relative_betweenness(edge),
+            // arcs take the mean closeness of their ends
+            CentralityOverlay::Closeness => Some(
+                (centrality.relative_closeness(&edge.from)?
+                    + centrality.relative_closeness(&edge.to)?)
+                    / 2.,
+            ),
+        }
+    }
+}
+
+fn render_heat_legend(ui: &mut egui::Ui, max: f32) {
+    let (response, painter) = ui.allocate_painter(Vec2::new(240., 12.), egui::Sense::hover());
+    let rect = response.rect;
+    let steps = 48;
+    (0..steps).for_each(|step| {
+        let width = rect.width() / steps as f32;
+        let bar = Rect::from_min_size(
+            Pos2::new(rect.left() + step as f32 * width, rect.top()),
+            Vec2::new(width + 0.5, rect.height()),
+        );
+        painter.rect_filled(bar, 0., heat_color(step as f32 / (steps - 1) as f32));
+    });
+    ui.horizontal(|ui| {
+        ui.label("0");
+        ui.add_space(200.);
+        ui.label(format!("{:.3}", max));
+    });
+}
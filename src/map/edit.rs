@@ -0,0 +1,436 @@
+use egui::{Key, Pos2, Rect};
+use egui_toast::ToastKind;
+use enum_iterator::all;
+use std::path::PathBuf;
+
+use super::Map;
+use crate::{
+    components::toggle,
+    contexts::{EditOp, EditTool, Selection},
+    export::write_map_xml,
+    models::{Edge, Node},
+    utils::{
+        constants::{EDIT_HIGHLIGHT_COLOR, EDIT_HIGHLIGHT_WIDTH},
+        haversine_distance,
+    },
+};
+
+// the edit mode: picking, dragging and changing nodes and arcs, the history and saving
+impl Map {
+    // dragging from a node moves it instead of the map, returns whether a node is dragged
+    pub(super) fn drag_node(&mut self, origin: Pos2, controls_rect: Rect) -> bool {
+        if self.edit_ctx.is_editing
+            && self.edit_ctx.tool == EditTool::Select
+            && !self.state.is_dragging
+        {
+            if let Some(node) = (!controls_rect.contains(origin))
+                .then(|| self.find_closest_node(origin))
+                .flatten()
+            {
+                self.edit_ctx.dragged = Some((node.id.clone(), (node.longitude, node.latitude)));
+            }
+        }
+
+        let Some((id, _)) = self.edit_ctx.dragged.clone() else {
+            return false;
+        };
+        let (longitude, latitude) = self
+            .data_ctx
+            .translator()
+            .unproject(self.draw_ctx.screen_to_canvas(self.state.mouse_pos));
+        self.data_ctx.move_node(&id, longitude, latitude, false);
+        true
+    }
+
+    pub(super) fn handle_edit_keys(&mut self, i: &egui::InputState) {
+        if i.modifiers.command && i.key_pressed(Key::Z) {
+            if i.modifiers.shift {
+                self.redo_edit();
+            } else {
+                self.undo_edit();
+            }
+        }
+        if i.modifiers.command && i.key_pressed(Key::Y) {
+            self.redo_edit();
+        }
+        if i.key_pressed(Key::Delete) || i.key_pressed(Key::Backspace) {
+            self.delete_selection();
+        }
+        if i.key_pressed(Key::Escape) {
+            self.edit_ctx.arc_start = None;
+            self.edit_ctx.selection = None;
+        }
+    }
+
+    pub(super) fn render_edit_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Edit mode");
+        if ui.add(toggle(&mut self.edit_ctx.is_editing)).changed() {
+            self.edit_ctx.arc_start = None;
+            self.edit_ctx.dragged = None;
+        }
+        if !self.edit_ctx.is_editing {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            for tool in all::<EditTool>() {
+                if ui
+                    .selectable_value(&mut self.edit_ctx.tool, tool, tool.to_string())
+                    .clicked()
+                {
+                    self.edit_ctx.arc_start = None;
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.edit_ctx.can_undo(), egui::Button::new("Undo"))
+                .on_hover_text("Ctrl+Z")
+                .clicked()
+            {
+                self.undo_edit();
+            }
+            if ui
+                .add_enabled(self.edit_ctx.can_redo(), egui::Button::new("Redo"))
+                .on_hover_text("Ctrl+Shift+Z or Ctrl+Y")
+                .clicked()
+            {
+                self.redo_edit();
+            }
+        });
+        if let Some(id) = &self.edit_ctx.arc_start {
+            ui.label(format!("Arc from {}, click the destination node", id));
+        }
+
+        self.render_inspector(ui);
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.map_save_path_input)
+                    .hint_text("map.xml")
+                    .desired_width(160.),
+            );
+            if ui.button("Save map").clicked() {
+                self.save_map();
+            }
+        });
+        if self.edit_ctx.is_dirty() {
+            ui.label("Unsaved changes");
+        }
+    }
+
+    fn render_inspector(&mut self, ui: &mut egui::Ui) {
+        match self.edit_ctx.selection.clone() {
+            Some(Selection::Node(id)) => {
+                let Some(node) = self.data_ctx.find_node(&id) else {
+                    return;
+                };
+                let arcs = self
+                    .data_ctx
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.from.id == id || edge.to.id == id)
+                    .count();
+                ui.label(format!("Node {}", id));
+                ui.label(format!("{:.5}, {:.5}", node.latitude, node.longitude));
+                ui.label(format!("{} arc(s)", arcs));
+                if ui.button("Delete node").clicked() {
+                    self.delete_selection();
+                }
+            }
+            Some(Selection::Edge(from, to)) => {
+                let Some(edge) = self.data_ctx.find_edge(&from, &to).cloned() else {
+                    return;
+                };
+                let has_opposite = self.data_ctx.find_edge(&to, &from).is_some();
+                ui.label(format!("Arc {} -> {}", from, to));
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.edit_ctx.length_input)
+                            .clamp_range(0.0..=f32::MAX)
+                            .suffix(" m"),
+                    );
+                    if ui.button("Set length").clicked() {
+                        let mut after = edge.clone();
+                        after.length = self.edit_ctx.length_input;
+                        self.apply_edit(EditOp::ReplaceEdge {
+                            before: edge.clone(),
+                            after,
+                        });
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!has_opposite, egui::Button::new("Reverse"))
+                        .clicked()
+                    {
+                        let after = reversed(&edge);
+                        self.edit_ctx.selection =
+                            Some(Selection::Edge(after.from.id.clone(), after.to.id.clone()));
+                        self.apply_edit(EditOp::ReplaceEdge {
+                            before: edge.clone(),
+                            after,
+                        });
+                    }
+                    if ui
+                        .add_enabled(!has_opposite, egui::Button::new("Make two-way"))
+                        .clicked()
+                    {
+                        self.apply_edit(EditOp::AddEdge(reversed(&edge)));
+                    }
+                    if ui.button("Delete arc").clicked() {
+                        self.delete_selection();
+                    }
+                });
+            }
+            None => {
+                ui.label("Click a node or an arc to inspect it");
+            }
+        }
+    }
+
+    pub(super) fn handle_edit_click(&mut self, pos: Pos2) {
+        let closest_node = self.find_closest_node(pos);
+
+        match self.edit_ctx.tool {
+            EditTool::Select => {
+                self.edit_ctx.selection = if let Some(node) = closest_node {
+                    Some(Selection::Node(node.id))
+                } else if let Some(edge) = self.find_closest_edge(pos) {
+                    self.edit_ctx.length_input = edge.length;
+                    Some(Selection::Edge(edge.from.id, edge.to.id))
+                } else {
+                    None
+                };
+            }
+            EditTool::AddNode => {
+                let (longitude, latitude) = self
+                    .data_ctx
+                    .translator()
+                    .unproject(self.draw_ctx.screen_to_canvas(pos));
+                let position = self
+                    .data_ctx
+                    .translator()
+                    .translate_coordinates(longitude, latitude);
+                let node = Node::new(self.next_node_id(), position, longitude, latitude);
+
+                self.edit_ctx.selection = Some(Selection::Node(node.id.clone()));
+                self.apply_edit(EditOp::AddNode(node, vec![]));
+            }
+            EditTool::AddArc => {
+                let Some(node) = closest_node else {
+                    self.edit_ctx.arc_start = None;
+                    return;
+                };
+                let Some(start_id) = self.edit_ctx.arc_start.take() else {
+                    self.edit_ctx.arc_start = Some(node.id);
+                    return;
+                };
+                if start_id == node.id {
+                    return;
+                }
+                if self.data_ctx.find_edge(&start_id, &node.id).is_some() {
+                    self.state.show_toast(
+                        format!("There already is an arc from {} to {}", start_id, node.id),
+                        ToastKind::Warning,
+                    );
+                    return;
+                }
+
+                let from = self.data_ctx.find_node(&start_id).unwrap().clone();
+                let length = haversine_distance(
+                    (from.longitude, from.latitude),
+                    (node.longitude, node.latitude),
+                )
+                .round() as f32;
+                let edge = Edge::new(from, node, length);
+
+                self.edit_ctx.length_input = length;
+                self.edit_ctx.selection =
+                    Some(Selection::Edge(edge.from.id.clone(), edge.to.id.clone()));
+                self.apply_edit(EditOp::AddEdge(edge));
+            }
+            EditTool::Delete => {
+                self.edit_ctx.selection = if let Some(node) = closest_node {
+                    Some(Selection::Node(node.id))
+                } else {
+                    self.find_closest_edge(pos)
+                        .map(|edge| Selection::Edge(edge.from.id, edge.to.id))
+                };
+                self.delete_selection();
+            }
+        }
+    }
+
+    fn delete_selection(&mut self) {
+        let op = match self.edit_ctx.selection.take() {
+            Some(Selection::Node(id)) => {
+                let Some(node) = self.data_ctx.find_node(&id).cloned() else {
+                    return;
+                };
+                let edges = self
+                    .data_ctx
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.from.id == id || edge.to.id == id)
+                    .cloned()
+                    .collect();
+                EditOp::RemoveNode(node, edges)
+            }
+            Some(Selection::Edge(from, to)) => {
+                let Some(edge) = self.data_ctx.find_edge(&from, &to).cloned() else {
+                    return;
+                };
+                EditOp::RemoveEdge(edge)
+            }
+            None => return,
+        };
+        self.apply_edit(op);
+    }
+
+    pub(super) fn drop_node(&mut self, id: String, from: (f64, f64)) {
+        let Some(node) = self.data_ctx.find_node(&id) else {
+            return;
+        };
+        let to = (node.longitude, node.latitude);
+
+        self.data_ctx.move_node(&id, to.0, to.1, true);
+        self.edit_ctx.record(EditOp::MoveNode { id, from, to });
+        self.after_edit();
+    }
+
+    fn apply_edit(&mut self, op: EditOp) {
+        self.edit_ctx.apply(op, &mut self.data_ctx);
+        self.after_edit();
+    }
+
+    fn undo_edit(&mut self) {
+        if self.edit_ctx.undo(&mut self.data_ctx) {
+            self.after_edit();
+        }
+    }
+
+    fn redo_edit(&mut self) {
+        if self.edit_ctx.redo(&mut self.data_ctx) {
+            self.after_edit();
+        }
+    }
+
+    // the computed routes and the selected nodes may refer to what was just changed
+    fn after_edit(&mut self) {
+        self.algorithm_ctx.set_graph(&self.data_ctx.neighbors);
+        self.data_ctx.revalidate();
+        self.data_ctx.discard_statistics();
+        self.analysis_ctx.discard();
+        self.state.is_drawing_path = false;
+
+        let refresh = |node: &Option<Node>| {
+            node.as_ref()
+                .and_then(|node| self.data_ctx.find_node(&node.id))
+                .cloned()
+        };
+        self.state.start_node = refresh(&self.state.start_node);
+        self.state.end_node = refresh(&self.state.end_node);
+        self.algorithm_ctx.waypoints = self
+            .algorithm_ctx
+            .waypoints
+            .iter()
+            .filter_map(|node| self.data_ctx.find_node(&node.id).cloned())
+            .collect();
+        self.state.node_matches.clear();
+
+        let is_selection_gone = match &self.edit_ctx.selection {
+            Some(Selection::Node(id)) => self.data_ctx.find_node(id).is_none(),
+            Some(Selection::Edge(from, to)) => self.data_ctx.find_edge(from, to).is_none(),
+            None => false,
+        };
+        if is_selection_gone {
+            self.edit_ctx.selection = None;
+        }
+        if let Some(Selection::Edge(from, to)) = &self.edit_ctx.selection {
+            self.edit_ctx.length_input = self.data_ctx.find_edge(from, to).unwrap().length;
+        }
+    }
+
+    // one past the largest numeric id, the files use consecutive integers
+    fn next_node_id(&self) -> String {
+        let next = self
+            .data_ctx
+            .nodes
+            .iter()
+            .filter_map(|node| node.id.parse::<u64>().ok())
+            .max()
+            .map_or(0, |max| max + 1);
+
+        next.to_string()
+    }
+
+    fn save_map(&mut self) {
+        let mut path = PathBuf::from(self.state.map_save_path_input.trim());
+        if path.as_os_str().is_empty() {
+            path = PathBuf::from("map.xml");
+        }
+
+        match write_map_xml(
+            &self.data_ctx.data_name(),
+            &self.data_ctx.nodes,
+            &self.data_ctx.edges,
+            &self.data_ctx.restrictions,
+            &path,
+        ) {
+            Ok(()) => {
+                self.edit_ctx.mark_saved();
+                self.state.show_toast(
+                    format!("Map saved to {}", path.display()),
+                    ToastKind::Success,
+                );
+            }
+            Err(e) => self.state.show_toast(
+                format!("Unable to save map to {}: {}", path.display(), e),
+                ToastKind::Error,
+            ),
+        }
+    }
+
+    pub(super) fn render_edit_highlights(&self, ui: &mut egui::Ui) {
+        let stroke = (EDIT_HIGHLIGHT_WIDTH, EDIT_HIGHLIGHT_COLOR);
+
+        match &self.edit_ctx.selection {
+            Some(Selection::Node(id)) => {
+                if let Some(node) = self.data_ctx.find_node(id) {
+                    ui.painter()
+                        .circle_stroke(self.draw_ctx.calc_node_coords(node), 6., stroke);
+                }
+            }
+            Some(Selection::Edge(from, to)) => {
+                if let Some(edge) = self.data_ctx.find_edge(from, to) {
+                    let (from, to) = self.draw_ctx.calc_edge_coords(edge);
+                    ui.painter().arrow(from, to - from, stroke);
+                }
+            }
+            None => {}
+        }
+
+        // the arc being added follows the pointer
+        if let Some(node) = self
+            .edit_ctx
+            .arc_start
+            .as_ref()
+            .and_then(|id| self.data_ctx.find_node(id))
+        {
+            let from = self.draw_ctx.calc_node_coords(node);
+            ui.painter().circle_stroke(from, 6., stroke);
+            ui.painter()
+                .line_segment([from, self.state.mouse_pos], stroke);
+        }
+    }
+}
+
+// same arc in the opposite direction
+fn reversed(edge: &Edge) -> Edge {
+    Edge {
+        from: edge.to.clone(),
+        to: edge.from.clone(),
+        ..edge.clone()
+    }
+}
@@ -0,0 +1,79 @@
+use super::Map;
+use crate::utils::constants::MAX_LISTED_ISSUES;
+
+// the heuristic check: comparing A* with Dijkstra and checking the estimates
+impl Map {
+    pub(super) fn render_heuristic_check(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.add(
+            egui::Slider::new(&mut self.analysis_ctx.heuristic_samples, 0..=200)
+                .text("Random queries"),
+        )
+        .on_hover_text("Checked besides the current query, if any");
+        ui.horizontal(|ui| {
+            let can_check = self.data_ctx.has_data() && !self.analysis_ctx.is_checking_heuristic();
+            if ui
+                .add_enabled(can_check, egui::Button::new("Check heuristic"))
+                .clicked()
+            {
+                let current = self
+                    .state
+                    .start_node
+                    .clone()
+                    .zip(self.state.end_node.clone());
+                self.analysis_ctx.request_heuristic_check(
+                    &self.data_ctx.neighbors,
+                    &self.algorithm_ctx.cost_model,
+                    &self.algorithm_ctx.parameters,
+                    current,
+                    ctx,
+                );
+            }
+            if self.analysis_ctx.is_checking_heuristic() {
+                ui.spinner();
+            }
+        });
+
+        let Some(report) = self.analysis_ctx.heuristic_report() else {
+            return;
+        };
+        let cost_model = &self.algorithm_ctx.cost_model;
+        let suboptimal = report.suboptimal_queries().collect::<Vec<_>>();
+        ui.label(format!(
+            "A* costlier than Dijkstra on {} of {} queries",
+            suboptimal.len(),
+            report.queries.len()
+        ));
+        suboptimal.iter().take(MAX_LISTED_ISSUES).for_each(|check| {
+            let format = |cost: Option<f32>| {
+                cost.map_or("no route".to_string(), |cost| cost_model.format_cost(cost))
+            };
+            ui.label(format!(
+                "{} -> {}: {} instead of {}",
+                check.start.id,
+                check.end.id,
+                format(check.astar_cost),
+                format(check.dijkstra_cost)
+            ));
+        });
+        if report.is_admissible() {
+            ui.label("Admissible: no estimate exceeds the actual cost");
+        } else {
+            ui.label(format!(
+                "Not admissible: {} estimates exceed the actual cost, by up to {:.2} times",
+                report.overestimated_nodes(),
+                report.worst_ratio()
+            ));
+        }
+        ui.label(format!(
+            "Inconsistent on {} arcs, {} checked",
+            report.inconsistent_edges.len(),
+            report.checked_edges
+        ));
+        if !report.is_consistent() {
+            ui.checkbox(
+                &mut self.analysis_ctx.show_heuristic_violations,
+                "Show the inconsistent arcs",
+            );
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use egui::Pos2;
+use egui_toast::ToastKind;
+use std::path::PathBuf;
+
+use super::Map;
+use crate::{
+    components::toggle,
+    scenario::{Incident, Scenario, ScenarioIncident},
+};
+
+// incidents: closing or slowing down arcs on click, and saving them as scenarios
+impl Map {
+    pub(super) fn render_incident_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Place incidents on click");
+        ui.add(toggle(&mut self.state.is_placing_incidents))
+            .on_hover_text(
+                "Clicking an arc adds or removes an incident instead of selecting nodes",
+            );
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.state.incident_closes, true, "Close road");
+            ui.radio_value(&mut self.state.incident_closes, false, "Slow down");
+        });
+        if !self.state.incident_closes {
+            ui.add(
+                egui::Slider::new(&mut self.state.incident_factor, 1.0..=10.0)
+                    .step_by(0.5)
+                    .text("Cost factor"),
+            );
+        }
+
+        let incidents = self.algorithm_ctx.incidents();
+        let closed = incidents
+            .values()
+            .filter(|incident| **incident == Incident::Closed)
+            .count();
+        ui.label(format!(
+            "{} closed, {} slowed down",
+            closed,
+            incidents.len() - closed
+        ));
+        if ui.button("Clear incidents").clicked() {
+            self.algorithm_ctx.clear_incidents();
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.scenario_path_input)
+                    .hint_text("scenario.json")
+                    .desired_width(160.),
+            );
+            if ui.button("Save scenario").clicked() {
+                self.save_scenario();
+            }
+            if ui.button("Open scenario").clicked() {
+                self.open_scenario();
+            }
+        });
+    }
+
+    pub(super) fn toggle_incident(&mut self, pos: Pos2) {
+        let Some(edge) = self.find_closest_edge(pos) else {
+            return;
+        };
+
+        if self.algorithm_ctx.incident(&edge).is_some() {
+            self.algorithm_ctx.clear_incident(&edge);
+        } else {
+            let incident = if self.state.incident_closes {
+                Incident::Closed
+            } else {
+                Incident::Slowed {
+                    factor: self.state.incident_factor,
+                }
+            };
+            self.algorithm_ctx.set_incident(edge, incident);
+        }
+        // recomputed with the incidents as soon as both nodes are set
+        self.state.is_drawing_path = false;
+    }
+
+    fn scenario_path(&self) -> PathBuf {
+        let path = self.state.scenario_path_input.trim();
+        if path.is_empty() {
+            PathBuf::from("scenario.json")
+        } else {
+            PathBuf::from(path)
+        }
+    }
+
+    fn save_scenario(&mut self) {
+        let path = self.scenario_path();
+        let mut incidents = self
+            .algorithm_ctx
+            .incidents()
+            .iter()
+            .map(|(edge, incident)| ScenarioIncident {
+                from: edge.from.id.clone(),
+                to: edge.to.id.clone(),
+                incident: *incident,
+            })
+            .collect::<Vec<ScenarioIncident>>();
+        // stable output, the incidents are kept in a hash map
+        incidents.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+        let scenario = Scenario {
+            map: self.data_ctx.data_name(),
+            incidents,
+        };
+
+        match scenario.save(&path) {
+            Ok(()) => self.state.show_toast(
+                format!("Scenario saved to {}", path.display()),
+                ToastKind::Success,
+            ),
+            Err(e) => self.state.show_toast(
+                format!("Unable to save scenario to {}: {}", path.display(), e),
+                ToastKind::Error,
+            ),
+        }
+    }
+
+    fn open_scenario(&mut self) {
+        let path = self.scenario_path();
+        let scenario = match Scenario::load(&path) {
+            Ok(scenario) => scenario,
+            Err(e) => {
+                self.state.show_toast(
+                    format!("Unable to open scenario {}: {}", path.display(), e),
+                    ToastKind::Error,
+                );
+                return;
+            }
+        };
+        if scenario.map != self.data_ctx.data_name() {
+            self.state.show_toast(
+                format!("The scenario was made for {}", scenario.map),
+                ToastKind::Warning,
+            );
+        }
+
+        self.algorithm_ctx.clear_incidents();
+        let mut missing = 0;
+        scenario
+            .incidents
+            .into_iter()
+            .for_each(|scenario_incident| {
+                match self
+                    .data_ctx
+                    .find_edge(&scenario_incident.from, &scenario_incident.to)
+                {
+                    Some(edge) => self
+                        .algorithm_ctx
+                        .set_incident(edge.clone(), scenario_incident.incident),
+                    None => missing += 1,
+                }
+            });
+        self.state.is_drawing_path = false;
+
+        if missing > 0 {
+            self.state.show_toast(
+                format!("{} arc(s) of the scenario not found in the map", missing),
+                ToastKind::Warning,
+            );
+        }
+        self.state.show_toast(
+            format!("Scenario loaded from {}", path.display()),
+            ToastKind::Success,
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use egui_toast::ToastKind;
+use enum_iterator::all;
+use std::path::PathBuf;
+
+use super::Map;
+use crate::{analysis::read_node_ids, export::MatrixFormat};
+
+// the distance matrix: picking the nodes and exporting the distances between them
+impl Map {
+    pub(super) fn render_matrix_controls(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.add(
+            egui::TextEdit::multiline(&mut self.state.matrix_ids_input)
+                .hint_text("Node ids, separated by commas or new lines")
+                .desired_rows(2)
+                .desired_width(240.),
+        );
+        ui.horizontal(|ui| {
+            if ui.button("Add start and end").clicked() {
+                let mut ids = matrix_ids(&self.state.matrix_ids_input);
+                [&self.state.start_node, &self.state.end_node]
+                    .into_iter()
+                    .flatten()
+                    .for_each(|node| {
+                        if !ids.contains(&node.id) {
+                            ids.push(node.id.clone());
+                        }
+                    });
+                self.state.matrix_ids_input = ids.join(", ");
+            }
+            if ui.button("Clear").clicked() {
+                self.state.matrix_ids_input.clear();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.matrix_nodes_path_input)
+                    .hint_text("path/to/ids.csv")
+                    .desired_width(160.),
+            );
+            if ui.button("Load ids").clicked() {
+                let path = PathBuf::from(self.state.matrix_nodes_path_input.trim());
+                match read_node_ids(&path) {
+                    Ok(ids) => self.state.matrix_ids_input = ids.join(", "),
+                    Err(message) => self.state.show_toast(message, ToastKind::Error),
+                }
+            }
+        });
+
+        egui::ComboBox::from_label("Matrix format")
+            .selected_text(self.state.matrix_format.to_string())
+            .show_ui(ui, |ui| {
+                for format in all::<MatrixFormat>() {
+                    ui.selectable_value(&mut self.state.matrix_format, format, format.to_string());
+                }
+            });
+        let ids = matrix_ids(&self.state.matrix_ids_input);
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.matrix_path_input)
+                    .hint_text(format!("matrix.{}", self.state.matrix_format.extension()))
+                    .desired_width(160.),
+            );
+            let can_export = self.data_ctx.has_data()
+                && !ids.is_empty()
+                && !self.analysis_ctx.is_exporting_matrix();
+            if ui
+                .add_enabled(can_export, egui::Button::new("Export matrix"))
+                .on_hover_text("Distances in meters and hop counts between every pair of nodes")
+                .clicked()
+            {
+                let mut path = PathBuf::from(self.state.matrix_path_input.trim());
+                if path.as_os_str().is_empty() {
+                    path =
+                        PathBuf::from(format!("matrix.{}", self.state.matrix_format.extension()));
+                }
+                self.analysis_ctx.request_distance_matrix(
+                    &self.data_ctx.nodes,
+                    &self.data_ctx.neighbors,
+                    ids.clone(),
+                    self.state.matrix_format,
+                    path,
+                    ctx,
+                );
+            }
+            if self.analysis_ctx.is_exporting_matrix() {
+                ui.spinner();
+            }
+        });
+        ui.label(format!(
+            "{} nodes, {} pairs",
+            ids.len(),
+            ids.len() * ids.len()
+        ));
+    }
+}
+
+// ids typed in the distance matrix list
+fn matrix_ids(input: &str) -> Vec<String> {
+    input
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .collect()
+}
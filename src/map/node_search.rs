@@ -0,0 +1,81 @@
+use super::{Map, NodeRole};
+use crate::{node_search::search_nodes, utils::constants::MAX_NODE_MATCHES};
+
+// the node search box: finding nodes by id, name or coordinates and using them as stops
+impl Map {
+    pub(super) fn render_node_search(&mut self, ui: &mut egui::Ui) {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.state.node_query)
+                .hint_text("Node id, name or lat, lon")
+                .desired_width(200.),
+        );
+        if response.changed() {
+            self.state.node_matches = search_nodes(
+                &self.data_ctx.nodes,
+                &self.state.node_query,
+                MAX_NODE_MATCHES,
+            );
+        }
+        if !self.state.node_query.trim().is_empty() && self.state.node_matches.is_empty() {
+            ui.label("No matching node");
+        }
+
+        let mut picked = None;
+        egui::ScrollArea::vertical()
+            .id_source("node_matches")
+            .max_height(160.)
+            .show(ui, |ui| {
+                self.state.node_matches.iter().for_each(|(node, detail)| {
+                    ui.horizontal(|ui| {
+                        match detail {
+                            Some(detail) => ui.label(format!("{} ({})", node.id, detail)),
+                            None => ui.label(&node.id),
+                        };
+                        let roles = [
+                            ("Show", None),
+                            ("Start", Some(NodeRole::Start)),
+                            ("End", Some(NodeRole::End)),
+                            ("Via", Some(NodeRole::Waypoint)),
+                        ];
+                        for (label, role) in roles {
+                            if ui.small_button(label).clicked() {
+                                picked = Some((node.clone(), role));
+                            }
+                        }
+                    });
+                });
+            });
+        if let Some((node, role)) = picked {
+            self.draw_ctx.animate_center_on(node.position);
+            if let Some(role) = role {
+                self.assign_node(node, role);
+            }
+        }
+
+        if self.algorithm_ctx.waypoints.is_empty() {
+            return;
+        }
+        ui.label("Waypoints, in order:");
+        let mut removed = None;
+        self.algorithm_ctx
+            .waypoints
+            .iter()
+            .enumerate()
+            .for_each(|(index, node)| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}. {}", index + 1, node.id));
+                    if ui.small_button("Remove").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            });
+        if let Some(index) = removed {
+            self.algorithm_ctx.waypoints.remove(index);
+            self.state.is_drawing_path = false;
+        }
+        if ui.button("Clear waypoints").clicked() {
+            self.algorithm_ctx.waypoints.clear();
+            self.state.is_drawing_path = false;
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use egui_toast::ToastKind;
+use enum_iterator::all;
+use std::path::PathBuf;
+
+use super::Map;
+use crate::export::{RouteExport, RouteFormat};
+
+// route export: writing the current route as GeoJSON, GPX or KML
+impl Map {
+    pub(super) fn render_route_export(&mut self, ui: &mut egui::Ui) {
+        egui::ComboBox::from_label("Format")
+            .selected_text(self.state.route_format.to_string())
+            .show_ui(ui, |ui| {
+                for format in all::<RouteFormat>() {
+                    ui.selectable_value(&mut self.state.route_format, format, format.to_string());
+                }
+            });
+        ui.checkbox(
+            &mut self.state.export_explored_edges,
+            "Include explored edges",
+        )
+        .on_hover_text("Requires 'Show passed edges'");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.export_path_input)
+                    .hint_text("route")
+                    .desired_width(160.),
+            );
+            if ui.button("Export").clicked() {
+                self.export_route();
+            }
+        });
+    }
+
+    fn export_route(&mut self) {
+        let format = self.state.route_format;
+        let mut path = PathBuf::from(self.state.export_path_input.trim());
+        if path.as_os_str().is_empty() {
+            path = PathBuf::from("route");
+        }
+        if path.extension().is_none() {
+            path.set_extension(format.extension());
+        }
+
+        let explored_edges = if self.state.export_explored_edges {
+            Some(self.algorithm_ctx.passed_edges().iter().collect())
+        } else {
+            None
+        };
+        let map_name = self.data_ctx.data_name();
+        let route = RouteExport {
+            map_name: &map_name,
+            algorithm: self.algorithm_ctx.algorithm().name().to_string(),
+            heuristic_weight: self
+                .algorithm_ctx
+                .parameter_values()
+                .number("heuristic_weight"),
+            length: self.algorithm_ctx.route_length,
+            duration: self.algorithm_ctx.route_duration,
+            path: self.algorithm_ctx.path(),
+            explored_edges,
+        };
+
+        match route.write(format, &path) {
+            Ok(()) => self.state.show_toast(
+                format!("Route exported to {}", path.display()),
+                ToastKind::Success,
+            ),
+            Err(e) => self.state.show_toast(
+                format!("Unable to export route to {}: {}", path.display(), e),
+                ToastKind::Error,
+            ),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use egui::Vec2;
+use egui_toast::ToastKind;
+use std::path::PathBuf;
+
+use super::Map;
+use crate::{
+    contexts::DataSource,
+    models::Node,
+    session::{Session, SessionMap},
+    tiles::TileSource,
+    utils::FloatOrd,
+};
+
+// sessions: saving and restoring the map, the view, the selection and the settings
+impl Map {
+    pub(super) fn capture_session(&self) -> Session {
+        Session {
+            map: SessionMap::from_data_source(self.data_ctx.data_source()),
+            projection: self.data_ctx.projection(),
+            start_node: self.state.start_node.as_ref().map(|node| node.id.clone()),
+            end_node: self.state.end_node.as_ref().map(|node| node.id.clone()),
+            waypoints: self
+                .algorithm_ctx
+                .waypoints
+                .iter()
+                .map(|node| node.id.clone())
+                .collect(),
+            algorithm: self.algorithm_ctx.algorithm().id().to_string(),
+            parameters: self.algorithm_ctx.parameters.clone(),
+            is_marking_passed_edges: self.algorithm_ctx.is_marking_passed_edges,
+            cost_metric: self.algorithm_ctx.cost_model.metric,
+            distance_weight: self.algorithm_ctx.cost_model.distance_weight.0,
+            time_weight: self.algorithm_ctx.cost_model.time_weight.0,
+            departure: self.algorithm_ctx.departure,
+            use_turn_restrictions: self.algorithm_ctx.use_turn_restrictions,
+            allow_u_turns: self.algorithm_ctx.allow_u_turns,
+            persist_runs: self.algorithm_ctx.persist_runs,
+            zoom: self.draw_ctx.zoom,
+            pan: [self.draw_ctx.pan.x, self.draw_ctx.pan.y],
+            basemap: self
+                .basemap_ctx
+                .source()
+                .map(|source| source.path().to_path_buf()),
+            basemap_opacity: self.basemap_ctx.opacity,
+        }
+    }
+
+    /// Applies the settings of a session, returns whether the map has to be reloaded.
+    pub(super) fn apply_session(&mut self, session: Session) -> bool {
+        if !self.algorithm_ctx.set_algorithm(&session.algorithm) {
+            self.state.show_toast(
+                format!("Unknown algorithm {}", session.algorithm),
+                ToastKind::Warning,
+            );
+        }
+        self.algorithm_ctx.parameters = session.parameters.clone();
+        self.algorithm_ctx.is_marking_passed_edges = session.is_marking_passed_edges;
+        self.algorithm_ctx.cost_model.metric = session.cost_metric;
+        self.algorithm_ctx.cost_model.distance_weight = FloatOrd(session.distance_weight);
+        self.algorithm_ctx.cost_model.time_weight = FloatOrd(session.time_weight);
+        self.algorithm_ctx.departure = session.departure;
+        self.algorithm_ctx.use_turn_restrictions = session.use_turn_restrictions;
+        self.algorithm_ctx.allow_u_turns = session.allow_u_turns;
+        self.algorithm_ctx.persist_runs = session.persist_runs;
+
+        self.basemap_ctx.opacity = session.basemap_opacity;
+        if let Some(path) = &session.basemap {
+            let is_loaded = self
+                .basemap_ctx
+                .source()
+                .is_some_and(|source| source.path() == path);
+            if !is_loaded {
+                match TileSource::open(path) {
+                    Ok(source) => self.basemap_ctx.set_source(source),
+                    Err(message) => self.state.show_toast(message, ToastKind::Warning),
+                }
+            }
+            self.state.basemap_path_input = path.display().to_string();
+        }
+
+        let mut data_source = session.map.to_data_source();
+        // the map may have been moved or deleted since the session was saved
+        if let DataSource::File(path) = &data_source {
+            if !path.is_file() {
+                self.state.show_toast(
+                    format!(
+                        "Map file not found: {}, using the embedded map",
+                        path.display()
+                    ),
+                    ToastKind::Warning,
+                );
+                data_source = SessionMap::Embedded("map2.xml".to_string()).to_data_source();
+            }
+        }
+        let needs_reload = data_source != *self.data_ctx.data_source()
+            || session.projection != self.data_ctx.projection();
+        if let DataSource::File(path) = &data_source {
+            self.state.map_path_input = path.display().to_string();
+        }
+        self.state.test_data_on = data_source == SessionMap::test_data().to_data_source();
+        self.data_ctx.set_data_source(data_source);
+        if session.projection != self.data_ctx.projection() {
+            self.data_ctx.set_projection(session.projection);
+        }
+
+        self.state.start_node = None;
+        self.state.end_node = None;
+        self.algorithm_ctx.waypoints.clear();
+        self.state.is_drawing_path = false;
+        self.pending_session = Some(session);
+
+        needs_reload
+    }
+
+    pub(super) fn restore_session_view(&mut self) {
+        let Some(session) = &self.pending_session else {
+            return;
+        };
+
+        self.draw_ctx.stop_motion();
+        self.draw_ctx.zoom = session
+            .zoom
+            .clamp(self.draw_ctx.min_zoom(), self.draw_ctx.max_zoom());
+        self.draw_ctx.pan = Vec2::from(session.pan);
+        self.draw_ctx.clamp_pan();
+    }
+
+    // the selected nodes can only be found once the whole map is there
+    pub(super) fn restore_session_nodes(&mut self) {
+        let Some(session) = self.pending_session.take() else {
+            return;
+        };
+
+        let find = |id: &Option<String>| {
+            id.as_ref()
+                .and_then(|id| self.data_ctx.nodes.iter().find(|node| &node.id == id))
+                .cloned()
+        };
+        let (start_node, end_node) = (find(&session.start_node), find(&session.end_node));
+        let waypoints = session
+            .waypoints
+            .iter()
+            .filter_map(|id| find(&Some(id.clone())))
+            .collect::<Vec<Node>>();
+
+        let missing = [
+            (&session.start_node, &start_node),
+            (&session.end_node, &end_node),
+        ]
+        .iter()
+        .filter(|(id, node)| id.is_some() && node.is_none())
+        .count()
+            + session.waypoints.len()
+            - waypoints.len();
+        if missing > 0 {
+            self.state.show_toast(
+                format!("{} selected node(s) not found in the map", missing),
+                ToastKind::Warning,
+            );
+        }
+
+        self.state.start_node = start_node;
+        self.state.end_node = end_node;
+        self.algorithm_ctx.waypoints = waypoints;
+        self.state
+            .show_toast("Session restored".to_string(), ToastKind::Success);
+    }
+
+    pub(super) fn render_session_controls(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.session_path_input)
+                    .hint_text("session.json")
+                    .desired_width(160.),
+            );
+            if ui.button("Save session").clicked() {
+                let path = self.session_path();
+                match self.capture_session().save(&path) {
+                    Ok(()) => self.state.show_toast(
+                        format!("Session saved to {}", path.display()),
+                        ToastKind::Success,
+                    ),
+                    Err(e) => self.state.show_toast(
+                        format!("Unable to save session to {}: {}", path.display(), e),
+                        ToastKind::Error,
+                    ),
+                }
+            }
+            if ui.button("Open session").clicked() {
+                let path = self.session_path();
+                match Session::load(&path) {
+                    Ok(session) => {
+                        if self.apply_session(session) || !self.data_ctx.has_data() {
+                            self.send_load_data_req(ctx);
+                        } else {
+                            self.restore_session_view();
+                            self.restore_session_nodes();
+                        }
+                    }
+                    Err(e) => self.state.show_toast(
+                        format!("Unable to open session {}: {}", path.display(), e),
+                        ToastKind::Error,
+                    ),
+                }
+            }
+        });
+
+        ui.checkbox(
+            &mut self.algorithm_ctx.persist_runs,
+            "Keep computed routes between sessions",
+        )
+        .on_hover_text("Repeated queries on the same map are answered without searching");
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{} route(s) cached",
+                self.algorithm_ctx.cached_run_count()
+            ));
+            if ui.button("Clear cache").clicked() {
+                self.algorithm_ctx.clear_cached_runs();
+            }
+        });
+    }
+
+    fn session_path(&self) -> PathBuf {
+        let path = self.state.session_path_input.trim();
+        if path.is_empty() {
+            PathBuf::from("session.json")
+        } else {
+            PathBuf::from(path)
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use log::debug;
+
+use super::Map;
+use crate::{analysis::GraphStatistics, components::histogram, contexts::DataContext};
+
+// the statistics panel: degrees, arc lengths and the shape of the network
+impl Map {
+    pub(super) fn render_analytics(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let Some(statistics) = &self.data_ctx.statistics else {
+            let is_loading = self.data_ctx.nodes_loading || self.data_ctx.edges_loading;
+            if self.data_ctx.has_data() && !is_loading && !self.data_ctx.statistics_loading {
+                self.data_ctx.statistics_loading = true;
+                send_statistics_request(&self.data_ctx, ctx.clone());
+            }
+            ui.label("Computing statistics...");
+            return;
+        };
+
+        ui.label(format!(
+            "{} nodes, {} arcs",
+            statistics.node_count, statistics.edge_count
+        ));
+        ui.label(format!("Mean degree: {:.2}", statistics.mean_degree()));
+        let degree_bins = statistics
+            .degree_histogram
+            .iter()
+            .enumerate()
+            .map(|(degree, count)| (format!("Degree {}", degree), *count))
+            .collect::<Vec<(String, usize)>>();
+        histogram(ui, &degree_bins);
+
+        ui.label(format!(
+            "Network length: {:.3} km",
+            statistics.total_length / 1000.
+        ));
+        ui.label(format!(
+            "Arc lengths: {:.1} to {:.1} m, {:.1} m on average",
+            statistics.min_length,
+            statistics.max_length,
+            statistics.mean_length()
+        ));
+        let length_bins = statistics
+            .length_histogram
+            .iter()
+            .enumerate()
+            .map(|(bin, count)| (GraphStatistics::length_bin_label(bin), *count))
+            .collect::<Vec<(String, usize)>>();
+        histogram(ui, &length_bins);
+
+        if let Some((min_lon, min_lat, max_lon, max_lat)) = statistics.bounding_box {
+            ui.label(format!(
+                "Bounds: {:.5}, {:.5} to {:.5}, {:.5}",
+                min_lat, min_lon, max_lat, max_lon
+            ));
+        }
+        if let Some((from, to, length)) = &statistics.diameter {
+            ui.label(format!(
+                "Diameter: at least {:.3} km, from {} to {}",
+                length / 1000.,
+                from,
+                to
+            ))
+            .on_hover_text("Estimated by two sweeps ignoring the direction of the arcs");
+        }
+        ui.label(format!(
+            "One-way arcs: {} ({:.1}%), two-way arcs: {}",
+            statistics.one_way_arcs,
+            statistics.one_way_ratio() * 100.,
+            statistics.two_way_arcs
+        ));
+    }
+}
+
+fn send_statistics_request(data_ctx: &DataContext, ctx: egui::Context) {
+    let tx_statistics = data_ctx.tx_statistics.clone();
+    let generation = data_ctx.statistics_generation();
+    let nodes = data_ctx.nodes.clone();
+    let edges = data_ctx.edges.clone();
+
+    tokio::task::spawn_blocking(move || {
+        debug!("Computing statistics...");
+        tx_statistics
+            .send((generation, GraphStatistics::new(&nodes, &edges)))
+            .unwrap();
+        debug!("Statistics computed");
+        ctx.request_repaint();
+    });
+}
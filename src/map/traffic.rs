@@ -0,0 +1,161 @@
+use egui::{Pos2, Rect, Vec2};
+use egui_toast::ToastKind;
+use std::path::PathBuf;
+
+use super::{route_color, Map};
+use crate::traffic::{format_time_of_day, TrafficProfiles};
+
+// traffic profiles: loading them, the departure time and the routes over the day
+impl Map {
+    pub(super) fn render_traffic_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.traffic_path_input)
+                    .hint_text("traffic.csv")
+                    .desired_width(160.),
+            );
+            if ui
+                .button("Load profiles")
+                .on_hover_text("One from,to,t0,t1,... row per arc, travel times in seconds")
+                .clicked()
+            {
+                let path = PathBuf::from(self.state.traffic_path_input.trim());
+                self.load_traffic(&path);
+            }
+        });
+
+        let Some(traffic) = self.algorithm_ctx.traffic() else {
+            ui.label("No traffic profiles, travel times are fixed");
+            return;
+        };
+        ui.label(format!(
+            "{} arcs with profiles, {:.0} minute buckets",
+            traffic.arc_count(),
+            traffic.bucket_minutes()
+        ));
+        if ui.button("Clear profiles").clicked() {
+            self.algorithm_ctx.set_traffic(None);
+            self.state.day_profile = None;
+            return;
+        }
+
+        ui.add(
+            egui::Slider::new(&mut self.algorithm_ctx.departure, 0..=24 * 60 - 1)
+                .step_by(15.)
+                .custom_formatter(|minutes, _| format_time_of_day(minutes as f32 * 60.))
+                .text("Departure"),
+        );
+
+        let (Some(start_node), Some(end_node)) = (&self.state.start_node, &self.state.end_node)
+        else {
+            ui.label("Select a start and an end node to compare the day");
+            return;
+        };
+        if ui
+            .button("Analyse the day")
+            .on_hover_text("Computes the route for a departure every hour")
+            .clicked()
+        {
+            self.algorithm_ctx.request_day_profile(
+                start_node,
+                end_node,
+                &self.data_ctx.neighbors,
+                ui.ctx(),
+            );
+            // filled in once every departure has been searched
+            self.state.day_profile = Some((start_node.id.clone(), end_node.id.clone(), Vec::new()));
+        }
+        if let Some((done, departures)) = self.algorithm_ctx.day_profile_progress() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!(
+                    "Analysing the day, {}/{} departures",
+                    done, departures
+                ));
+                if ui.button("Cancel").clicked() {
+                    self.algorithm_ctx.cancel_day_profile();
+                    self.state.day_profile = None;
+                }
+            });
+        }
+        self.render_day_profile(ui);
+    }
+
+    // one bar per departure, coloured by route, the selected departure is outlined
+    fn render_day_profile(&mut self, ui: &mut egui::Ui) {
+        let Some((start_id, end_id, profile)) = &self.state.day_profile else {
+            return;
+        };
+        let is_current = self.state.start_node.as_ref().map(|node| &node.id) == Some(start_id)
+            && self.state.end_node.as_ref().map(|node| &node.id) == Some(end_id);
+        if !is_current || profile.is_empty() {
+            return;
+        }
+
+        let routes = profile.iter().map(|(_, _, route)| route + 1).max().unwrap();
+        let longest = profile
+            .iter()
+            .map(|(_, duration, _)| *duration)
+            .fold(f32::EPSILON, f32::max);
+        ui.label(format!(
+            "{} distinct route(s), {:.1} to {:.1} min",
+            routes,
+            profile.iter().map(|(_, d, _)| *d).fold(f32::MAX, f32::min) / 60.,
+            longest / 60.
+        ));
+
+        let (response, painter) = ui.allocate_painter(Vec2::new(240., 60.), egui::Sense::click());
+        let rect = response.rect;
+        let bar_width = rect.width() / profile.len() as f32;
+        let mut clicked_departure = None;
+        profile
+            .iter()
+            .enumerate()
+            .for_each(|(index, (departure, duration, route))| {
+                let height = rect.height() * duration / longest;
+                let bar = Rect::from_min_max(
+                    Pos2::new(
+                        rect.left() + index as f32 * bar_width,
+                        rect.bottom() - height,
+                    ),
+                    Pos2::new(
+                        rect.left() + (index + 1) as f32 * bar_width - 1.,
+                        rect.bottom(),
+                    ),
+                );
+                painter.rect_filled(bar, 0., route_color(*route));
+
+                let next_departure = departure + (24 * 60) / profile.len() as u32;
+                if (*departure..next_departure).contains(&self.algorithm_ctx.departure) {
+                    painter.rect_stroke(bar, 0., (1., ui.visuals().text_color()));
+                }
+                if response
+                    .interact_pointer_pos()
+                    .is_some_and(|pos| response.clicked() && bar.x_range().contains(pos.x))
+                {
+                    clicked_departure = Some(*departure);
+                }
+            });
+        response.on_hover_text("Click a bar to depart at that time");
+
+        if let Some(departure) = clicked_departure {
+            self.algorithm_ctx.departure = departure;
+        }
+    }
+
+    pub(super) fn load_traffic(&mut self, path: &std::path::Path) {
+        match TrafficProfiles::load(path) {
+            Ok(traffic) => {
+                self.state.show_toast(
+                    format!("Traffic profiles loaded for {} arcs", traffic.arc_count()),
+                    ToastKind::Info,
+                );
+                self.algorithm_ctx.set_traffic(Some(traffic));
+                self.state.traffic_path_input = path.display().to_string();
+                self.state.is_drawing_path = false;
+                self.state.day_profile = None;
+            }
+            Err(message) => self.state.show_toast(message, ToastKind::Error),
+        }
+    }
+}
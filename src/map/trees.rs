@@ -0,0 +1,81 @@
+use enum_iterator::all;
+
+use super::Map;
+use crate::{
+    analysis::TreeKind,
+    components::toggle,
+    utils::constants::{TERMINAL_NODE_RADIUS, TREE_EDGE_COLOR, TREE_EDGE_WIDTH},
+};
+
+// spanning and Steiner trees: picking the terminals, computing the tree and drawing it
+impl Map {
+    pub(super) fn render_tree_controls(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        egui::ComboBox::from_label("Tree")
+            .selected_text(self.analysis_ctx.tree_kind.to_string())
+            .show_ui(ui, |ui| {
+                for kind in all::<TreeKind>() {
+                    ui.selectable_value(&mut self.analysis_ctx.tree_kind, kind, kind.to_string());
+                }
+            });
+        if self.analysis_ctx.tree_kind == TreeKind::Steiner {
+            ui.label("Pick terminals on click");
+            ui.add(toggle(&mut self.analysis_ctx.is_picking_terminals))
+                .on_hover_text("Clicking a node adds it to the terminals or removes it");
+            ui.horizontal(|ui| {
+                ui.label(format!("{} terminal(s)", self.analysis_ctx.terminals.len()));
+                if ui.button("Clear terminals").clicked() {
+                    self.analysis_ctx.terminals.clear();
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            let can_compute = self.data_ctx.has_data() && !self.analysis_ctx.is_computing_tree();
+            if ui
+                .add_enabled(can_compute, egui::Button::new("Compute tree"))
+                .clicked()
+            {
+                self.analysis_ctx
+                    .request_tree(&self.data_ctx.nodes, &self.data_ctx.edges, ctx);
+            }
+            if self.analysis_ctx.is_computing_tree() {
+                ui.spinner();
+            }
+            if self.analysis_ctx.tree().is_some() && ui.button("Clear tree").clicked() {
+                self.analysis_ctx.clear_tree();
+            }
+        });
+
+        if let Some(tree) = self.analysis_ctx.tree() {
+            ui.label(format!(
+                "{}: {:.3} km, {} arcs",
+                tree.kind,
+                tree.length / 1000.,
+                tree.edges.len()
+            ));
+        }
+    }
+
+    pub(super) fn render_tree(&self, ui: &mut egui::Ui) {
+        if let Some(tree) = self.analysis_ctx.tree() {
+            tree.edges.iter().for_each(|edge| {
+                let (from, to) = self.draw_ctx.calc_edge_coords(edge);
+                ui.painter()
+                    .line_segment([from, to], (TREE_EDGE_WIDTH, TREE_EDGE_COLOR));
+            });
+        }
+        if self.analysis_ctx.tree_kind == TreeKind::Steiner {
+            self.analysis_ctx
+                .terminals
+                .iter()
+                .filter_map(|id| self.data_ctx.find_node(id))
+                .for_each(|node| {
+                    ui.painter().circle_filled(
+                        self.draw_ctx.calc_node_coords(node),
+                        TERMINAL_NODE_RADIUS,
+                        TREE_EDGE_COLOR,
+                    );
+                });
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use super::Map;
+use crate::{components::toggle, utils::constants::MAX_LISTED_ISSUES};
+
+// the validation report: issues of the map and its components
+impl Map {
+    pub(super) fn render_validation_report(&mut self, ui: &mut egui::Ui) {
+        let Some(report) = &self.data_ctx.validation else {
+            ui.label("The map has not been validated yet");
+            return;
+        };
+
+        ui.label(format!("Issues found: {}", report.issue_count()));
+        ui.label(format!("Dangling arcs: {}", report.dangling_arcs.len()));
+        ui.label(format!(
+            "Unreadable arc attributes: {}",
+            report.invalid_attributes.len()
+        ));
+        ui.label(format!(
+            "Duplicate node ids: {}",
+            report.duplicate_ids.len()
+        ));
+        ui.label(format!("Self-loops: {}", report.self_loops.len()));
+        ui.label(format!(
+            "Zero or negative lengths: {}",
+            report.invalid_lengths.len()
+        ));
+        ui.label(format!(
+            "Strongly connected components: {}, the largest has {} of {} nodes",
+            report.components.len(),
+            report.components.first().map_or(0, Vec::len),
+            self.data_ctx.nodes.len()
+        ));
+        ui.label(format!("Islands: {}", report.island_count()))
+            .on_hover_text("Parts of the map no arc leads to or from");
+        ui.collapsing(
+            format!(
+                "One-way dead ends and entries: {}",
+                report.one_way_nodes.len()
+            ),
+            |ui| {
+                ui.label("No route leaves and comes back to these nodes, they are not issues");
+                egui::ScrollArea::vertical()
+                    .id_source("one_way_nodes")
+                    .max_height(120.)
+                    .show(ui, |ui| {
+                        report
+                            .one_way_nodes
+                            .iter()
+                            .take(MAX_LISTED_ISSUES)
+                            .for_each(|node| {
+                                ui.label(format!("Node {}", node.id));
+                            });
+                    });
+            },
+        );
+        egui::ScrollArea::vertical()
+            .max_height(120.)
+            .show(ui, |ui| {
+                report.issues().take(MAX_LISTED_ISSUES).for_each(|issue| {
+                    ui.label(issue);
+                });
+            });
+
+        ui.label("Highlight components");
+        ui.add(toggle(&mut self.state.highlight_components))
+            .on_hover_text("Colours the nodes and arcs outside of the largest component");
+        ui.label("Pick nodes in the largest component only");
+        ui.add(toggle(&mut self.state.pick_in_largest_component))
+            .on_hover_text("Routes between other components may not exist");
+    }
+}
@@ -0,0 +1,242 @@
+use egui::{emath::Rot2, Color32, Pos2, Rect, Vec2};
+use egui_toast::ToastKind;
+use enum_iterator::all;
+use std::path::PathBuf;
+
+use super::{edge_color, heat_color, incident_color, Map};
+use crate::{
+    analysis::TreeKind,
+    contexts::DrawingContext,
+    export::{Primitive, Scene, ViewFormat},
+    models::{Edge, Node},
+    utils::constants::{
+        EDGE_WIDTH, HEAT_EDGE_EXTRA_WIDTH, HEURISTIC_VIOLATION_COLOR, HEURISTIC_VIOLATION_WIDTH,
+        INCIDENT_EDGE_WIDTH, MAX_EXPORT_SIZE, NEGATIVE_CYCLE_COLOR, NODE_RADIUS, PASSED_EDGE_COLOR,
+        PASSED_EDGE_WIDTH, ROUTE_EDGE_COLOR, ROUTE_EDGE_WIDTH, SELECTED_NODE_COLOR,
+        SELECTED_NODE_RADIUS, TERMINAL_NODE_RADIUS, TREE_EDGE_COLOR, TREE_EDGE_WIDTH,
+        WAYPOINT_NODE_COLOR,
+    },
+};
+
+// view export: writing the current view or the whole map as PNG or SVG
+impl Map {
+    pub(super) fn render_view_export(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.state.export_full_map, false, "Current view");
+            ui.radio_value(&mut self.state.export_full_map, true, "Full map");
+        });
+        if self.state.export_full_map {
+            ui.add(
+                egui::DragValue::new(&mut self.state.export_width)
+                    .clamp_range(1..=MAX_EXPORT_SIZE)
+                    .suffix(" px wide"),
+            );
+        }
+        egui::ComboBox::from_label("Image format")
+            .selected_text(self.state.view_format.to_string())
+            .show_ui(ui, |ui| {
+                for format in all::<ViewFormat>() {
+                    ui.selectable_value(&mut self.state.view_format, format, format.to_string());
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.view_path_input)
+                    .hint_text("map")
+                    .desired_width(160.),
+            );
+            if ui.button("Export").clicked() {
+                let visuals = ui.visuals().clone();
+                self.export_view(&visuals);
+            }
+        });
+    }
+
+    fn export_view(&mut self, visuals: &egui::Visuals) {
+        let format = self.state.view_format;
+        let mut path = PathBuf::from(self.state.view_path_input.trim());
+        if path.as_os_str().is_empty() {
+            path = PathBuf::from("map");
+        }
+        if path.extension().is_none() {
+            path.set_extension(format.extension());
+        }
+
+        let mut draw_ctx = self.draw_ctx.clone();
+        let (offset, size) = if self.state.export_full_map {
+            let Some(bounds) = draw_ctx.bounds() else {
+                self.state
+                    .show_toast("No map loaded".to_string(), ToastKind::Warning);
+                return;
+            };
+            let width = self.state.export_width;
+            let height = (width as f32 * bounds.height() / bounds.width().max(f32::EPSILON))
+                .round()
+                .clamp(1.0, MAX_EXPORT_SIZE as f32) as u32;
+
+            draw_ctx.set_viewport(Rect::from_min_size(
+                Pos2::ZERO,
+                Vec2::new(width as f32, height as f32),
+            ));
+            draw_ctx.fit_to_bounds();
+            (Vec2::ZERO, [width, height])
+        } else {
+            let viewport = draw_ctx.viewport();
+            (
+                viewport.min.to_vec2(),
+                [viewport.width() as u32, viewport.height() as u32],
+            )
+        };
+        if size[0] == 0 || size[1] == 0 {
+            return;
+        }
+
+        let scene = self.build_scene(&draw_ctx, offset, size, visuals);
+        match scene.write(format, &path) {
+            Ok(()) => self.state.show_toast(
+                format!("View exported to {}", path.display()),
+                ToastKind::Success,
+            ),
+            Err(e) => self.state.show_toast(
+                format!("Unable to export view to {}: {}", path.display(), e),
+                ToastKind::Error,
+            ),
+        }
+    }
+
+    // mirrors `render_edges`, `render_nodes` and `render_selected` with the same layers and
+    // overlays, without the basemap, the edit highlights and the controls
+    fn build_scene(
+        &self,
+        draw_ctx: &DrawingContext,
+        offset: Vec2,
+        size: [u32; 2],
+        visuals: &egui::Visuals,
+    ) -> Scene {
+        let mut scene = Scene::new(size, visuals.panel_fill);
+        let line = |edge: &Edge, width: f32, color: Color32| {
+            let (from, to) = draw_ctx.calc_edge_coords(edge);
+            Primitive::Line {
+                from: from - offset,
+                to: to - offset,
+                width,
+                color,
+            }
+        };
+        let circle = |node: &Node, radius: f32, color: Color32| Primitive::Circle {
+            center: draw_ctx.calc_node_coords(node) - offset,
+            radius,
+            color,
+        };
+
+        let mut incidents = Vec::new();
+        let mut passed = Vec::new();
+        let mut route = Vec::new();
+        let edge_color = edge_color(visuals.dark_mode);
+        self.data_ctx.edges.iter().for_each(|edge| {
+            if self.state.is_drawing_path {
+                if self.algorithm_ctx.is_edge_selected(edge) {
+                    route.push(line(edge, ROUTE_EDGE_WIDTH, ROUTE_EDGE_COLOR));
+                    return;
+                } else if self.algorithm_ctx.is_marking_passed_edges
+                    && self.algorithm_ctx.is_edge_passed(edge)
+                {
+                    passed.push(line(edge, PASSED_EDGE_WIDTH, PASSED_EDGE_COLOR));
+                    return;
+                }
+            }
+            if let Some(incident) = self.algorithm_ctx.incident(edge) {
+                incidents.push(line(edge, INCIDENT_EDGE_WIDTH, incident_color(incident)));
+                return;
+            }
+            if let Some(heat) = self.centrality_heat(edge) {
+                scene.push(line(
+                    edge,
+                    EDGE_WIDTH + heat * HEAT_EDGE_EXTRA_WIDTH,
+                    heat_color(heat),
+                ));
+                return;
+            }
+            let color = self
+                .component_color(&edge.from)
+                .filter(|color| self.component_color(&edge.to) == Some(*color))
+                .unwrap_or(edge_color);
+            scene.push(line(edge, EDGE_WIDTH, color));
+        });
+
+        let mut selected = Vec::new();
+        self.data_ctx.nodes.iter().for_each(|node| {
+            if self.is_selected(&node.id) {
+                selected.push(circle(node, SELECTED_NODE_RADIUS, SELECTED_NODE_COLOR));
+                return;
+            }
+            if self.algorithm_ctx.waypoints.contains(node) {
+                selected.push(circle(node, SELECTED_NODE_RADIUS, WAYPOINT_NODE_COLOR));
+                return;
+            }
+            let color = self.component_color(node).unwrap_or(visuals.text_color());
+            scene.push(circle(node, NODE_RADIUS, color));
+        });
+
+        incidents
+            .into_iter()
+            .chain(passed)
+            .chain(route)
+            .chain(selected)
+            .for_each(|primitive| scene.push(primitive));
+
+        if let Some(tree) = self.analysis_ctx.tree() {
+            tree.edges
+                .iter()
+                .for_each(|edge| scene.push(line(edge, TREE_EDGE_WIDTH, TREE_EDGE_COLOR)));
+        }
+        if self.analysis_ctx.tree_kind == TreeKind::Steiner {
+            self.analysis_ctx
+                .terminals
+                .iter()
+                .filter_map(|id| self.data_ctx.find_node(id))
+                .for_each(|node| {
+                    scene.push(circle(node, TERMINAL_NODE_RADIUS, TREE_EDGE_COLOR));
+                });
+        }
+
+        if let Some(report) = self
+            .analysis_ctx
+            .heuristic_report()
+            .filter(|_| self.analysis_ctx.show_heuristic_violations)
+        {
+            report.inconsistent_edges.keys().for_each(|edge| {
+                scene.push(line(
+                    edge,
+                    HEURISTIC_VIOLATION_WIDTH,
+                    HEURISTIC_VIOLATION_COLOR,
+                ));
+            });
+        }
+
+        // the same arrows as `Painter::arrow`, a shaft and two tips a quarter of its length
+        let tip_rotation = Rot2::from_angle(std::f32::consts::TAU / 10.0);
+        self.algorithm_ctx.negative_cycle().iter().for_each(|edge| {
+            let (from, to) = draw_ctx.calc_edge_coords(edge);
+            let (from, to) = (from - offset, to - offset);
+            let direction = (to - from).normalized();
+            let tip_length = (to - from).length() / 4.0;
+            [
+                from,
+                to - tip_length * (tip_rotation * direction),
+                to - tip_length * (tip_rotation.inverse() * direction),
+            ]
+            .into_iter()
+            .for_each(|start| {
+                scene.push(Primitive::Line {
+                    from: start,
+                    to,
+                    width: ROUTE_EDGE_WIDTH,
+                    color: NEGATIVE_CYCLE_COLOR,
+                })
+            });
+        });
+
+        scene
+    }
+}
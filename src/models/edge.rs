@@ -1,17 +1,60 @@
 use std::{fmt, hash::Hash};
 
-use super::Node;
+use super::{Node, RoadClass};
+
+// km/h, used when an arc has neither a speed limit nor a road class
+pub const DEFAULT_SPEED: f32 = 50.;
 
 #[derive(Debug, Clone)]
 pub struct Edge {
     pub from: Node,
     pub to: Node,
+    // in meters
     pub length: f32,
+    pub road_class: Option<RoadClass>,
+    // in km/h
+    pub speed_limit: Option<f32>,
+    // in seconds, overrides the time derived from the speed
+    pub travel_time: Option<f32>,
 }
 
 impl Edge {
     pub fn new(from: Node, to: Node, length: f32) -> Self {
-        Self { from, to, length }
+        Self {
+            from,
+            to,
+            length,
+            road_class: None,
+            speed_limit: None,
+            travel_time: None,
+        }
+    }
+
+    pub fn with_attributes(
+        mut self,
+        road_class: Option<RoadClass>,
+        speed_limit: Option<f32>,
+        travel_time: Option<f32>,
+    ) -> Self {
+        self.road_class = road_class;
+        self.speed_limit = speed_limit;
+        self.travel_time = travel_time;
+        self
+    }
+
+    /// Speed in km/h, falling back on the road class and then on [`DEFAULT_SPEED`].
+    pub fn speed(&self) -> f32 {
+        // a speed of zero or less would give an endless duration
+        self.speed_limit
+            .filter(|speed| *speed > 0.)
+            .or(self.road_class.map(|class| class.default_speed()))
+            .unwrap_or(DEFAULT_SPEED)
+    }
+
+    /// Time in seconds needed to drive along the edge.
+    pub fn duration(&self) -> f32 {
+        self.travel_time
+            .unwrap_or_else(|| self.length / (self.speed() / 3.6))
     }
 }
 
@@ -1,7 +1,13 @@
 mod edge;
 mod node;
+mod road_class;
+mod turn_restriction;
 
 #[allow(unused_imports)]
 pub use edge::Edge;
 #[allow(unused_imports)]
 pub use node::Node;
+#[allow(unused_imports)]
+pub use road_class::RoadClass;
+#[allow(unused_imports)]
+pub use turn_restriction::TurnRestriction;
@@ -9,11 +9,27 @@ use egui::Pos2;
 pub struct Node {
     pub id: String,
     pub position: Pos2,
+    // the geographic coordinates the position was projected from, in degrees
+    pub longitude: f64,
+    pub latitude: f64,
+    // the name or label attribute of the map file, if any
+    pub label: Option<String>,
 }
 
 impl Node {
-    pub fn new(id: String, position: Pos2) -> Self {
-        Self { id, position }
+    pub fn new(id: String, position: Pos2, longitude: f64, latitude: f64) -> Self {
+        Self {
+            id,
+            position,
+            longitude,
+            latitude,
+            label: None,
+        }
+    }
+
+    pub fn with_label(mut self, label: Option<String>) -> Self {
+        self.label = label;
+        self
     }
 }
 
@@ -0,0 +1,63 @@
+use std::{fmt, str::FromStr};
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum RoadClass {
+    Motorway,
+    Trunk,
+    Primary,
+    Secondary,
+    Tertiary,
+    Residential,
+    Service,
+    Unclassified,
+}
+
+impl RoadClass {
+    /// Speed in km/h assumed when an arc has no explicit speed limit.
+    pub fn default_speed(&self) -> f32 {
+        match self {
+            RoadClass::Motorway => 130.,
+            RoadClass::Trunk => 110.,
+            RoadClass::Primary => 90.,
+            RoadClass::Secondary => 70.,
+            RoadClass::Tertiary => 50.,
+            RoadClass::Residential => 30.,
+            RoadClass::Service => 20.,
+            RoadClass::Unclassified => 50.,
+        }
+    }
+}
+
+impl FromStr for RoadClass {
+    type Err = String;
+
+    // the names follow the OpenStreetMap "highway" values
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "motorway" => Ok(RoadClass::Motorway),
+            "trunk" => Ok(RoadClass::Trunk),
+            "primary" => Ok(RoadClass::Primary),
+            "secondary" => Ok(RoadClass::Secondary),
+            "tertiary" => Ok(RoadClass::Tertiary),
+            "residential" => Ok(RoadClass::Residential),
+            "service" => Ok(RoadClass::Service),
+            "unclassified" => Ok(RoadClass::Unclassified),
+            _ => Err(format!("Unknown road class: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for RoadClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoadClass::Motorway => write!(f, "motorway"),
+            RoadClass::Trunk => write!(f, "trunk"),
+            RoadClass::Primary => write!(f, "primary"),
+            RoadClass::Secondary => write!(f, "secondary"),
+            RoadClass::Tertiary => write!(f, "tertiary"),
+            RoadClass::Residential => write!(f, "residential"),
+            RoadClass::Service => write!(f, "service"),
+            RoadClass::Unclassified => write!(f, "unclassified"),
+        }
+    }
+}
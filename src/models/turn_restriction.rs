@@ -0,0 +1,21 @@
+/// The turn from the arc `from -> via` onto the arc `via -> to`, given by node ids.
+///
+/// Without a penalty the turn is forbidden, otherwise it costs the penalty (seconds).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnRestriction {
+    pub from: String,
+    pub via: String,
+    pub to: String,
+    pub penalty: Option<f32>,
+}
+
+impl TurnRestriction {
+    pub fn new(from: String, via: String, to: String, penalty: Option<f32>) -> Self {
+        Self {
+            from,
+            via,
+            to,
+            penalty,
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{models::Node, utils::haversine_distance};
+
+// (kind of match, then position or gaps, then text length), lower is closer
+type Score = (u8, usize, usize);
+
+/// The nodes whose id or label matches `query`, tolerating left out characters, or
+/// the nodes nearest to it when it reads as "latitude, longitude".
+///
+/// Each node comes with what matched, `None` when it is the id itself.
+pub fn search_nodes(nodes: &[Node], query: &str, limit: usize) -> Vec<(Node, Option<String>)> {
+    let query = query.trim();
+    if query.is_empty() {
+        return vec![];
+    }
+
+    if let Some((latitude, longitude)) = parse_coordinates(query) {
+        let mut nearest = nodes
+            .iter()
+            .map(|node| {
+                let distance =
+                    haversine_distance((longitude, latitude), (node.longitude, node.latitude));
+                (distance, node)
+            })
+            .collect::<Vec<_>>();
+        nearest.sort_by(|a, b| a.0.total_cmp(&b.0));
+        return nearest
+            .into_iter()
+            .take(limit)
+            .map(|(distance, node)| (node.clone(), Some(format!("{:.0} m away", distance))))
+            .collect();
+    }
+
+    let query = query.to_lowercase();
+    let mut matches = nodes
+        .iter()
+        .filter_map(|node| {
+            let by_id = fuzzy_score(&query, &node.id).map(|score| (score, false));
+            let by_label = node
+                .label
+                .as_ref()
+                .and_then(|label| Some((fuzzy_score(&query, label)?, true)));
+            let (score, is_label) = by_id.into_iter().chain(by_label).min()?;
+            Some((score, node, is_label))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.id.cmp(&b.1.id)));
+
+    matches
+        .into_iter()
+        .take(limit)
+        .map(|(_, node, is_label)| (node.clone(), node.label.clone().filter(|_| is_label)))
+        .collect()
+}
+
+// "49.61, 6.13" or "49.61 6.13", the latitude first like most maps show them
+fn parse_coordinates(query: &str) -> Option<(f64, f64)> {
+    let parts = query
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>();
+    let [latitude, longitude] = parts[..] else {
+        return None;
+    };
+    let (latitude, longitude) = (
+        latitude.parse::<f64>().ok()?,
+        longitude.parse::<f64>().ok()?,
+    );
+
+    ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude))
+        .then_some((latitude, longitude))
+}
+
+// exact matches first, then the texts containing the query, then the ones holding its
+// characters in order; `query` is lowercase already
+fn fuzzy_score(query: &str, text: &str) -> Option<Score> {
+    let text = text.to_lowercase();
+    if text == query {
+        return Some((0, 0, text.len()));
+    }
+    if let Some(position) = text.find(query) {
+        return Some((1, position, text.len()));
+    }
+
+    // characters skipped between the first and the last one matched
+    let mut gaps = 0;
+    let mut started = false;
+    let mut pending = query.chars().peekable();
+    for c in text.chars() {
+        match pending.peek() {
+            Some(&next) if next == c => {
+                pending.next();
+                started = true;
+            }
+            Some(_) if started => gaps += 1,
+            Some(_) => {}
+            None => break,
+        }
+    }
+    pending.peek().is_none().then_some((2, gaps, text.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_nodes_by_id_label_and_coordinates() {
+        let node = |id: &str, label: Option<&str>, longitude: f64, latitude: f64| {
+            Node::new(id.to_string(), egui::Pos2::ZERO, longitude, latitude)
+                .with_label(label.map(str::to_string))
+        };
+        let nodes = [
+            node("12", Some("Gare Centrale"), 6.13, 49.60),
+            node("120", None, 6.14, 49.61),
+            node("3", Some("Place Guillaume"), 6.20, 49.70),
+        ];
+        let ids = |matches: Vec<(Node, Option<String>)>| {
+            matches
+                .into_iter()
+                .map(|(node, _)| node.id)
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(ids(search_nodes(&nodes, "12", 10)), ["12", "120"]);
+        assert_eq!(ids(search_nodes(&nodes, "", 10)), Vec::<String>::new());
+        // the characters of "plgm" appear in order in "place guillaume"
+        let matches = search_nodes(&nodes, "PlGm", 10);
+        assert_eq!(matches[0].1.as_deref(), Some("Place Guillaume"));
+        assert_eq!(ids(search_nodes(&nodes, "gare", 1)), ["12"]);
+        assert_eq!(ids(search_nodes(&nodes, "49.61, 6.14", 2)), ["120", "12"]);
+    }
+}
@@ -1,11 +1,60 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, io::BufRead, str::FromStr};
+
+use quick_xml::events::{BytesStart, Event};
 
 use crate::{
-    models::{Edge, Node},
-    translator::TRANSLATOR,
+    models::{Edge, Node, RoadClass, TurnRestriction},
+    translator::Translator,
 };
 
-pub fn parse_xml(data_buffer: &'static [u8]) -> (Vec<Node>, Vec<Edge>, HashMap<Node, Vec<Edge>>) {
+// how many nodes are accumulated before a batch is handed over to the UI
+const NODE_BATCH_SIZE: usize = 10_000;
+// how many bytes are read between two progress reports
+const PROGRESS_STEP_BYTES: u64 = 1024 * 1024;
+
+// (nodes, edges, neighbors, restrictions, dangling_arcs, invalid_attributes)
+pub type ParseOutput = (
+    Vec<Node>,
+    Vec<Edge>,
+    HashMap<Node, Vec<Edge>>,
+    Vec<TurnRestriction>,
+    Vec<(String, String)>,
+    Vec<InvalidAttribute>,
+);
+
+// (from, to, name, value) of an optional arc attribute that could not be read
+pub type InvalidAttribute = (String, String, String, String);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseProgress {
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+impl ParseProgress {
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        (self.bytes_read as f64 / self.total_bytes as f64).min(1.0) as f32
+    }
+}
+
+pub enum ParseEvent {
+    // nodes parsed since the previous batch, sent before the arcs are read
+    NodeBatch(Vec<Node>),
+    Progress(ParseProgress),
+}
+
+/// Besides `from`, `to` and `length`, arcs may carry an optional road `class`,
+/// `speed` limit (km/h) and travel `time` (seconds). An optional `<restrictions>`
+/// element lists `<restriction from via to [penalty]/>` turns, see [`TurnRestriction`].
+///
+/// Arcs referring to unknown nodes are left out and returned as (from, to) ids, so
+/// are the optional attributes that cannot be read, the arcs being kept without them.
+pub fn parse_xml(data_buffer: &[u8], translator: &mut Translator) -> ParseOutput {
     let text = std::str::from_utf8(data_buffer).unwrap();
 
     let doc = roxmltree::Document::parse(text).unwrap();
@@ -25,30 +74,314 @@ pub fn parse_xml(data_buffer: &'static [u8]) -> (Vec<Node>, Vec<Edge>, HashMap<N
         .filter(|n| n.has_tag_name("node"))
         .map(|n| {
             let id = n.attribute("id").unwrap().parse::<String>().unwrap();
-            let lat = n.attribute("latitude").unwrap().parse::<f64>().unwrap() / 100000.0;
-            let long = n.attribute("longitude").unwrap().parse::<f64>().unwrap() / 100000.0;
+            // the map files have the "latitude" and "longitude" attributes swapped
+            let longitude = n.attribute("latitude").unwrap().parse::<f64>().unwrap() / 100000.0;
+            let latitude = n.attribute("longitude").unwrap().parse::<f64>().unwrap() / 100000.0;
 
-            let position_on_screen = TRANSLATOR.lock().project(lat, long);
+            let position_on_screen = translator.project(longitude, latitude);
 
-            Node::new(id, position_on_screen)
+            // maps name their places with either attribute
+            let label = n.attribute("name").or(n.attribute("label"));
+
+            Node::new(id, position_on_screen, longitude, latitude)
+                .with_label(label.map(str::to_string))
         })
         .collect::<Vec<Node>>();
 
+    let node_index = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.id.as_str(), index))
+        .collect::<HashMap<&str, usize>>();
+    let mut dangling_arcs = Vec::new();
+    let mut invalid_attributes = Vec::new();
+
     let edges = arcs_elem
         .children()
         .filter(|n| n.has_tag_name("arc"))
-        .map(|n| {
+        .filter_map(|n| {
             let from = n.attribute("from").unwrap().parse::<String>().unwrap();
             let to = n.attribute("to").unwrap().parse::<String>().unwrap();
             let length = n.attribute("length").unwrap().parse::<f32>().unwrap();
 
-            let from_node = nodes.iter().find(|n| n.id == from).unwrap().clone();
-            let to_node = nodes.iter().find(|n| n.id == to).unwrap().clone();
+            let (Some(&from_index), Some(&to_index)) =
+                (node_index.get(from.as_str()), node_index.get(to.as_str()))
+            else {
+                dangling_arcs.push((from, to));
+                return None;
+            };
+            let from_node = nodes[from_index].clone();
+            let to_node = nodes[to_index].clone();
 
-            Edge::new(from_node, to_node, length)
+            let (road_class, speed_limit, travel_time) = optional_arc_attributes(
+                (&from, &to),
+                [
+                    n.attribute("class"),
+                    n.attribute("speed"),
+                    n.attribute("time"),
+                ],
+                &mut invalid_attributes,
+            );
+            Some(Edge::new(from_node, to_node, length).with_attributes(
+                road_class,
+                speed_limit,
+                travel_time,
+            ))
         })
         .collect::<Vec<Edge>>();
 
+    let restrictions = map_elem
+        .children()
+        .filter(|n| n.has_tag_name("restrictions"))
+        .flat_map(|n| n.children())
+        .filter(|n| n.has_tag_name("restriction"))
+        .map(|n| {
+            TurnRestriction::new(
+                n.attribute("from").unwrap().to_string(),
+                n.attribute("via").unwrap().to_string(),
+                n.attribute("to").unwrap().to_string(),
+                n.attribute("penalty").map(|p| p.parse::<f32>().unwrap()),
+            )
+        })
+        .collect::<Vec<TurnRestriction>>();
+
+    let neighbors = build_neighbors(&edges);
+
+    (
+        nodes,
+        edges,
+        neighbors,
+        restrictions,
+        dangling_arcs,
+        invalid_attributes,
+    )
+}
+
+/// SAX-style counterpart of [`parse_xml`]: the document is never held in memory,
+/// nodes and arcs are built while the reader advances.
+///
+/// `on_event` receives the nodes in batches as soon as they are parsed, so they
+/// can be drawn while the arcs are still being read, along with periodic progress.
+///
+/// Fails on malformed or truncated files, with the position of the problem.
+pub fn parse_xml_streaming<R: BufRead>(
+    source: R,
+    total_bytes: u64,
+    translator: &mut Translator,
+    mut on_event: impl FnMut(ParseEvent),
+) -> Result<ParseOutput, String> {
+    let mut reader = quick_xml::Reader::from_reader(source);
+    reader.trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut node_index: HashMap<String, usize> = HashMap::new();
+    let mut edges: Vec<Edge> = Vec::new();
+    let mut restrictions: Vec<TurnRestriction> = Vec::new();
+    let mut dangling_arcs: Vec<(String, String)> = Vec::new();
+    let mut invalid_attributes: Vec<InvalidAttribute> = Vec::new();
+    let mut pending_nodes: Vec<Node> = Vec::with_capacity(NODE_BATCH_SIZE);
+    let mut last_reported: u64 = 0;
+
+    // a truncated file is missing the end of the map
+    let mut is_complete = false;
+    loop {
+        let position = reader.buffer_position();
+        let at = |e: String| format!("Byte {}: {}", position, e);
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| at(e.to_string()))?;
+        match event {
+            Event::Start(elem) | Event::Empty(elem) => match elem.name().as_ref() {
+                b"node" => {
+                    let id = attribute(&elem, "id").map_err(at)?;
+                    // same swapped attributes as in `parse_xml`
+                    let longitude = number::<f64>(&elem, "latitude").map_err(at)? / 100000.0;
+                    let latitude = number::<f64>(&elem, "longitude").map_err(at)? / 100000.0;
+
+                    let position_on_screen = translator.project(longitude, latitude);
+                    let label = match optional_attribute(&elem, "name").map_err(at)? {
+                        Some(name) => Some(name),
+                        None => optional_attribute(&elem, "label").map_err(at)?,
+                    };
+                    let node =
+                        Node::new(id, position_on_screen, longitude, latitude).with_label(label);
+
+                    node_index.insert(node.id.clone(), nodes.len());
+                    pending_nodes.push(node.clone());
+                    nodes.push(node);
+
+                    if pending_nodes.len() >= NODE_BATCH_SIZE {
+                        on_event(ParseEvent::NodeBatch(std::mem::take(&mut pending_nodes)));
+                    }
+                }
+                b"arc" => {
+                    let from = attribute(&elem, "from").map_err(at)?;
+                    let to = attribute(&elem, "to").map_err(at)?;
+                    let length = number::<f32>(&elem, "length").map_err(at)?;
+
+                    match (node_index.get(&from), node_index.get(&to)) {
+                        (Some(&from_index), Some(&to_index)) => {
+                            let from_node = nodes[from_index].clone();
+                            let to_node = nodes[to_index].clone();
+
+                            let class = optional_attribute(&elem, "class").map_err(at)?;
+                            let speed = optional_attribute(&elem, "speed").map_err(at)?;
+                            let time = optional_attribute(&elem, "time").map_err(at)?;
+                            let (road_class, speed_limit, travel_time) = optional_arc_attributes(
+                                (&from, &to),
+                                [class.as_deref(), speed.as_deref(), time.as_deref()],
+                                &mut invalid_attributes,
+                            );
+
+                            edges.push(Edge::new(from_node, to_node, length).with_attributes(
+                                road_class,
+                                speed_limit,
+                                travel_time,
+                            ));
+                        }
+                        _ => dangling_arcs.push((from, to)),
+                    }
+                }
+                b"restriction" => {
+                    let penalty = match optional_attribute(&elem, "penalty").map_err(at)? {
+                        Some(penalty) => Some(parse(&penalty, "penalty").map_err(at)?),
+                        None => None,
+                    };
+                    restrictions.push(TurnRestriction::new(
+                        attribute(&elem, "from").map_err(at)?,
+                        attribute(&elem, "via").map_err(at)?,
+                        attribute(&elem, "to").map_err(at)?,
+                        penalty,
+                    ));
+                }
+                _ => {}
+            },
+            Event::End(elem) if elem.name().as_ref() == b"nodes" && !pending_nodes.is_empty() => {
+                on_event(ParseEvent::NodeBatch(std::mem::take(&mut pending_nodes)));
+            }
+            Event::End(elem) if elem.name().as_ref() == b"map" => is_complete = true,
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+
+        let bytes_read = reader.buffer_position() as u64;
+        if bytes_read - last_reported >= PROGRESS_STEP_BYTES {
+            last_reported = bytes_read;
+            on_event(ParseEvent::Progress(ParseProgress {
+                bytes_read,
+                total_bytes,
+                nodes: nodes.len(),
+                edges: edges.len(),
+            }));
+        }
+    }
+
+    if !is_complete {
+        return Err("The file ends before </map>".to_string());
+    }
+    if !pending_nodes.is_empty() {
+        on_event(ParseEvent::NodeBatch(pending_nodes));
+    }
+    on_event(ParseEvent::Progress(ParseProgress {
+        bytes_read: total_bytes,
+        total_bytes,
+        nodes: nodes.len(),
+        edges: edges.len(),
+    }));
+
+    let neighbors = build_neighbors(&edges);
+
+    Ok((
+        nodes,
+        edges,
+        neighbors,
+        restrictions,
+        dangling_arcs,
+        invalid_attributes,
+    ))
+}
+
+// the class, speed limit and travel time of an arc, the values that cannot be read or
+// make no sense are left out and recorded in `invalid_attributes`
+fn optional_arc_attributes(
+    (from, to): (&str, &str),
+    [class, speed, time]: [Option<&str>; 3],
+    invalid_attributes: &mut Vec<InvalidAttribute>,
+) -> (Option<RoadClass>, Option<f32>, Option<f32>) {
+    let mut invalid = |name: &str, value: &str| {
+        invalid_attributes.push((
+            from.to_string(),
+            to.to_string(),
+            name.to_string(),
+            value.to_string(),
+        ));
+    };
+
+    let road_class = class.and_then(|class| {
+        let road_class = class.parse::<RoadClass>().ok();
+        if road_class.is_none() {
+            invalid("class", class);
+        }
+        road_class
+    });
+    // a speed of zero would make the arc take forever
+    let speed_limit = speed.and_then(|speed| {
+        let speed_limit = speed
+            .parse::<f32>()
+            .ok()
+            .filter(|s| s.is_finite() && *s > 0.);
+        if speed_limit.is_none() {
+            invalid("speed", speed);
+        }
+        speed_limit
+    });
+    let travel_time = time.and_then(|time| {
+        let travel_time = time
+            .parse::<f32>()
+            .ok()
+            .filter(|t| t.is_finite() && *t >= 0.);
+        if travel_time.is_none() {
+            invalid("time", time);
+        }
+        travel_time
+    });
+
+    (road_class, speed_limit, travel_time)
+}
+
+fn attribute(elem: &BytesStart, name: &str) -> Result<String, String> {
+    optional_attribute(elem, name)?.ok_or_else(|| {
+        format!(
+            "<{}> without the {} attribute",
+            String::from_utf8_lossy(elem.name().as_ref()),
+            name
+        )
+    })
+}
+
+fn optional_attribute(elem: &BytesStart, name: &str) -> Result<Option<String>, String> {
+    let attr = elem.try_get_attribute(name).map_err(|e| e.to_string())?;
+    attr.map(|attr| {
+        attr.unescape_value()
+            .map(|value| value.into_owned())
+            .map_err(|e| e.to_string())
+    })
+    .transpose()
+}
+
+fn number<T: FromStr>(elem: &BytesStart, name: &str) -> Result<T, String> {
+    parse(&attribute(elem, name)?, name)
+}
+
+fn parse<T: FromStr>(value: &str, name: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("Invalid {} \"{}\"", name, value))
+}
+
+pub fn build_neighbors(edges: &[Edge]) -> HashMap<Node, Vec<Edge>> {
     let mut neighbors: HashMap<Node, Vec<Edge>> = HashMap::new();
     edges.iter().for_each(|edge| {
         neighbors
@@ -57,5 +390,142 @@ pub fn parse_xml(data_buffer: &'static [u8]) -> (Vec<Node>, Vec<Edge>, HashMap<N
             .or_insert(vec![edge.clone()]);
     });
 
-    (nodes, edges, neighbors)
+    neighbors
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+
+    use super::*;
+    use crate::utils::constants::xml_data::TEST_XML;
+
+    // (id, position, longitude, latitude, label)
+    type NodeFields<'a> = (&'a str, Pos2, f64, f64, Option<&'a str>);
+
+    // arcs with the optional attributes, some of which cannot be read
+    const ARCS_XML: &[u8] = br#"<map><nodes>
+            <node id="0" longitude="4963454" latitude="621476"/>
+            <node id="1" longitude="4959493" latitude="614350"/>
+        </nodes><arcs>
+            <arc from="0" to="1" length="1000" class="residential"/>
+            <arc from="1" to="0" length="1000" speed="120" time="45"/>
+            <arc from="0" to="1" length="500" class="footway" speed="0" time="x"/>
+        </arcs><restrictions>
+            <restriction from="0" via="1" to="0"/>
+            <restriction from="1" via="0" to="1" penalty="30"/>
+        </restrictions></map>"#;
+
+    // nodes and arcs are equal when their ids are, every field is compared here
+    fn node_fields(node: &Node) -> NodeFields<'_> {
+        let label = node.label.as_deref();
+        (
+            &node.id,
+            node.position,
+            node.longitude,
+            node.latitude,
+            label,
+        )
+    }
+
+    // (from, to, length, class, speed, time)
+    fn edge_fields(
+        edge: &Edge,
+    ) -> (
+        NodeFields<'_>,
+        NodeFields<'_>,
+        f32,
+        Option<RoadClass>,
+        Option<f32>,
+        Option<f32>,
+    ) {
+        (
+            node_fields(&edge.from),
+            node_fields(&edge.to),
+            edge.length,
+            edge.road_class,
+            edge.speed_limit,
+            edge.travel_time,
+        )
+    }
+
+    #[test]
+    fn streaming_matches_dom_parser() {
+        for xml in [TEST_XML, ARCS_XML] {
+            let mut translator = Translator::default();
+            let (nodes, edges, neighbors, restrictions, dangling_arcs, invalid_attributes) =
+                parse_xml(xml, &mut translator);
+
+            let mut batched = Vec::new();
+            let (s_nodes, s_edges, s_neighbors, s_restrictions, s_dangling, s_invalid) =
+                parse_xml_streaming(xml, xml.len() as u64, &mut translator, |event| {
+                    if let ParseEvent::NodeBatch(batch) = event {
+                        batched.extend(batch);
+                    }
+                })
+                .unwrap();
+
+            let nodes = nodes.iter().map(node_fields).collect::<Vec<_>>();
+            assert_eq!(nodes, s_nodes.iter().map(node_fields).collect::<Vec<_>>());
+            assert_eq!(nodes, batched.iter().map(node_fields).collect::<Vec<_>>());
+            let edges = edges.iter().map(edge_fields).collect::<Vec<_>>();
+            assert_eq!(edges, s_edges.iter().map(edge_fields).collect::<Vec<_>>());
+            assert_eq!(restrictions, s_restrictions);
+            assert_eq!(dangling_arcs, s_dangling);
+            assert_eq!(invalid_attributes, s_invalid);
+            assert_eq!(neighbors.len(), s_neighbors.len());
+            for (node, node_edges) in &neighbors {
+                let s_node_edges = s_neighbors.get(node).unwrap();
+                assert_eq!(
+                    node_edges.iter().map(edge_fields).collect::<Vec<_>>(),
+                    s_node_edges.iter().map(edge_fields).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn optional_arc_attributes() {
+        let xml = ARCS_XML;
+
+        for (_, edges, _, restrictions, _, invalid_attributes) in [
+            parse_xml(xml, &mut Translator::default()),
+            parse_xml_streaming(xml, xml.len() as u64, &mut Translator::default(), |_| {}).unwrap(),
+        ] {
+            assert_eq!(edges[0].road_class, Some(RoadClass::Residential));
+            assert!((edges[0].duration() - 120.).abs() < 1e-3);
+            assert_eq!(edges[1].speed(), 120.);
+            assert_eq!(edges[1].duration(), 45.);
+            assert_eq!(restrictions.len(), 2);
+            assert_eq!(restrictions[0].penalty, None);
+            assert_eq!(restrictions[1].penalty, Some(30.));
+            // kept without the attributes that could not be read
+            assert_eq!(edges[2].road_class, None);
+            assert_eq!(edges[2].speed_limit, None);
+            assert_eq!(edges[2].travel_time, None);
+            let names = invalid_attributes
+                .iter()
+                .map(|(_, _, name, _)| name.as_str())
+                .collect::<Vec<&str>>();
+            assert_eq!(names, ["class", "speed", "time"]);
+        }
+    }
+
+    #[test]
+    fn malformed_files_are_errors() {
+        let parse = |xml: &[u8]| {
+            parse_xml_streaming(xml, xml.len() as u64, &mut Translator::default(), |_| {})
+        };
+
+        assert!(
+            parse(br#"<map><nodes><node id="0" longitude="1" latitude="x"/>"#)
+                .unwrap_err()
+                .contains("latitude")
+        );
+        assert!(parse(br#"<map><nodes><node longitude="1" latitude="2"/>"#).is_err());
+        assert!(parse(br#"<map><nodes></arcs></map>"#).is_err());
+        assert!(parse(b"").is_err());
+        // truncated between two elements
+        assert!(parse(br#"<map><nodes><node id="0" longitude="1" latitude="2"/>"#).is_err());
+    }
 }
@@ -0,0 +1,20 @@
+use super::{Projection, EARTH_RADIUS};
+
+/// Plate carrée: longitude and latitude are used as-is, scaled to meters on the equator.
+pub struct Equirectangular;
+
+impl Projection for Equirectangular {
+    fn forward(&self, longitude: f64, latitude: f64) -> (f64, f64) {
+        (
+            EARTH_RADIUS * longitude.to_radians(),
+            EARTH_RADIUS * latitude.to_radians(),
+        )
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            (x / EARTH_RADIUS).to_degrees(),
+            (y / EARTH_RADIUS).to_degrees(),
+        )
+    }
+}
@@ -0,0 +1,192 @@
+use super::Projection;
+
+// International 1924 (Hayford) ellipsoid
+const A: f64 = 6_378_388.0;
+const F: f64 = 1.0 / 297.0;
+
+// Luxembourg 1930 / Gauss: transverse Mercator centred on the country
+const LAT_0: f64 = 49.833_333_333_333_33;
+const LON_0: f64 = 6.166_666_666_666_667;
+const K_0: f64 = 1.0;
+const FALSE_EASTING: f64 = 80_000.0;
+const FALSE_NORTHING: f64 = 100_000.0;
+
+// Luxembourg 1930 -> WGS84, position vector convention (EPSG:1642)
+// (tx, ty, tz) in meters, (rx, ry, rz) in arc seconds, scale in ppm
+const TO_WGS84: [f64; 7] = [
+    -189.681, 18.3463, -42.7695, -0.33746, -3.09264, 2.53861, 0.4598,
+];
+
+// WGS84 ellipsoid, the input coordinates are expected in this datum
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// Luxembourg's national grid, LUREF (EPSG:2169).
+///
+/// Coordinates are shifted from WGS84 to the Luxembourg 1930 datum with a
+/// 7-parameter Helmert transformation before the transverse Mercator projection.
+pub struct Luref;
+
+impl Projection for Luref {
+    fn forward(&self, longitude: f64, latitude: f64) -> (f64, f64) {
+        let ecef = geodetic_to_ecef(longitude, latitude, WGS84_A, WGS84_F);
+        let ecef = helmert(ecef, true);
+        let (longitude, latitude) = ecef_to_geodetic(ecef, A, F);
+
+        transverse_mercator(longitude, latitude)
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> (f64, f64) {
+        let (longitude, latitude) = inverse_transverse_mercator(x, y);
+
+        let ecef = geodetic_to_ecef(longitude, latitude, A, F);
+        let ecef = helmert(ecef, false);
+        ecef_to_geodetic(ecef, WGS84_A, WGS84_F)
+    }
+}
+
+fn eccentricity_squared(f: f64) -> f64 {
+    f * (2.0 - f)
+}
+
+// distance along the central meridian from the equator to `phi`
+fn meridian_arc(phi: f64) -> f64 {
+    let e2 = eccentricity_squared(F);
+    let e4 = e2 * e2;
+    let e6 = e4 * e2;
+
+    A * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * phi
+        - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * phi).sin()
+        + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * phi).sin()
+        - (35.0 * e6 / 3072.0) * (6.0 * phi).sin())
+}
+
+// Snyder, "Map Projections: A Working Manual", p. 61
+fn transverse_mercator(longitude: f64, latitude: f64) -> (f64, f64) {
+    let e2 = eccentricity_squared(F);
+    let ep2 = e2 / (1.0 - e2);
+
+    let phi = latitude.to_radians();
+    let (sin_phi, cos_phi) = phi.sin_cos();
+
+    let n = A / (1.0 - e2 * sin_phi * sin_phi).sqrt();
+    let t = phi.tan().powi(2);
+    let c = ep2 * cos_phi * cos_phi;
+    let a = (longitude - LON_0).to_radians() * cos_phi;
+    let m = meridian_arc(phi);
+    let m_0 = meridian_arc(LAT_0.to_radians());
+
+    let x = K_0
+        * n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0);
+    let y = K_0
+        * (m - m_0
+            + n * phi.tan()
+                * (a * a / 2.0
+                    + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                    + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0));
+
+    (x + FALSE_EASTING, y + FALSE_NORTHING)
+}
+
+fn inverse_transverse_mercator(x: f64, y: f64) -> (f64, f64) {
+    let e2 = eccentricity_squared(F);
+    let e4 = e2 * e2;
+    let e6 = e4 * e2;
+    let ep2 = e2 / (1.0 - e2);
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+
+    let m = meridian_arc(LAT_0.to_radians()) + (y - FALSE_NORTHING) / K_0;
+    let mu = m / (A * (1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0));
+
+    // footpoint latitude
+    let phi_1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1 * e1 / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+    let (sin_phi_1, cos_phi_1) = phi_1.sin_cos();
+
+    let c_1 = ep2 * cos_phi_1 * cos_phi_1;
+    let t_1 = phi_1.tan().powi(2);
+    let n_1 = A / (1.0 - e2 * sin_phi_1 * sin_phi_1).sqrt();
+    let r_1 = A * (1.0 - e2) / (1.0 - e2 * sin_phi_1 * sin_phi_1).powf(1.5);
+    let d = (x - FALSE_EASTING) / (n_1 * K_0);
+
+    let phi = phi_1
+        - (n_1 * phi_1.tan() / r_1)
+            * (d * d / 2.0
+                - (5.0 + 3.0 * t_1 + 10.0 * c_1 - 4.0 * c_1 * c_1 - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t_1 + 298.0 * c_1 + 45.0 * t_1 * t_1
+                    - 252.0 * ep2
+                    - 3.0 * c_1 * c_1)
+                    * d.powi(6)
+                    / 720.0);
+    let lambda = (d - (1.0 + 2.0 * t_1 + c_1) * d.powi(3) / 6.0
+        + (5.0 - 2.0 * c_1 + 28.0 * t_1 - 3.0 * c_1 * c_1 + 8.0 * ep2 + 24.0 * t_1 * t_1)
+            * d.powi(5)
+            / 120.0)
+        / cos_phi_1;
+
+    (LON_0 + lambda.to_degrees(), phi.to_degrees())
+}
+
+fn geodetic_to_ecef(longitude: f64, latitude: f64, a: f64, f: f64) -> [f64; 3] {
+    let e2 = eccentricity_squared(f);
+    let (sin_phi, cos_phi) = latitude.to_radians().sin_cos();
+    let (sin_lambda, cos_lambda) = longitude.to_radians().sin_cos();
+    let n = a / (1.0 - e2 * sin_phi * sin_phi).sqrt();
+
+    [
+        n * cos_phi * cos_lambda,
+        n * cos_phi * sin_lambda,
+        n * (1.0 - e2) * sin_phi,
+    ]
+}
+
+fn ecef_to_geodetic([x, y, z]: [f64; 3], a: f64, f: f64) -> (f64, f64) {
+    let e2 = eccentricity_squared(f);
+    let p = (x * x + y * y).sqrt();
+
+    // a few fixed point iterations are plenty for points close to the surface
+    let mut phi = (z / (p * (1.0 - e2))).atan();
+    for _ in 0..5 {
+        let sin_phi = phi.sin();
+        let n = a / (1.0 - e2 * sin_phi * sin_phi).sqrt();
+        let h = p / phi.cos() - n;
+        phi = (z / (p * (1.0 - e2 * n / (n + h)))).atan();
+    }
+
+    (y.atan2(x).to_degrees(), phi.to_degrees())
+}
+
+// applies the datum shift, `to_local` goes from WGS84 to Luxembourg 1930
+// the small angle approximation allows inverting by negating the parameters
+fn helmert([x, y, z]: [f64; 3], to_local: bool) -> [f64; 3] {
+    let sign = if to_local { -1.0 } else { 1.0 };
+    let [tx, ty, tz, rx, ry, rz, s] = TO_WGS84.map(|p| p * sign);
+
+    let arcsec = std::f64::consts::PI / (180.0 * 3600.0);
+    let (rx, ry, rz) = (rx * arcsec, ry * arcsec, rz * arcsec);
+    let scale = 1.0 + s * 1e-6;
+
+    [
+        tx + scale * (x - rz * y + ry * z),
+        ty + scale * (rz * x + y - rx * z),
+        tz + scale * (-ry * x + rx * y + z),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_origin_maps_to_false_origin() {
+        let (x, y) = transverse_mercator(LON_0, LAT_0);
+
+        assert!((x - FALSE_EASTING).abs() < 1e-6);
+        assert!((y - FALSE_NORTHING).abs() < 1e-6);
+    }
+}
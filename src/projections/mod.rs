@@ -0,0 +1,76 @@
+mod equirectangular;
+mod luref;
+mod web_mercator;
+
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[allow(unused_imports)]
+pub use equirectangular::Equirectangular;
+#[allow(unused_imports)]
+pub use luref::Luref;
+#[allow(unused_imports)]
+pub use web_mercator::WebMercator;
+
+// WGS84 semi-major axis, the spherical projections use it as the earth radius
+pub const EARTH_RADIUS: f64 = 6_378_137.0;
+
+/// Maps geographic coordinates (in degrees) onto a plane (in meters) and back.
+pub trait Projection {
+    fn forward(&self, longitude: f64, latitude: f64) -> (f64, f64);
+
+    fn inverse(&self, x: f64, y: f64) -> (f64, f64);
+}
+
+#[derive(PartialEq, Eq, Hash, Sequence, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum ProjectionKind {
+    WebMercator,
+    Equirectangular,
+    Luref,
+}
+
+impl ProjectionKind {
+    pub fn projection(&self) -> &'static dyn Projection {
+        match self {
+            ProjectionKind::WebMercator => &WebMercator,
+            ProjectionKind::Equirectangular => &Equirectangular,
+            ProjectionKind::Luref => &Luref,
+        }
+    }
+}
+
+impl fmt::Display for ProjectionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectionKind::WebMercator => write!(f, "Web Mercator"),
+            ProjectionKind::Equirectangular => write!(f, "Equirectangular"),
+            ProjectionKind::Luref => write!(f, "LUREF (EPSG:2169)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use enum_iterator::all;
+
+    use super::ProjectionKind;
+
+    #[test]
+    fn inverse_undoes_forward() {
+        let points = [(6.13, 49.61), (5.75, 49.45), (6.52, 50.18), (0.0, 0.0)];
+
+        for kind in all::<ProjectionKind>() {
+            for (longitude, latitude) in points {
+                let (x, y) = kind.projection().forward(longitude, latitude);
+                let (lon, lat) = kind.projection().inverse(x, y);
+
+                assert!(
+                    (lon - longitude).abs() < 1e-7,
+                    "{kind}: {lon} != {longitude}"
+                );
+                assert!((lat - latitude).abs() < 1e-7, "{kind}: {lat} != {latitude}");
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use std::f64::consts::PI;
+
+use super::{Projection, EARTH_RADIUS};
+
+/// Spherical (pseudo) Mercator, EPSG:3857.
+pub struct WebMercator;
+
+impl Projection for WebMercator {
+    fn forward(&self, longitude: f64, latitude: f64) -> (f64, f64) {
+        let x = EARTH_RADIUS * longitude.to_radians();
+        let y = EARTH_RADIUS * (PI / 4.0 + latitude.to_radians() / 2.0).tan().ln();
+
+        (x, y)
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> (f64, f64) {
+        let longitude = (x / EARTH_RADIUS).to_degrees();
+        let latitude = (2.0 * (y / EARTH_RADIUS).exp().atan() - PI / 2.0).to_degrees();
+
+        (longitude, latitude)
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// What happened on a road, as seen by the search.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Incident {
+    Closed,
+    // the cost of the arc is multiplied by the factor
+    Slowed { factor: f32 },
+}
+
+impl Incident {
+    /// The cost of an arc under the incident, `None` when it cannot be used at all.
+    pub fn apply(&self, cost: f32) -> Option<f32> {
+        match self {
+            Incident::Closed => None,
+            Incident::Slowed { factor } => Some(cost * factor),
+        }
+    }
+
+    /// A slow-down has to keep the cost positive, the searches rely on it.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Incident::Closed => true,
+            Incident::Slowed { factor } => factor.is_finite() && *factor > 0.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScenarioIncident {
+    pub from: String,
+    pub to: String,
+    pub incident: Incident,
+}
+
+/// A set of incidents, saved apart from the map so it can be replayed on it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Scenario {
+    pub map: String,
+    pub incidents: Vec<ScenarioIncident>,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let scenario: Self = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+        if let Some(invalid) = scenario
+            .incidents
+            .iter()
+            .find(|scenario_incident| !scenario_incident.incident.is_valid())
+        {
+            return Err(format!(
+                "Arc {} -> {}: {:?} has to slow down by a positive factor",
+                invalid.from, invalid.to, invalid.incident
+            ));
+        }
+        Ok(scenario)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_factors_that_are_not_positive() {
+        let scenario = |factor: &str| {
+            Scenario::parse(&format!(
+                r#"{{"map": "test.xml", "incidents": [
+                    {{"from": "0", "to": "1", "incident": "Closed"}},
+                    {{"from": "1", "to": "2", "incident": {{"Slowed": {{"factor": {}}}}}}}
+                ]}}"#,
+                factor
+            ))
+        };
+
+        assert_eq!(scenario("1.5").unwrap().incidents.len(), 2);
+        assert!(scenario("0").is_err());
+        assert!(scenario("-2").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path, path::PathBuf};
+
+use crate::{
+    algorithms::ParameterValues,
+    contexts::{CostMetric, DataSource},
+    projections::ProjectionKind,
+    utils::constants::xml_data::{MAP2_XML, TEST_XML},
+};
+
+// key of the session in eframe's app storage
+pub const SESSION_KEY: &str = "session";
+// key of the cached runs, only stored when the session asks for it
+pub const RUNS_KEY: &str = "runs";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SessionMap {
+    Embedded(String),
+    File(PathBuf),
+}
+
+impl SessionMap {
+    pub fn test_data() -> Self {
+        SessionMap::Embedded("test.xml".to_string())
+    }
+
+    pub fn from_data_source(data_source: &DataSource) -> Self {
+        match data_source {
+            DataSource::Embedded(buf) if *buf == TEST_XML => SessionMap::test_data(),
+            DataSource::Embedded(_) => SessionMap::Embedded("map2.xml".to_string()),
+            DataSource::File(path) => SessionMap::File(path.clone()),
+        }
+    }
+
+    pub fn to_data_source(&self) -> DataSource {
+        match self {
+            SessionMap::Embedded(name) if name == "test.xml" => DataSource::Embedded(TEST_XML),
+            SessionMap::Embedded(_) => DataSource::Embedded(MAP2_XML),
+            SessionMap::File(path) => DataSource::File(path.clone()),
+        }
+    }
+}
+
+/// Everything needed to bring the application back to where the user left it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Session {
+    pub map: SessionMap,
+    pub projection: ProjectionKind,
+    pub start_node: Option<String>,
+    pub end_node: Option<String>,
+    #[serde(default)]
+    pub waypoints: Vec<String>,
+    // id of the algorithm, older sessions stored the same names under another key
+    #[serde(alias = "algorithm_type")]
+    pub algorithm: String,
+    #[serde(default)]
+    pub parameters: ParameterValues,
+    pub is_marking_passed_edges: bool,
+    // sessions saved before the cost metrics existed minimise the distance
+    #[serde(default)]
+    pub cost_metric: CostMetric,
+    #[serde(default = "default_weight")]
+    pub distance_weight: f32,
+    #[serde(default = "default_weight")]
+    pub time_weight: f32,
+    // minutes after midnight
+    #[serde(default = "default_departure")]
+    pub departure: u32,
+    #[serde(default)]
+    pub use_turn_restrictions: bool,
+    #[serde(default = "default_allow_u_turns")]
+    pub allow_u_turns: bool,
+    #[serde(default)]
+    pub persist_runs: bool,
+    pub zoom: f32,
+    pub pan: [f32; 2],
+    pub basemap: Option<PathBuf>,
+    pub basemap_opacity: f32,
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&contents).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+fn default_allow_u_turns() -> bool {
+    true
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+fn default_departure() -> u32 {
+    8 * 60
+}
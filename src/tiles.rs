@@ -0,0 +1,178 @@
+use std::{
+    f64::consts::PI,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use egui::ColorImage;
+use log::debug;
+
+// Web Mercator does not reach the poles, tiles stop at this latitude
+pub const MAX_TILE_LATITUDE: f64 = 85.051_128_78;
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct TileId {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileId {
+    pub fn new(z: u8, x: u32, y: u32) -> Self {
+        Self { z, x, y }
+    }
+
+    /// Finds the tile covering the given point at zoom level `z`.
+    pub fn at(longitude: f64, latitude: f64, z: u8) -> Self {
+        let tiles = (1u64 << z) as f64;
+        let latitude = latitude
+            .clamp(-MAX_TILE_LATITUDE, MAX_TILE_LATITUDE)
+            .to_radians();
+
+        let x = (longitude + 180.0) / 360.0 * tiles;
+        let y = (1.0 - (latitude.tan() + 1.0 / latitude.cos()).ln() / PI) / 2.0 * tiles;
+
+        let max = tiles as u32 - 1;
+        Self::new(
+            z,
+            (x.max(0.0) as u32).min(max),
+            (y.max(0.0) as u32).min(max),
+        )
+    }
+
+    /// (longitude, latitude) of the corners, clockwise from the north-west one.
+    pub fn corners(&self) -> [(f64, f64); 4] {
+        [
+            corner(self.z, self.x, self.y),
+            corner(self.z, self.x + 1, self.y),
+            corner(self.z, self.x + 1, self.y + 1),
+            corner(self.z, self.x, self.y + 1),
+        ]
+    }
+}
+
+fn corner(z: u8, x: u32, y: u32) -> (f64, f64) {
+    let tiles = (1u64 << z) as f64;
+    let longitude = x as f64 / tiles * 360.0 - 180.0;
+    let latitude = (PI * (1.0 - 2.0 * y as f64 / tiles))
+        .sinh()
+        .atan()
+        .to_degrees();
+
+    (longitude, latitude)
+}
+
+/// Where the basemap tiles are read from: a `z/x/y.png` directory tree or an MBTiles file.
+#[derive(Debug, Clone)]
+pub enum TileSource {
+    Directory {
+        path: PathBuf,
+        zoom_range: (u8, u8),
+    },
+    MbTiles {
+        path: PathBuf,
+        zoom_range: (u8, u8),
+        connection: Arc<Mutex<rusqlite::Connection>>,
+    },
+}
+
+impl TileSource {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if path.is_dir() {
+            return Ok(TileSource::Directory {
+                path: path.to_path_buf(),
+                zoom_range: directory_zoom_range(path),
+            });
+        }
+
+        let connection =
+            rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(|e| format!("Unable to open {}: {}", path.display(), e))?;
+        let zoom_range = connection
+            .query_row(
+                "SELECT MIN(zoom_level), MAX(zoom_level) FROM tiles",
+                [],
+                |row| Ok((row.get::<_, u8>(0)?, row.get::<_, u8>(1)?)),
+            )
+            .map_err(|e| format!("{} is not a valid MBTiles file: {}", path.display(), e))?;
+
+        Ok(TileSource::MbTiles {
+            path: path.to_path_buf(),
+            zoom_range,
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            TileSource::Directory { path, .. } | TileSource::MbTiles { path, .. } => path,
+        }
+    }
+
+    pub fn zoom_range(&self) -> (u8, u8) {
+        match self {
+            TileSource::Directory { zoom_range, .. } | TileSource::MbTiles { zoom_range, .. } => {
+                *zoom_range
+            }
+        }
+    }
+
+    /// Reads and decodes a tile, `None` if the source does not have it.
+    pub fn load(&self, tile: TileId) -> Option<ColorImage> {
+        let bytes = match self {
+            TileSource::Directory { path, .. } => {
+                ["png", "jpg", "jpeg"].iter().find_map(|ext| {
+                    fs::read(
+                        path.join(tile.z.to_string())
+                            .join(tile.x.to_string())
+                            .join(format!("{}.{}", tile.y, ext)),
+                    )
+                    .ok()
+                })?
+            }
+            TileSource::MbTiles { connection, .. } => {
+                // MBTiles rows follow the TMS scheme, counted from the south
+                let row = (1u32 << tile.z) - 1 - tile.y;
+                connection
+                    .lock()
+                    .unwrap()
+                    .query_row(
+                        "SELECT tile_data FROM tiles \
+                         WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                        (tile.z, tile.x, row),
+                        |row| row.get::<_, Vec<u8>>(0),
+                    )
+                    .ok()?
+            }
+        };
+
+        match image::load_from_memory(&bytes) {
+            Ok(image) => {
+                let image = image.to_rgba8();
+                let size = [image.width() as usize, image.height() as usize];
+                Some(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+            }
+            Err(e) => {
+                debug!("Unable to decode tile {:?}: {}", tile, e);
+                None
+            }
+        }
+    }
+}
+
+// the zoom levels are the names of the top level directories
+fn directory_zoom_range(path: &Path) -> (u8, u8) {
+    let levels = fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u8>().ok())
+                .collect::<Vec<u8>>()
+        })
+        .unwrap_or_default();
+
+    match (levels.iter().min(), levels.iter().max()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => (0, 19),
+    }
+}
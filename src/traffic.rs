@@ -0,0 +1,190 @@
+use std::{
+    collections::HashMap,
+    fs,
+    hash::{Hash, Hasher},
+    path::Path,
+};
+
+use crate::{
+    models::Edge,
+    utils::{unordered_hash, FloatOrd},
+};
+
+pub const SECONDS_PER_DAY: f32 = 86_400.;
+
+/// Travel times of arcs through the day, in equally long buckets starting at midnight.
+///
+/// Read from a CSV file with one `from,to,t0,t1,...` row per arc, the times being in
+/// seconds. A header row is allowed, lines starting with `#` are ignored.
+#[derive(Debug, Clone)]
+pub struct TrafficProfiles {
+    buckets: usize,
+    // from -> to -> travel time of each bucket
+    profiles: HashMap<String, HashMap<String, Vec<f32>>>,
+}
+
+impl TrafficProfiles {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut buckets = None;
+        let mut is_first_row = true;
+        let mut profiles: HashMap<String, HashMap<String, Vec<f32>>> = HashMap::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // a header only holds labels such as from,to,t0,...
+            let is_header = std::mem::replace(&mut is_first_row, false)
+                && line
+                    .split(',')
+                    .all(|field| field.trim().parse::<f64>().is_err());
+            if is_header {
+                continue;
+            }
+
+            let mut fields = line.split(',').map(str::trim);
+            let (Some(from), Some(to)) = (fields.next(), fields.next()) else {
+                return Err(format!("Line {}: expected from,to,times...", index + 1));
+            };
+            let times = fields
+                .map(|field| match field.parse::<f32>() {
+                    Ok(time) if time.is_finite() && time >= 0. => Ok(time),
+                    Ok(_) => Err(format!("invalid travel time {}", field)),
+                    Err(e) => Err(format!("{}: {}", field, e)),
+                })
+                .collect::<Result<Vec<f32>, String>>()
+                .map_err(|e| format!("Line {}: {}", index + 1, e))?;
+
+            let buckets = *buckets.get_or_insert(times.len());
+            if times.is_empty() || times.len() != buckets {
+                return Err(format!(
+                    "Line {}: expected {} travel times, found {}",
+                    index + 1,
+                    buckets,
+                    times.len()
+                ));
+            }
+            profiles
+                .entry(from.to_string())
+                .or_default()
+                .insert(to.to_string(), times);
+        }
+
+        match buckets {
+            Some(buckets) if !profiles.is_empty() => Ok(Self { buckets, profiles }),
+            _ => Err("No travel time profile found".to_string()),
+        }
+    }
+
+    pub fn arc_count(&self) -> usize {
+        self.profiles.values().map(HashMap::len).sum()
+    }
+
+    pub fn bucket_minutes(&self) -> f32 {
+        SECONDS_PER_DAY / 60. / self.buckets as f32
+    }
+
+    /// Travel time in seconds when entering the arc `at` seconds after midnight,
+    /// interpolated between the buckets. `None` for arcs without a profile.
+    pub fn travel_time(&self, edge: &Edge, at: f32) -> Option<f32> {
+        let times = self
+            .profiles
+            .get(edge.from.id.as_str())?
+            .get(edge.to.id.as_str())?;
+
+        let position = at.rem_euclid(SECONDS_PER_DAY) / SECONDS_PER_DAY * self.buckets as f32;
+        let bucket = (position as usize).min(self.buckets - 1);
+        let next = (bucket + 1) % self.buckets;
+        let fraction = position - bucket as f32;
+
+        Some(times[bucket] * (1. - fraction) + times[next] * fraction)
+    }
+
+    /// The shortest and the longest travel times of the arc through the day, in seconds.
+    pub fn travel_time_range(&self, edge: &Edge) -> Option<(f32, f32)> {
+        let times = self
+            .profiles
+            .get(edge.from.id.as_str())?
+            .get(edge.to.id.as_str())?;
+
+        Some(
+            times
+                .iter()
+                .fold((f32::INFINITY, 0.), |(fastest, slowest), time| {
+                    (fastest.min(*time), slowest.max(*time))
+                }),
+        )
+    }
+}
+
+// the same whatever order the arcs are stored in
+impl Hash for TrafficProfiles {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.buckets.hash(state);
+        let rows = self.profiles.iter().flat_map(|(from, profiles)| {
+            profiles.iter().map(move |(to, times)| {
+                let times = times.iter().map(|time| FloatOrd(*time)).collect::<Vec<_>>();
+                (from, to, times)
+            })
+        });
+        state.write_u64(unordered_hash(rows));
+    }
+}
+
+/// Formats seconds after midnight as `HH:MM`, wrapping over the next days.
+pub fn format_time_of_day(seconds: f32) -> String {
+    let minutes = (seconds.rem_euclid(SECONDS_PER_DAY) / 60.).round() as u32 % (24 * 60);
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_node;
+
+    #[test]
+    fn travel_times_are_interpolated() {
+        let profiles = TrafficProfiles::parse("from,to,t0,t1\n# comment\n0,1,60,120\n").unwrap();
+        let edge = Edge::new(test_node(0), test_node(1), 1000.);
+
+        assert_eq!(profiles.arc_count(), 1);
+        assert_eq!(profiles.bucket_minutes(), 720.);
+        assert_eq!(profiles.travel_time(&edge, 0.), Some(60.));
+        assert_eq!(profiles.travel_time(&edge, SECONDS_PER_DAY / 4.), Some(90.));
+        assert_eq!(
+            profiles.travel_time(&edge, SECONDS_PER_DAY / 2.),
+            Some(120.)
+        );
+        // wraps around midnight
+        assert_eq!(
+            profiles.travel_time(&edge, SECONDS_PER_DAY * 0.75),
+            Some(90.)
+        );
+        assert_eq!(
+            profiles.travel_time(&Edge::new(test_node(1), test_node(0), 1.), 0.),
+            None
+        );
+        assert_eq!(format_time_of_day(8.5 * 3600.), "08:30");
+    }
+
+    #[test]
+    fn rejects_corrupt_rows() {
+        // a first row holding numbers is data, not a header
+        assert!(TrafficProfiles::parse("0,1,60,abc\n1,0,60,120\n").is_err());
+        assert!(TrafficProfiles::parse("0,1,60,-5\n").is_err());
+        assert!(TrafficProfiles::parse("0,1,60,NaN\n").is_err());
+        assert!(TrafficProfiles::parse("0,1,60,inf\n").is_err());
+        assert_eq!(
+            TrafficProfiles::parse("0,1,60,120\n").unwrap().arc_count(),
+            1
+        );
+    }
+}
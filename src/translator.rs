@@ -1,28 +1,34 @@
-use egui::{mutex::Mutex, Pos2};
-use once_cell::sync::Lazy;
+use egui::Pos2;
 use std::collections::HashMap;
 
-use crate::utils::{constants::CANVAS_SIZE, WindowSize, HF64};
+use crate::{
+    projections::{ProjectionKind, EARTH_RADIUS},
+    utils::{constants::CANVAS_SIZE, WindowSize, HF64},
+};
 
 pub type TranslationArgs = (HF64, HF64); // longitude, latitude
 pub type TranslationResults = Pos2; // x, y
 
-pub static TRANSLATOR: Lazy<Mutex<Translator>> = Lazy::new(|| Mutex::new(Translator::default()));
-
 #[derive(Debug, Clone)]
 pub struct Translator {
     window_size: WindowSize,
+    projection: ProjectionKind,
     translation_cache: HashMap<TranslationArgs, TranslationResults>,
 }
 
 impl Translator {
-    pub fn new(window_size: WindowSize) -> Self {
+    pub fn new(window_size: WindowSize, projection: ProjectionKind) -> Self {
         Self {
             window_size,
+            projection,
             translation_cache: HashMap::new(),
         }
     }
 
+    pub fn projection(&self) -> ProjectionKind {
+        self.projection
+    }
+
     pub fn project(&mut self, longitude: f64, latitude: f64) -> Pos2 {
         let args = (HF64::new(longitude), HF64::new(latitude));
 
@@ -38,35 +44,35 @@ impl Translator {
         position_on_screen
     }
 
-    fn translate_coordinates(&self, longitude: f64, latitude: f64) -> Pos2 {
-        let lon_in_radians = degrees_to_radians(longitude + 180.0);
-        let lat_in_radians = degrees_to_radians(latitude);
+    /// Converts a position on the canvas back to (longitude, latitude).
+    pub fn unproject(&self, position: Pos2) -> (f64, f64) {
+        let scale = self.scale();
+        let x = (position.x as f64 - self.window_size.width as f64 / 2.0) / scale;
+        let y = (self.window_size.height as f64 / 2.0 - position.y as f64) / scale;
 
-        let radius = earth_radius(self.window_size.width as f64);
-        let x = lon_in_radians * radius;
+        self.projection.projection().inverse(x, y)
+    }
 
-        let vertical_offset = radius
-            * (std::f64::consts::PI / 4.0 + lat_in_radians / 2.0)
-            * (std::f64::consts::PI / 4.0 + lat_in_radians / 2.0)
-                .tan()
-                .ln();
+    /// Same as [`Translator::project`], without going through the cache.
+    pub fn translate_coordinates(&self, longitude: f64, latitude: f64) -> Pos2 {
+        let (x, y) = self.projection.projection().forward(longitude, latitude);
 
-        let y = self.window_size.height as f64 / 2.0 - vertical_offset;
+        // the canvas is centred on the projection origin and spans the equator horizontally
+        let scale = self.scale();
+        let x = self.window_size.width as f64 / 2.0 + x * scale;
+        let y = self.window_size.height as f64 / 2.0 - y * scale;
 
         Pos2::new(x as f32, y as f32)
     }
+
+    // canvas units per projected meter
+    fn scale(&self) -> f64 {
+        self.window_size.width as f64 / (2.0 * std::f64::consts::PI * EARTH_RADIUS)
+    }
 }
 
 impl Default for Translator {
     fn default() -> Self {
-        Translator::new(WindowSize::from(CANVAS_SIZE))
+        Translator::new(WindowSize::from(CANVAS_SIZE), ProjectionKind::WebMercator)
     }
 }
-
-fn earth_radius(circumference: f64) -> f64 {
-    circumference / (2.0 * std::f64::consts::PI)
-}
-
-fn degrees_to_radians(degrees: f64) -> f64 {
-    degrees * std::f64::consts::PI / 180.0
-}
@@ -1,13 +1,71 @@
-use egui::{Pos2, Vec2};
+use egui::{Color32, Pos2};
+use std::time::Duration;
 
 pub const CANVAS_SIZE: Pos2 = Pos2::new(1366., 900.);
 
-pub const MIN_PAN: Vec2 = Vec2::new(-1000., -1000.);
-pub const MAX_PAN: Vec2 = Vec2::new(1000., 1000.);
-pub const MAX_ZOOM: f32 = 1000.0;
+// how much of the viewport the map takes when fitted to its bounds
+pub const FIT_MARGIN: f32 = 0.9;
+// how far in the user can zoom, relative to the zoom that fits the whole map
+pub const MAX_ZOOM_FACTOR: f32 = 100.0;
+pub const MIN_ZOOM_FACTOR: f32 = 0.5;
 
-pub const DEFAULT_ZOOM: f32 = 100.0;
-pub const DEFAULT_PAN: Vec2 = Vec2::new(-700., -179.);
+// zoom is multiplied by exp(scroll * ZOOM_SCROLL_SPEED), scroll being in points
+pub const ZOOM_SCROLL_SPEED: f32 = 0.003;
+pub const KEYBOARD_ZOOM_STEP: f32 = 1.5;
+// fraction of the viewport moved by one arrow key press
+pub const KEYBOARD_PAN_STEP: f32 = 0.2;
+
+// rate (1/s) at which animated zoom and pan converge on their target
+pub const VIEW_ANIMATION_SPEED: f32 = 12.0;
+// rate (1/s) at which the kinetic pan slows down after a drag
+pub const PAN_FRICTION: f32 = 4.0;
+// screen points per second under which the kinetic pan stops
+pub const MIN_FLING_SPEED: f32 = 20.0;
+
+// how the graph is drawn, shared by the screen and the exported images
+pub const EDGE_WIDTH: f32 = 0.5;
+pub const PASSED_EDGE_WIDTH: f32 = 1.0;
+pub const PASSED_EDGE_COLOR: Color32 = Color32::GREEN;
+pub const ROUTE_EDGE_WIDTH: f32 = 2.0;
+pub const ROUTE_EDGE_COLOR: Color32 = Color32::RED;
+pub const INCIDENT_EDGE_WIDTH: f32 = 2.0;
+pub const CLOSED_EDGE_COLOR: Color32 = Color32::from_rgb(255, 0, 255);
+pub const SLOWED_EDGE_COLOR: Color32 = Color32::from_rgb(255, 165, 0);
+pub const NODE_RADIUS: f32 = 0.5;
+pub const SELECTED_NODE_RADIUS: f32 = 3.0;
+pub const SELECTED_NODE_COLOR: Color32 = Color32::RED;
+// nodes the route has to go through
+pub const WAYPOINT_NODE_COLOR: Color32 = Color32::from_rgb(255, 200, 0);
+// results listed by the node search
+pub const MAX_NODE_MATCHES: usize = 20;
+// added to the width of the most central arcs of the heatmap
+pub const HEAT_EDGE_EXTRA_WIDTH: f32 = 2.0;
+// arcs of the negative cycle found instead of a route
+pub const NEGATIVE_CYCLE_COLOR: Color32 = Color32::from_rgb(220, 0, 220);
+// spanning and Steiner trees are drawn over the map with these
+pub const TREE_EDGE_WIDTH: f32 = 3.0;
+pub const TREE_EDGE_COLOR: Color32 = Color32::from_rgb(0, 190, 120);
+pub const TERMINAL_NODE_RADIUS: f32 = 4.0;
+// how often the progress of a background search is redrawn
+pub const SEARCH_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+// arcs the A* heuristic is inconsistent on
+pub const HEURISTIC_VIOLATION_WIDTH: f32 = 3.0;
+pub const HEURISTIC_VIOLATION_COLOR: Color32 = Color32::from_rgb(255, 140, 0);
+// what is being edited is highlighted with these
+pub const EDIT_HIGHLIGHT_WIDTH: f32 = 3.0;
+pub const EDIT_HIGHLIGHT_COLOR: Color32 = Color32::LIGHT_BLUE;
+// how close to an arc, in screen points, a click has to be to pick it
+pub const ARC_PICK_DISTANCE: f32 = 6.0;
+// issues listed by the validation report, the counts include the others
+pub const MAX_LISTED_ISSUES: usize = 200;
+// upper bound for the side of an exported image, in pixels
+pub const MAX_EXPORT_SIZE: u32 = 16_384;
+
+// slippy map tiles are square images of this size, in pixels
+pub const TILE_SIZE: f32 = 256.0;
+// caps the number of tiles drawn at once, a lower zoom level is used above it
+pub const MAX_VISIBLE_TILES: usize = 256;
+pub const MAX_CACHED_TILES: usize = 1024;
 
 // these are the bounds of the Luxembourg
 // they are here only for historical reasons
@@ -1,5 +1,7 @@
 use egui::Pos2;
 
+use crate::projections::EARTH_RADIUS;
+
 pub fn euclidean_distance(a: &Pos2, b: &Pos2) -> f32 {
     (*a - *b).length()
 }
@@ -9,3 +11,24 @@ pub fn manhattan_distance(a: &Pos2, b: &Pos2) -> f32 {
     let dy = (a.y - b.y).abs();
     dx + dy
 }
+
+pub fn distance_to_segment(point: Pos2, from: Pos2, to: Pos2) -> f32 {
+    let segment = to - from;
+    let length_sq = segment.length_sq();
+    if length_sq == 0.0 {
+        return point.distance(from);
+    }
+
+    let t = ((point - from).dot(segment) / length_sq).clamp(0.0, 1.0);
+    point.distance(from + segment * t)
+}
+
+/// Great-circle distance in meters between two (longitude, latitude) points.
+pub fn haversine_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lon_a, lat_a) = (a.0.to_radians(), a.1.to_radians());
+    let (lon_b, lat_b) = (b.0.to_radians(), b.1.to_radians());
+
+    let h = ((lat_b - lat_a) / 2.0).sin().powi(2)
+        + lat_a.cos() * lat_b.cos() * ((lon_b - lon_a) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * h.sqrt().asin()
+}
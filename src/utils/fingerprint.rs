@@ -0,0 +1,16 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// Hash of a collection that does not depend on the order of its items, e.g. a `HashMap`.
+pub fn unordered_hash<T: Hash>(items: impl IntoIterator<Item = T>) -> u64 {
+    items
+        .into_iter()
+        .map(|item| {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            hasher.finish()
+        })
+        .fold(0, u64::wrapping_add)
+}
@@ -0,0 +1,72 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Keeps the values used last, evicting the least recently used one past the capacity.
+pub struct LruCache<K, V> {
+    capacity: usize,
+    // bumped on every use, the entry used last has the highest tick
+    clock: u64,
+    entries: HashMap<K, (u64, V)>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            clock: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.0 = self.clock;
+        Some(&entry.1)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.clock += 1;
+        self.entries.insert(key, (self.clock, value));
+        while self.entries.len() > self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (tick, _))| *tick)
+                .map(|(key, _)| key.clone())
+                .unwrap();
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The entries from the least to the most recently used.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(_, (tick, _))| *tick);
+        entries.into_iter().map(|(key, (_, value))| (key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_the_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        cache.insert("c", 3);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.iter().collect::<Vec<_>>(), [(&"a", &1), (&"c", &3)]);
+    }
+}
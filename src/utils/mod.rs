@@ -2,18 +2,30 @@ pub mod constants;
 
 mod distance;
 mod extensions;
+mod fingerprint;
 mod frame_history;
 mod hashable_float;
+mod lru_cache;
 mod orderable_float;
+#[cfg(test)]
+mod testing;
 mod window_utils;
 
 #[allow(unused_imports)]
-pub use distance::{euclidean_distance, manhattan_distance};
+pub use distance::{
+    distance_to_segment, euclidean_distance, haversine_distance, manhattan_distance,
+};
+#[allow(unused_imports)]
+pub use fingerprint::unordered_hash;
 #[allow(unused_imports)]
 pub use frame_history::FrameHistory;
 #[allow(unused_imports)]
 pub use hashable_float::HF64;
 #[allow(unused_imports)]
+pub use lru_cache::LruCache;
+#[allow(unused_imports)]
 pub use orderable_float::FloatOrd;
+#[cfg(test)]
+pub use testing::{test_graph, test_node, wait_for_route};
 #[allow(unused_imports)]
 pub use window_utils::WindowSize;
@@ -8,10 +8,12 @@ use core::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
 use core::hash::{Hash, Hasher};
 use core::mem::transmute;
 use core::ops::{Add, Div, Mul, Sub};
+use serde::{Deserialize, Serialize};
 
 /// A wrapper for floats, that implements total equality and ordering
 /// and hashing.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 #[repr(transparent)]
 pub struct FloatOrd<T>(pub T);
 
@@ -0,0 +1,38 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use crate::{
+    contexts::AlgorithmContext,
+    models::{Edge, Node},
+};
+
+/// A node named after its index, at the origin.
+pub fn test_node(id: usize) -> Node {
+    Node::new(id.to_string(), egui::Pos2::ZERO, 0., 0.)
+}
+
+/// The nodes `0..node_count` and the arcs `(from, to, length)` between them.
+pub fn test_graph(node_count: usize, arcs: &[(usize, usize, f32)]) -> (Vec<Node>, Vec<Edge>) {
+    let nodes = (0..node_count).map(test_node).collect::<Vec<Node>>();
+    let edges = arcs
+        .iter()
+        .map(|&(from, to, length)| Edge::new(nodes[from].clone(), nodes[to].clone(), length))
+        .collect();
+
+    (nodes, edges)
+}
+
+/// Searches the route in the background as the UI does, receiving it once it has been found.
+pub fn wait_for_route(
+    algorithm_ctx: &mut AlgorithmContext,
+    from: &Node,
+    to: &Node,
+    neighbors: &Arc<HashMap<Node, Vec<Edge>>>,
+) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _guard = runtime.enter();
+    algorithm_ctx.request_path(from, to, neighbors, &egui::Context::default());
+    // a cached route is received right away, nothing is searched for the same arguments
+    while !algorithm_ctx.receive_path() && algorithm_ctx.is_searching() {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}